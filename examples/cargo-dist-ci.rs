@@ -1,4 +1,7 @@
-use grackle::{Job, JobStep, PullRequest, Push, Strategy, Workflow};
+use grackle::{
+    actions::{Checkout, DownloadArtifact, RustCache, Submodules, UploadArtifact},
+    Job, JobStep, PullRequest, Push, Strategy, Workflow,
+};
 
 fn main() {
     let wf = Workflow::builder()
@@ -13,10 +16,7 @@ fn main() {
             .output("tag-flag", "${{ !github.event.pull_request && format('--tag={0}', github.ref_name) || '' }}")
             .output("publishing", "${{ !github.event.pull_request }}")
             .env_var("GH_TOKEN", "${{ secrets.GITHUB_TOKEN }}")
-            .add_step(JobStep::builder()
-                .uses("actions/checkout@v4")
-                .with("submodules", "recursive")
-                .build())
+            .add_step(Checkout::builder().submodules(Submodules::Recursive).build())
             .add_step(JobStep::builder()
                 .name("Install cargo-dist")
                 .shell("bash")
@@ -29,11 +29,10 @@ echo "cargo dist ran successfully"
 cat plan-dist-manifest.json
 echo "manifest=$(jq -c "." plan-dist-manifest.json)" >> "$GITHUB_OUTPUT""#)
                 .build())
-            .add_step(JobStep::builder()
-                .name("Upload dist-manifest.json")
-                .uses("actions/upload-artifact@v4")
-                .with("name", "artifacts-plan-dist-manifest")
-                .with("path", "plan-dist-manifest.json")
+            .add_step(UploadArtifact::builder()
+                .step_name("Upload dist-manifest.json")
+                .name("artifacts-plan-dist-manifest")
+                .path("plan-dist-manifest.json")
                 .build())
             .build())
         .add_job("build-local-artifacts", Job::builder()
@@ -51,24 +50,17 @@ echo "manifest=$(jq -c "." plan-dist-manifest.json)" >> "$GITHUB_OUTPUT""#)
                 .name("enable windows longpaths")
                 .run("git config --global core.longpaths true")
                 .build())
-            .add_step(JobStep::builder()
-                .uses("actions/checkout@v4")
-                .with("submodules", "recursive")
-                .build())
-            .add_step(JobStep::builder()
-                .uses("swatinem/rust-cache@v2")
-                .with("key", "${{ join(matrix.targets, '-') }}")
-                .build())
+            .add_step(Checkout::builder().submodules(Submodules::Recursive).build())
+            .add_step(RustCache::builder().key("${{ join(matrix.targets, '-') }}").build())
             .add_step(JobStep::builder()
                 .name("Install cargo-dist")
                 .run("${{ matrix.install_dist }}")
                 .build())
-            .add_step(JobStep::builder()
-                .name("Fetch local artifacts")
-                .uses("actions/download-artifact@v4")
-                .with("pattern", "artifacts-*")
-                .with("path", "target/distrib/")
-                .with("merge-multiple", true)
+            .add_step(DownloadArtifact::builder()
+                .step_name("Fetch local artifacts")
+                .pattern("artifacts-*")
+                .path("target/distrib/")
+                .merge_multiple(true)
                 .build())
             .add_step(JobStep::builder().name("Install dependencies").run("${{ matrix.packages_install }}").build())
             .add_step(JobStep::builder()
@@ -87,11 +79,10 @@ jq --raw-output ".upload_files[]" dist-manifest.json >> "$GITHUB_OUTPUT"
 echo "EOF" >> "$GITHUB_OUTPUT"
 cp dist-manifest.json "$BUILD_MANIFEST_NAME""#)
                 .build())
-            .add_step(JobStep::builder()
-                .name("Upload artifacts")
-                .uses("actions/upload-artifact@v4")
-                .with("name", "artifacts-build-local-${{ join(matrix.targets, '_') }}")
-                .with("path", r#"${{ steps.cargo-dist.outputs.paths }}
+            .add_step(UploadArtifact::builder()
+                .step_name("Upload artifacts")
+                .name("artifacts-build-local-${{ join(matrix.targets, '_') }}")
+                .path(r#"${{ steps.cargo-dist.outputs.paths }}
 ${{ env.BUILD_MANIFEST_NAME }}"#)
                 .build())
             .build())
@@ -101,16 +92,12 @@ ${{ env.BUILD_MANIFEST_NAME }}"#)
             .runs_on("ubuntu-20.04")
             .env_var("GH_TOKEN", "${{ secrets.GITHUB_TOKEN }}")
             .env_var("BUILD_MANIFEST_NAME", "target/distrib/global-dist-manifest.json")
-            .add_step(JobStep::builder()
-                .uses("actions/checkout@v4")
-                .with("submodules", "recursive")
-                .build())
-            .add_step(JobStep::builder()
-                .name("Install cargo-dist")
-                .uses("actions/download-artifact@v4")
-                .with("pattern", "artifacts-*")
-                .with("path", "target/distrib/")
-                .with("merge-multiple", true)
+            .add_step(Checkout::builder().submodules(Submodules::Recursive).build())
+            .add_step(DownloadArtifact::builder()
+                .step_name("Install cargo-dist")
+                .pattern("artifacts-*")
+                .path("target/distrib/")
+                .merge_multiple(true)
                 .build())
             .add_step(JobStep::builder()
                 .id("cargo-dist")
@@ -123,13 +110,12 @@ jq --raw-output ".upload_files[]" dist-manifest.json >> "$GITHUB_OUTPUT"
 echo "EOF" >> "$GITHUB_OUTPUT"
 cp dist-manifest.json "$BUILD_MANIFEST_NAME""#)
                 .build())
-            .add_step(JobStep::builder()
-                .name("Upload artifacts")
-                .uses("actions/upload-artifact@v4")
-                .with("name", "artifacts-build-global")
-                .with("path", "${{ steps.cargo-dist.outputs.paths }}
+            .add_step(UploadArtifact::builder()
+                .step_name("Upload artifacts")
+                .name("artifacts-build-global")
+                .path("${{ steps.cargo-dist.outputs.paths }}
 ${{ env.BUILD_MANIFEST_NAME }}")
-               .build())
+                .build())
             .build())
         .add_job("host", Job::builder()
             .name("host")
@@ -140,20 +126,16 @@ ${{ env.BUILD_MANIFEST_NAME }}")
             .env_var("GH_TOKEN", "${{ secrets.GITHUB_TOKEN }}")
             .runs_on("ubuntu-20.04")
             .output("val", "${{ steps.host.outputs.manifest }}")
-            .add_step(JobStep::builder()
-                .uses("actions/checkout@v4")
-                .with("submodules", "recursive")
-                .build())
+            .add_step(Checkout::builder().submodules(Submodules::Recursive).build())
             .add_step(JobStep::builder()
                 .name("Install cargo-dist")
                 .run("curl --proto '=https' --tlsv1.2 -LsSf https://github.com/axodotdev/cargo-dist/releases/download/v0.14.1/cargo-dist-installer.sh | sh")
                 .build())
-            .add_step(JobStep::builder()
-                .name("Fetch artifacts")
-                .uses("actions/download-artifact@v4")
-                .with("pattern", "artifacts-*")
-                .with("path", "target/distrib/")
-                .with("merge-multiple", true)
+            .add_step(DownloadArtifact::builder()
+                .step_name("Fetch artifacts")
+                .pattern("artifacts-*")
+                .path("target/distrib/")
+                .merge_multiple(true)
                 .build())
             .add_step(JobStep::builder()
                 .id("host")
@@ -163,11 +145,10 @@ echo "artifacts uploaded and released successfully"
 cat dist-manifest.json
 echo "manifest=$(jq -c "." dist-manifest.json)" >> "$GITHUB_OUTPUT""#)
                 .build())
-            .add_step(JobStep::builder()
-                .name("Upload dist-manifest.json")
-                .uses("actions/upload-artifact@v4")
-                .with("name", "artifacts-dist-manifest")
-                .with("path", "dist-manifest.json")
+            .add_step(UploadArtifact::builder()
+                .step_name("Upload dist-manifest.json")
+                .name("artifacts-dist-manifest")
+                .path("dist-manifest.json")
                 .build())
             .build())
         .add_job(
@@ -180,17 +161,15 @@ echo "manifest=$(jq -c "." dist-manifest.json)" >> "$GITHUB_OUTPUT""#)
             .env_var("GITHUB_USER", "axo bot")
             .env_var("GITHUB_EMAIL", "admin+bot@axo.dev")
             .if_cond("${{ !fromJson(needs.plan.outputs.val).announcement_is_prerelease || fromJson(needs.plan.outputs.val).publish_prereleases }}")
-            .add_step(JobStep::builder()
-                .uses("actions/checkout@v4")
-                .with("token", "freemasen/homebrew-tap")
-                .with("repository", "${{ secrets.HOMEBREW_TAP_TOKEN }}")
+            .add_step(Checkout::builder()
+                .repository("freemasen/homebrew-tap")
+                .token("${{ secrets.HOMEBREW_TAP_TOKEN }}")
                 .build())
-            .add_step(JobStep::builder()
-                .name("Fetch homebrew formulae")
-                .uses("actions/download-artifact@v4")
-                .with("pattern", "artifacts-*")
-                .with("path", "Formula/")
-                .with("merge-multiple", true)
+            .add_step(DownloadArtifact::builder()
+                .step_name("Fetch homebrew formulae")
+                .pattern("artifacts-*")
+                .path("Formula/")
+                .merge_multiple(true)
                 .build())
             .add_step(JobStep::builder()
                 .name("Commit formula files")
@@ -213,16 +192,12 @@ git push"#)
             .if_cond("${{ always() && needs.host.result == 'success' && (needs.publish-homebrew-formula.result == 'skipped' || needs.publish-homebrew-formula.result == 'success') }}")
             .runs_on("ubuntu-20.04")
             .env_var("GH_TOKEN", "${{ secrets.GITHUB_TOKEN }}")
-            .add_step(JobStep::builder()
-                .uses("actions/checkout@v4")
-                .with("submodules", "recursive")
-                .build())
-            .add_step(JobStep::builder()
-                .name("Download GitHub Artifacts")
-                .uses("actions/download-artifact@v4")
-                .with("pattern", "artifacts-*")
-                .with("path", "artifacts")
-                .with("merge-multiple:", true)
+            .add_step(Checkout::builder().submodules(Submodules::Recursive).build())
+            .add_step(DownloadArtifact::builder()
+                .step_name("Download GitHub Artifacts")
+                .pattern("artifacts-*")
+                .path("artifacts")
+                .merge_multiple(true)
                 .build())
             .add_step(JobStep::builder()
                 .name("Cleanup")
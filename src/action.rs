@@ -0,0 +1,308 @@
+//! The metadata of an action, its `action.yml`
+
+use crate::{lenient_bool, JobStep};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use serde_yml::Value;
+use std::{collections::BTreeMap, path::Path};
+use typed_builder::TypedBuilder;
+
+/// The file names an action's metadata may be read from, in order of
+/// preference
+pub const METADATA_FILE_NAMES: &[&str] = &["action.yml", "action.yaml"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
+#[builder(mutators(
+    pub fn input(&mut self, name: impl ToString, input: ActionInput) {
+        self.inputs.insert(name.to_string(), input);
+    }
+    pub fn output(&mut self, name: impl ToString, output: ActionOutput) {
+        self.outputs.insert(name.to_string(), output);
+    }
+))]
+pub struct Action {
+    #[builder(setter(into))]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default, setter(strip_option, into))]
+    pub author: Option<String>,
+    #[serde(default)]
+    #[builder(default, setter(into))]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub inputs: IndexMap<String, ActionInput>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub outputs: IndexMap<String, ActionOutput>,
+    pub runs: Runs,
+    #[serde(default, skip_serializing_if = "Branding::is_empty")]
+    #[builder(default)]
+    pub branding: Branding,
+}
+
+impl Action {
+    /// Read the `action.yml` (or `action.yaml`) in `dir`
+    pub fn load_dir(dir: impl AsRef<Path>) -> std::io::Result<Self> {
+        let dir = dir.as_ref();
+        let path = METADATA_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no action.yml in {}", dir.display()),
+                )
+            })?;
+        let text = std::fs::read_to_string(path)?;
+        serde_yml::from_str(&text)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[serde(rename_all = "camelCase")]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct ActionInput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_bool",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    #[builder(setter(!strip_option))]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+    /// Setting this marks the input as deprecated, the message is logged
+    /// when the input is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct ActionOutput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The expression the output is mapped from, only for composite actions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+/// How the action is shown on the GitHub Marketplace
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct Branding {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl Branding {
+    pub fn is_empty(&self) -> bool {
+        self.icon.is_none() && self.color.is_none()
+    }
+}
+
+/// How the action runs, keyed by `using`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "using")]
+pub enum Runs {
+    #[serde(rename = "composite")]
+    Composite(CompositeRuns),
+    #[serde(rename = "node24")]
+    Node24(NodeRuns),
+    #[serde(rename = "node20")]
+    Node20(NodeRuns),
+    /// No longer supported by GitHub but still found in older actions
+    #[serde(rename = "node16")]
+    Node16(NodeRuns),
+    /// No longer supported by GitHub but still found in older actions
+    #[serde(rename = "node12")]
+    Node12(NodeRuns),
+    #[serde(rename = "docker")]
+    Docker(DockerRuns),
+}
+
+impl Runs {
+    pub fn composite(steps: impl IntoIterator<Item = JobStep>) -> Self {
+        Self::Composite(CompositeRuns {
+            steps: steps.into_iter().collect(),
+        })
+    }
+
+    /// The `using` value
+    pub fn using(&self) -> &'static str {
+        match self {
+            Runs::Composite(_) => "composite",
+            Runs::Node24(_) => "node24",
+            Runs::Node20(_) => "node20",
+            Runs::Node16(_) => "node16",
+            Runs::Node12(_) => "node12",
+            Runs::Docker(_) => "docker",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[builder(mutators(
+    pub fn add_step(&mut self, step: impl Into<JobStep>) {
+        self.steps.push(step.into());
+    }
+))]
+pub struct CompositeRuns {
+    #[serde(default)]
+    #[builder(via_mutators(init = Default::default()))]
+    pub steps: Vec<JobStep>,
+}
+
+/// A JavaScript action, `pre` and `post` run before and after every job that
+/// uses the action when their condition is met
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[serde(rename_all = "kebab-case")]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct NodeRuns {
+    #[builder(!default, setter(!strip_option))]
+    pub main: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre: Option<String>,
+    /// Defaults to `always()`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_if: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post: Option<String>,
+    /// Defaults to `always()`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_if: Option<String>,
+}
+
+/// A container action, `image` is either a `Dockerfile` in the action's
+/// repository or a `docker://` image
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[serde(rename_all = "kebab-case")]
+#[builder(
+    field_defaults(default, setter(strip_option, into)),
+    mutators(
+        pub fn arg(&mut self, arg: impl ToString) {
+            self.args.push(arg.to_string());
+        }
+        pub fn env(&mut self, key: impl ToString, value: impl ToString) {
+            self.env.insert(key.to_string(), value.to_string());
+        }
+    )
+)]
+pub struct DockerRuns {
+    #[builder(!default, setter(!strip_option))]
+    pub image: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub args: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_entrypoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_if: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_entrypoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_if: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub env: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composite_builder() {
+        let action = Action::builder()
+            .name("setup")
+            .description("Install the toolchain and cache dependencies")
+            .author("octo-org")
+            .input(
+                "toolchain",
+                ActionInput::builder()
+                    .description("The rust toolchain")
+                    .default("stable")
+                    .build(),
+            )
+            .input(
+                "cache",
+                ActionInput::builder()
+                    .deprecation_message("caching is always enabled")
+                    .build(),
+            )
+            .output(
+                "version",
+                ActionOutput::builder()
+                    .value("${{ steps.toolchain.outputs.version }}")
+                    .build(),
+            )
+            .runs(Runs::composite([
+                JobStep::builder()
+                    .id("toolchain")
+                    .run("rustup toolchain install ${{ inputs.toolchain }}")
+                    .shell("bash")
+                    .build(),
+                JobStep::builder().uses("Swatinem/rust-cache@v2").build(),
+            ]))
+            .branding(Branding::builder().icon("package").color("orange").build())
+            .build();
+        insta::assert_yaml_snapshot!(action);
+    }
+
+    #[test]
+    fn parse_actions() {
+        let node: Action = serde_yml::from_str(
+            r#"name: checkout
+description: Checkout a Git repository at a particular version
+inputs:
+  repository:
+    description: Repository name with owner
+    default: ${{ github.repository }}
+  token:
+    required: 'true'
+runs:
+  using: node20
+  main: dist/index.js
+  post: dist/index.js
+  post-if: success()
+"#,
+        )
+        .unwrap();
+        assert!(node.inputs["token"].required);
+        let Runs::Node20(runs) = &node.runs else {
+            panic!("expected node20, found {}", node.runs.using());
+        };
+        assert_eq!(runs.post_if.as_deref(), Some("success()"));
+
+        let docker: Action = serde_yml::from_str(
+            r#"name: lint
+description: Run a linter
+runs:
+  using: docker
+  image: docker://ghcr.io/octo-org/lint:1
+  args: ["--strict", "${{ inputs.path }}"]
+  entrypoint: /lint
+  env:
+    MODE: ci
+"#,
+        )
+        .unwrap();
+        let Runs::Docker(runs) = &docker.runs else {
+            panic!("expected docker, found {}", docker.runs.using());
+        };
+        assert_eq!(runs.args, ["--strict", "${{ inputs.path }}"]);
+        assert_eq!(runs.env["MODE"], "ci");
+        let emitted = serde_yml::to_string(&docker).unwrap();
+        let reparsed: Action = serde_yml::from_str(&emitted).unwrap();
+        assert_eq!(reparsed.runs.using(), "docker");
+    }
+}
@@ -0,0 +1,146 @@
+use super::{lines, step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `actions/upload-artifact`, upload files to be downloaded by later jobs or
+/// from the workflow run
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(
+    field_defaults(default, setter(strip_option, into)),
+    mutators(
+        /// A file, directory or glob to upload, can be repeated and
+        /// prefixed with `!` to exclude paths
+        pub fn path(&mut self, path: impl ToString) {
+            self.path.push(path.to_string());
+        }
+    )
+)]
+pub struct UploadArtifact {
+    /// The version of the action to use
+    #[builder(default = "v4".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub step_name: Option<String>,
+    pub if_cond: Option<String>,
+    /// The artifact name, defaults to `artifact`
+    pub name: Option<String>,
+    #[builder(via_mutators(init = Default::default()))]
+    pub path: Vec<String>,
+    pub if_no_files_found: Option<IfNoFilesFound>,
+    /// Days after which the artifact expires, between 1 and 90
+    #[builder(setter(!into))]
+    pub retention_days: Option<u32>,
+    /// The zlib compression level, between 0 and 9
+    #[builder(setter(!into))]
+    pub compression_level: Option<u8>,
+    /// Delete an existing artifact with the same name before uploading
+    pub overwrite: Option<bool>,
+    /// Whether to include hidden files in the artifact
+    pub include_hidden_files: Option<bool>,
+}
+
+/// What to do when `path` doesn't match any files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum IfNoFilesFound {
+    Warn,
+    Error,
+    Ignore,
+}
+
+impl UploadArtifact {
+    /// The id of the uploaded artifact
+    pub fn artifact_id(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "artifact-id")
+    }
+
+    /// The URL the artifact can be downloaded from
+    pub fn artifact_url(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "artifact-url")
+    }
+}
+
+impl From<UploadArtifact> for JobStep {
+    fn from(upload: UploadArtifact) -> Self {
+        step(
+            format!("actions/upload-artifact@{}", upload.version),
+            upload.id,
+            upload.step_name,
+            upload.if_cond,
+            [
+                ("name", upload.name.map(Value::from)),
+                ("path", lines(&upload.path)),
+                (
+                    "if-no-files-found",
+                    upload.if_no_files_found.map(|v| v.to_string().into()),
+                ),
+                ("retention-days", upload.retention_days.map(Value::from)),
+                (
+                    "compression-level",
+                    upload.compression_level.map(Value::from),
+                ),
+                ("overwrite", upload.overwrite.map(Value::from)),
+                (
+                    "include-hidden-files",
+                    upload.include_hidden_files.map(Value::from),
+                ),
+            ],
+        )
+    }
+}
+
+/// `actions/download-artifact`, download artifacts uploaded by earlier jobs
+/// or by another workflow run
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct DownloadArtifact {
+    /// The version of the action to use
+    #[builder(default = "v4".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub step_name: Option<String>,
+    pub if_cond: Option<String>,
+    /// The artifact to download, every artifact of the run when unset
+    pub name: Option<String>,
+    /// Where to download to, defaults to `$GITHUB_WORKSPACE`
+    pub path: Option<String>,
+    /// A glob of the artifact names to download, ignored if `name` is set
+    pub pattern: Option<String>,
+    /// Extract every matching artifact into `path` rather than a directory
+    /// per artifact
+    pub merge_multiple: Option<bool>,
+    /// Token used to download artifacts from other repositories or runs
+    pub github_token: Option<String>,
+    /// The repository to download from, requires `github_token`
+    pub repository: Option<String>,
+    /// The run to download from, requires `github_token`
+    pub run_id: Option<String>,
+}
+
+impl DownloadArtifact {
+    /// The path the artifacts were downloaded to
+    pub fn download_path(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "download-path")
+    }
+}
+
+impl From<DownloadArtifact> for JobStep {
+    fn from(download: DownloadArtifact) -> Self {
+        step(
+            format!("actions/download-artifact@{}", download.version),
+            download.id,
+            download.step_name,
+            download.if_cond,
+            [
+                ("name", download.name.map(Value::from)),
+                ("path", download.path.map(Value::from)),
+                ("pattern", download.pattern.map(Value::from)),
+                ("merge-multiple", download.merge_multiple.map(Value::from)),
+                ("github-token", download.github_token.map(Value::from)),
+                ("repository", download.repository.map(Value::from)),
+                ("run-id", download.run_id.map(Value::from)),
+            ],
+        )
+    }
+}
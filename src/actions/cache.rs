@@ -0,0 +1,81 @@
+use super::{lines, step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `actions/cache`, restore files before the job and save them after it
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(
+    field_defaults(default, setter(strip_option, into)),
+    mutators(
+        /// A file, directory or glob to cache, can be repeated
+        pub fn path(&mut self, path: impl ToString) {
+            self.path.push(path.to_string());
+        }
+        /// A prefix of keys to restore from if `key` misses, in order of
+        /// preference
+        pub fn restore_key(&mut self, key: impl ToString) {
+            self.restore_keys.push(key.to_string());
+        }
+    )
+)]
+pub struct Cache {
+    /// The version of the action to use
+    #[builder(default = "v4".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    #[builder(via_mutators(init = Default::default()))]
+    pub path: Vec<String>,
+    /// The key to save and restore the cache with
+    #[builder(!default, setter(!strip_option))]
+    pub key: String,
+    #[builder(via_mutators(init = Default::default()))]
+    pub restore_keys: Vec<String>,
+    /// The chunk size used to split up large files during upload, in bytes
+    #[builder(setter(!into))]
+    pub upload_chunk_size: Option<u64>,
+    /// Allow a cache saved on Windows to be restored on other platforms
+    pub enable_cross_os_archive: Option<bool>,
+    /// Fail the step if no cache entry is found
+    pub fail_on_cache_miss: Option<bool>,
+    /// Check if a cache entry exists without downloading it
+    pub lookup_only: Option<bool>,
+}
+
+impl Cache {
+    /// Whether an exact match was found for `key`
+    pub fn cache_hit(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "cache-hit")
+    }
+}
+
+impl From<Cache> for JobStep {
+    fn from(cache: Cache) -> Self {
+        step(
+            format!("actions/cache@{}", cache.version),
+            cache.id,
+            cache.name,
+            cache.if_cond,
+            [
+                ("path", lines(&cache.path)),
+                ("key", Some(cache.key.into())),
+                ("restore-keys", lines(&cache.restore_keys)),
+                (
+                    "upload-chunk-size",
+                    cache.upload_chunk_size.map(Value::from),
+                ),
+                (
+                    "enableCrossOsArchive",
+                    cache.enable_cross_os_archive.map(Value::from),
+                ),
+                (
+                    "fail-on-cache-miss",
+                    cache.fail_on_cache_miss.map(Value::from),
+                ),
+                ("lookup-only", cache.lookup_only.map(Value::from)),
+            ],
+        )
+    }
+}
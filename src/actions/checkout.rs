@@ -0,0 +1,117 @@
+use super::{step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `actions/checkout`, check out a repository at `$GITHUB_WORKSPACE`
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct Checkout {
+    /// The version of the action to use
+    #[builder(default = "v4".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    /// Repository name with owner, defaults to the repository the workflow
+    /// runs in
+    pub repository: Option<String>,
+    /// The branch, tag or SHA to checkout, the `ref` input
+    pub git_ref: Option<String>,
+    /// Personal access token used to fetch the repository
+    pub token: Option<String>,
+    /// SSH key used to fetch the repository
+    pub ssh_key: Option<String>,
+    /// Whether to configure the token or SSH key with the local git config
+    pub persist_credentials: Option<bool>,
+    /// Relative path under `$GITHUB_WORKSPACE` to place the repository
+    pub path: Option<String>,
+    /// Whether to execute `git clean -ffdx && git reset --hard HEAD` before
+    /// fetching
+    pub clean: Option<bool>,
+    /// Partially clone against a given filter, overrides `sparse_checkout`
+    pub filter: Option<String>,
+    /// Patterns to do a sparse checkout of, one per line
+    pub sparse_checkout: Option<String>,
+    pub sparse_checkout_cone_mode: Option<bool>,
+    /// Number of commits to fetch, `0` fetches all history
+    #[builder(setter(!into))]
+    pub fetch_depth: Option<u32>,
+    /// Whether to fetch tags even if `fetch_depth` is not `0`
+    pub fetch_tags: Option<bool>,
+    pub show_progress: Option<bool>,
+    /// Whether to download Git-LFS files
+    pub lfs: Option<bool>,
+    pub submodules: Option<Submodules>,
+    /// Add the repository path as `safe.directory` in the global git config
+    pub set_safe_directory: Option<bool>,
+}
+
+/// Whether to checkout submodules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Submodules {
+    None,
+    /// Only the top level submodules
+    Top,
+    Recursive,
+}
+
+impl From<Submodules> for Value {
+    fn from(submodules: Submodules) -> Self {
+        match submodules {
+            Submodules::None => Value::Bool(false),
+            Submodules::Top => Value::Bool(true),
+            Submodules::Recursive => Value::String("recursive".into()),
+        }
+    }
+}
+
+impl Checkout {
+    /// The branch, tag or SHA that was checked out, the `ref` output
+    pub fn ref_output(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "ref")
+    }
+
+    /// The commit SHA that was checked out
+    pub fn commit(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "commit")
+    }
+}
+
+impl From<Checkout> for JobStep {
+    fn from(checkout: Checkout) -> Self {
+        step(
+            format!("actions/checkout@{}", checkout.version),
+            checkout.id,
+            checkout.name,
+            checkout.if_cond,
+            [
+                ("repository", checkout.repository.map(Value::from)),
+                ("ref", checkout.git_ref.map(Value::from)),
+                ("token", checkout.token.map(Value::from)),
+                ("ssh-key", checkout.ssh_key.map(Value::from)),
+                (
+                    "persist-credentials",
+                    checkout.persist_credentials.map(Value::from),
+                ),
+                ("path", checkout.path.map(Value::from)),
+                ("clean", checkout.clean.map(Value::from)),
+                ("filter", checkout.filter.map(Value::from)),
+                ("sparse-checkout", checkout.sparse_checkout.map(Value::from)),
+                (
+                    "sparse-checkout-cone-mode",
+                    checkout.sparse_checkout_cone_mode.map(Value::from),
+                ),
+                ("fetch-depth", checkout.fetch_depth.map(Value::from)),
+                ("fetch-tags", checkout.fetch_tags.map(Value::from)),
+                ("show-progress", checkout.show_progress.map(Value::from)),
+                ("lfs", checkout.lfs.map(Value::from)),
+                ("submodules", checkout.submodules.map(Value::from)),
+                (
+                    "set-safe-directory",
+                    checkout.set_safe_directory.map(Value::from),
+                ),
+            ],
+        )
+    }
+}
@@ -0,0 +1,77 @@
+use super::{step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `actions/github-script`, run JavaScript with an authenticated Octokit
+/// client and the workflow run context
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct GithubScript {
+    /// The version of the action to use
+    #[builder(default = "v7".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    /// The body of an async function with `github`, `context`, `core` and
+    /// friends in scope
+    #[builder(!default, setter(!strip_option))]
+    pub script: String,
+    /// The token the Octokit client authenticates with, defaults to the
+    /// `GITHUB_TOKEN`
+    pub github_token: Option<String>,
+    /// Whether to log the requests made
+    pub debug: Option<bool>,
+    pub user_agent: Option<String>,
+    /// A comma separated list of GraphQL API previews to accept
+    pub previews: Option<String>,
+    /// How the return value of the script is encoded into `result`
+    pub result_encoding: Option<ResultEncoding>,
+    /// The number of times to retry failed requests
+    #[builder(setter(!into))]
+    pub retries: Option<u32>,
+    /// A comma separated list of status codes that are never retried
+    pub retry_exempt_status_codes: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ResultEncoding {
+    Json,
+    String,
+}
+
+impl GithubScript {
+    /// The return value of the script
+    pub fn result(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "result")
+    }
+}
+
+impl From<GithubScript> for JobStep {
+    fn from(script: GithubScript) -> Self {
+        step(
+            format!("actions/github-script@{}", script.version),
+            script.id,
+            script.name,
+            script.if_cond,
+            [
+                ("script", Some(script.script.into())),
+                ("github-token", script.github_token.map(Value::from)),
+                ("debug", script.debug.map(Value::from)),
+                ("user-agent", script.user_agent.map(Value::from)),
+                ("previews", script.previews.map(Value::from)),
+                (
+                    "result-encoding",
+                    script.result_encoding.map(|e| e.to_string().into()),
+                ),
+                ("retries", script.retries.map(Value::from)),
+                (
+                    "retry-exempt-status-codes",
+                    script.retry_exempt_status_codes.map(Value::from),
+                ),
+            ],
+        )
+    }
+}
@@ -0,0 +1,197 @@
+//! Typed builders for commonly used actions
+//!
+//! Each builder produces a [`JobStep`] and exposes the action's outputs as
+//! [`StepOutput`] handles, which render as the `${{ steps.<id>.outputs.<name> }}`
+//! expression referencing them. The handles are `None` when the builder
+//! wasn't given an `id`.
+//!
+//! ```
+//! use grackle::{actions::{Checkout, Submodules, UploadArtifact}, Job};
+//!
+//! let checkout = Checkout::builder()
+//!     .id("checkout")
+//!     .submodules(Submodules::Recursive)
+//!     .build();
+//! let job = Job::builder()
+//!     .runs_on("ubuntu-latest")
+//!     .output("commit", checkout.commit().unwrap())
+//!     .add_step(checkout)
+//!     .add_step(UploadArtifact::builder().name("dist").path("target/dist").build())
+//!     .build();
+//! assert_eq!(job.outputs["commit"], "${{ steps.checkout.outputs.commit }}");
+//! ```
+
+mod artifact;
+mod cache;
+mod checkout;
+mod github_script;
+mod rust;
+mod setup_node;
+mod setup_python;
+
+pub use artifact::{DownloadArtifact, IfNoFilesFound, UploadArtifact};
+pub use cache::Cache;
+pub use checkout::{Checkout, Submodules};
+pub use github_script::{GithubScript, ResultEncoding};
+pub use rust::{RustCache, RustToolchain};
+pub use setup_node::{NodePackageManager, SetupNode};
+pub use setup_python::{PythonPackageManager, SetupPython};
+
+use crate::JobStep;
+use serde_yml::Value;
+use std::fmt;
+
+/// A reference to an output of a step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepOutput {
+    pub step: String,
+    pub name: String,
+}
+
+impl StepOutput {
+    pub fn new(step: impl ToString, name: impl ToString) -> Self {
+        Self {
+            step: step.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    /// The output `name` of the step with `id`, `None` without an `id` since
+    /// outputs can only be referenced by it
+    pub fn of(id: Option<&str>, name: &str) -> Option<Self> {
+        id.map(|id| Self::new(id, name))
+    }
+
+    /// The reference without the `${{ }}` delimiters, to be used as part of a
+    /// larger expression
+    pub fn expr(&self) -> String {
+        format!("steps.{}.outputs.{}", self.step, self.name)
+    }
+}
+
+impl fmt::Display for StepOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${{{{ {} }}}}", self.expr())
+    }
+}
+
+impl From<StepOutput> for String {
+    fn from(output: StepOutput) -> Self {
+        output.to_string()
+    }
+}
+
+impl From<StepOutput> for Value {
+    fn from(output: StepOutput) -> Self {
+        Value::String(output.to_string())
+    }
+}
+
+/// A step using `uses` with the inputs that were set
+pub(crate) fn step(
+    uses: String,
+    id: Option<String>,
+    name: Option<String>,
+    if_cond: Option<String>,
+    with: impl IntoIterator<Item = (&'static str, Option<Value>)>,
+) -> JobStep {
+    JobStep {
+        id,
+        name,
+        if_cond,
+        uses: Some(uses),
+        with: with
+            .into_iter()
+            .filter_map(|(key, value)| Some((key.to_string(), value?)))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+/// Multiple values of an input that takes one per line
+pub(crate) fn lines(values: &[String]) -> Option<Value> {
+    (!values.is_empty()).then(|| Value::String(values.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Job, Workflow};
+
+    #[test]
+    fn build_job() {
+        let checkout = Checkout::builder()
+            .id("checkout")
+            .submodules(Submodules::Recursive)
+            .fetch_depth(0)
+            .build();
+        let toolchain = RustToolchain::builder()
+            .id("toolchain")
+            .toolchain("nightly-2024-06-01")
+            .component("clippy")
+            .component("rustfmt")
+            .build();
+        let cache = Cache::builder()
+            .id("cache")
+            .path("~/.npm")
+            .key("npm-${{ hashFiles('package-lock.json') }}")
+            .restore_key("npm-")
+            .build();
+        let script = GithubScript::builder()
+            .id("script")
+            .script("return context.sha")
+            .result_encoding(ResultEncoding::String)
+            .build();
+        let job = Job::builder()
+            .runs_on("ubuntu-latest")
+            .output("commit", checkout.commit().unwrap())
+            .output("rustc", toolchain.cachekey().unwrap())
+            .output("sha", script.result().unwrap())
+            .add_step(checkout)
+            .add_step(toolchain)
+            .add_step(RustCache::builder().key("${{ matrix.target }}").build())
+            .add_step(
+                SetupNode::builder()
+                    .node_version("20")
+                    .cache(NodePackageManager::Npm)
+                    .build(),
+            )
+            .add_step(
+                SetupPython::builder()
+                    .python_version("3.12")
+                    .cache(PythonPackageManager::Pip)
+                    .build(),
+            )
+            .add_step(cache)
+            .add_step(script)
+            .add_step(
+                DownloadArtifact::builder()
+                    .pattern("artifacts-*")
+                    .path("target/distrib/")
+                    .merge_multiple(true)
+                    .build(),
+            )
+            .add_step(
+                UploadArtifact::builder()
+                    .name("dist")
+                    .path("target/distrib/*")
+                    .path("dist-manifest.json")
+                    .if_no_files_found(IfNoFilesFound::Error)
+                    .retention_days(7)
+                    .build(),
+            )
+            .build();
+        let workflow = Workflow::builder().name("ci").add_job("build", job).build();
+        insta::assert_yaml_snapshot!(workflow);
+    }
+
+    #[test]
+    fn output_without_id() {
+        assert_eq!(Checkout::builder().build().commit(), None);
+        let checkout = Checkout::builder().id("checkout").build();
+        assert_eq!(
+            checkout.ref_output().unwrap().to_string(),
+            "${{ steps.checkout.outputs.ref }}"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use super::{lines, step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `dtolnay/rust-toolchain`, install a rust toolchain with rustup
+///
+/// The action has a branch for each release channel and stable version,
+/// those are used as the ref directly and anything else, e.g. a dated
+/// nightly, is passed as the `toolchain` input of the `master` branch.
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(
+    field_defaults(default, setter(strip_option, into)),
+    mutators(
+        /// An additional target to install, can be repeated
+        pub fn target(&mut self, target: impl ToString) {
+            self.targets.push(target.to_string());
+        }
+        /// An additional component to install, can be repeated
+        pub fn component(&mut self, component: impl ToString) {
+            self.components.push(component.to_string());
+        }
+    )
+)]
+pub struct RustToolchain {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    /// The rustup toolchain, e.g. `stable`, `1.80.0` or `nightly-2024-06-01`
+    #[builder(default = "stable".into(), setter(!strip_option))]
+    pub toolchain: String,
+    #[builder(via_mutators(init = Default::default()))]
+    pub targets: Vec<String>,
+    #[builder(via_mutators(init = Default::default()))]
+    pub components: Vec<String>,
+}
+
+impl RustToolchain {
+    /// A short hash of the installed rustc version, for use in cache keys
+    pub fn cachekey(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "cachekey")
+    }
+
+    /// The toolchain that was installed, e.g. `1.80.0`
+    pub fn toolchain_name(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "name")
+    }
+
+    /// If the toolchain has a branch of its own in the action's repository
+    fn is_branch(toolchain: &str) -> bool {
+        matches!(toolchain, "stable" | "beta" | "nightly")
+            || toolchain
+                .split('.')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+    }
+}
+
+impl From<RustToolchain> for JobStep {
+    fn from(rust: RustToolchain) -> Self {
+        let (git_ref, toolchain) = if RustToolchain::is_branch(&rust.toolchain) {
+            (rust.toolchain, None)
+        } else {
+            ("master".to_string(), Some(Value::from(rust.toolchain)))
+        };
+        step(
+            format!("dtolnay/rust-toolchain@{git_ref}"),
+            rust.id,
+            rust.name,
+            rust.if_cond,
+            [
+                ("toolchain", toolchain),
+                (
+                    "targets",
+                    (!rust.targets.is_empty()).then(|| rust.targets.join(",").into()),
+                ),
+                (
+                    "components",
+                    (!rust.components.is_empty()).then(|| rust.components.join(",").into()),
+                ),
+            ],
+        )
+    }
+}
+
+/// `Swatinem/rust-cache`, cache cargo's registry and the target directory
+/// keyed by the toolchain and lockfiles
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(
+    field_defaults(default, setter(strip_option, into)),
+    mutators(
+        /// A workspace to cache as `<workspace> -> <target dir>`, can be
+        /// repeated
+        pub fn workspace(&mut self, workspace: impl ToString) {
+            self.workspaces.push(workspace.to_string());
+        }
+        /// An additional directory to cache, can be repeated
+        pub fn cache_directory(&mut self, directory: impl ToString) {
+            self.cache_directories.push(directory.to_string());
+        }
+    )
+)]
+pub struct RustCache {
+    /// The version of the action to use
+    #[builder(default = "v2".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    /// Replaces the `v0-rust` prefix of the cache key
+    pub prefix_key: Option<String>,
+    /// Replaces the job id in the cache key, to share a cache between jobs
+    pub shared_key: Option<String>,
+    /// An additional key added to the cache key
+    pub key: Option<String>,
+    /// Space separated environment variable prefixes whose values are part
+    /// of the cache key
+    pub env_vars: Option<String>,
+    #[builder(via_mutators(init = Default::default()))]
+    pub workspaces: Vec<String>,
+    #[builder(via_mutators(init = Default::default()))]
+    pub cache_directories: Vec<String>,
+    /// Whether to cache the target directories
+    pub cache_targets: Option<bool>,
+    /// Whether to save the cache when the job fails
+    pub cache_on_failure: Option<bool>,
+    /// Cache every crate in the registry rather than only the workspace
+    /// dependencies
+    pub cache_all_crates: Option<bool>,
+    /// An expression deciding whether to save the cache, e.g.
+    /// `${{ github.ref == 'refs/heads/main' }}`
+    pub save_if: Option<String>,
+    /// `github`, `buildjet` or `warpbuild`
+    pub cache_provider: Option<String>,
+    /// Check if a cache entry exists without downloading it
+    pub lookup_only: Option<bool>,
+}
+
+impl RustCache {
+    /// Whether an exact match was found
+    pub fn cache_hit(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "cache-hit")
+    }
+}
+
+impl From<RustCache> for JobStep {
+    fn from(cache: RustCache) -> Self {
+        step(
+            format!("Swatinem/rust-cache@{}", cache.version),
+            cache.id,
+            cache.name,
+            cache.if_cond,
+            [
+                ("prefix-key", cache.prefix_key.map(Value::from)),
+                ("shared-key", cache.shared_key.map(Value::from)),
+                ("key", cache.key.map(Value::from)),
+                ("env-vars", cache.env_vars.map(Value::from)),
+                ("workspaces", lines(&cache.workspaces)),
+                ("cache-directories", lines(&cache.cache_directories)),
+                ("cache-targets", cache.cache_targets.map(Value::from)),
+                ("cache-on-failure", cache.cache_on_failure.map(Value::from)),
+                ("cache-all-crates", cache.cache_all_crates.map(Value::from)),
+                ("save-if", cache.save_if.map(Value::from)),
+                ("cache-provider", cache.cache_provider.map(Value::from)),
+                ("lookup-only", cache.lookup_only.map(Value::from)),
+            ],
+        )
+    }
+}
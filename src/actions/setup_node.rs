@@ -0,0 +1,83 @@
+use super::{step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `actions/setup-node`, install Node.js and add it to the `PATH`
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct SetupNode {
+    /// The version of the action to use
+    #[builder(default = "v4".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    /// Version spec of the version to use, e.g. `20`, `20.x` or `lts/*`
+    pub node_version: Option<String>,
+    /// File containing the version to use, e.g. `.nvmrc`
+    pub node_version_file: Option<String>,
+    /// Target architecture, e.g. `x64`
+    pub architecture: Option<String>,
+    /// Check for the latest version matching `node_version` instead of using
+    /// a cached one
+    pub check_latest: Option<bool>,
+    /// Registry to set up for auth, sets it in the project level `.npmrc`
+    pub registry_url: Option<String>,
+    /// Scope for authenticating against scoped registries
+    pub scope: Option<String>,
+    /// Used to pull node distributions from the node-versions repository
+    pub token: Option<String>,
+    /// Package manager to cache dependencies of
+    pub cache: Option<NodePackageManager>,
+    /// Path to the dependency file, supports wildcards
+    pub cache_dependency_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum NodePackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+}
+
+impl SetupNode {
+    /// Whether an exact match was found in the cache
+    pub fn cache_hit(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "cache-hit")
+    }
+
+    /// The installed version
+    pub fn node_version(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "node-version")
+    }
+}
+
+impl From<SetupNode> for JobStep {
+    fn from(setup: SetupNode) -> Self {
+        step(
+            format!("actions/setup-node@{}", setup.version),
+            setup.id,
+            setup.name,
+            setup.if_cond,
+            [
+                ("node-version", setup.node_version.map(Value::from)),
+                (
+                    "node-version-file",
+                    setup.node_version_file.map(Value::from),
+                ),
+                ("architecture", setup.architecture.map(Value::from)),
+                ("check-latest", setup.check_latest.map(Value::from)),
+                ("registry-url", setup.registry_url.map(Value::from)),
+                ("scope", setup.scope.map(Value::from)),
+                ("token", setup.token.map(Value::from)),
+                ("cache", setup.cache.map(|c| c.to_string().into())),
+                (
+                    "cache-dependency-path",
+                    setup.cache_dependency_path.map(Value::from),
+                ),
+            ],
+        )
+    }
+}
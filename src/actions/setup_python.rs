@@ -0,0 +1,94 @@
+use super::{step, StepOutput};
+use crate::JobStep;
+use serde_yml::Value;
+use typed_builder::TypedBuilder;
+
+/// `actions/setup-python`, install Python and add it to the `PATH`
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(field_defaults(default, setter(strip_option, into)))]
+pub struct SetupPython {
+    /// The version of the action to use
+    #[builder(default = "v5".into(), setter(!strip_option))]
+    pub version: String,
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub if_cond: Option<String>,
+    /// Version range or exact version to use, e.g. `3.12` or `pypy3.10`
+    pub python_version: Option<String>,
+    /// File containing the version to use, e.g. `.python-version`
+    pub python_version_file: Option<String>,
+    /// Package manager to cache dependencies of
+    pub cache: Option<PythonPackageManager>,
+    /// Target architecture, e.g. `x64`
+    pub architecture: Option<String>,
+    /// Check for the latest version matching `python_version` instead of
+    /// using a cached one
+    pub check_latest: Option<bool>,
+    /// Used to pull python distributions from the python-versions repository
+    pub token: Option<String>,
+    /// Path to the dependency files, supports wildcards
+    pub cache_dependency_path: Option<String>,
+    /// Set the `pythonLocation` and `PKG_CONFIG_PATH` environment variables
+    pub update_environment: Option<bool>,
+    /// Allow prerelease versions to satisfy `python_version`
+    pub allow_prereleases: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum PythonPackageManager {
+    Pip,
+    Pipenv,
+    Poetry,
+}
+
+impl SetupPython {
+    /// The installed version
+    pub fn python_version(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "python-version")
+    }
+
+    /// Whether an exact match was found in the cache
+    pub fn cache_hit(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "cache-hit")
+    }
+
+    /// The absolute path to the python executable
+    pub fn python_path(&self) -> Option<StepOutput> {
+        StepOutput::of(self.id.as_deref(), "python-path")
+    }
+}
+
+impl From<SetupPython> for JobStep {
+    fn from(setup: SetupPython) -> Self {
+        step(
+            format!("actions/setup-python@{}", setup.version),
+            setup.id,
+            setup.name,
+            setup.if_cond,
+            [
+                ("python-version", setup.python_version.map(Value::from)),
+                (
+                    "python-version-file",
+                    setup.python_version_file.map(Value::from),
+                ),
+                ("cache", setup.cache.map(|c| c.to_string().into())),
+                ("architecture", setup.architecture.map(Value::from)),
+                ("check-latest", setup.check_latest.map(Value::from)),
+                ("token", setup.token.map(Value::from)),
+                (
+                    "cache-dependency-path",
+                    setup.cache_dependency_path.map(Value::from),
+                ),
+                (
+                    "update-environment",
+                    setup.update_environment.map(Value::from),
+                ),
+                (
+                    "allow-prereleases",
+                    setup.allow_prereleases.map(Value::from),
+                ),
+            ],
+        )
+    }
+}
@@ -0,0 +1,257 @@
+//! Which workflows and jobs a change would run, from the diff between two
+//! commits of a local git checkout
+
+use crate::{
+    event::{Event, GitRef, Reason},
+    Workflow,
+};
+use serde::Serialize;
+use std::{fmt::Write, io, path::Path, process::Command};
+
+/// The changes between two commits
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Diff {
+    /// The branch a pull request of the changes would target
+    pub base: String,
+    /// The branch the changes are pushed to, `None` for a detached head
+    pub branch: Option<String>,
+    /// The changed files, renames as a deletion and an addition
+    pub paths: Vec<String>,
+}
+
+impl Diff {
+    /// Read the files changed between the merge base of `base` and `head`
+    /// and `head`, like the diff of a pull request, from the repository at
+    /// `repo`
+    pub fn from_git(repo: &Path, base: &str, head: &str) -> io::Result<Self> {
+        let range = format!("{base}...{head}");
+        let diff = git(repo, &["diff", "--name-only", "--no-renames", "-z", &range])?;
+        let branch = git(repo, &["rev-parse", "--symbolic-full-name", head])?;
+        let base = base.strip_prefix("refs/heads/").unwrap_or(base);
+        Ok(Self {
+            base: base.strip_prefix("origin/").unwrap_or(base).to_string(),
+            branch: branch.trim().strip_prefix("refs/heads/").map(String::from),
+            paths: diff
+                .split('\0')
+                .filter(|p| !p.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
+
+    /// The events pushing the changes and updating a pull request of them
+    /// would trigger
+    pub fn events(&self) -> Vec<Event> {
+        let mut push = Event::builder().name("push").build();
+        push.git_ref = self.branch.clone().map(GitRef::Branch);
+        push.paths.clone_from(&self.paths);
+        let mut pull_request = Event::builder()
+            .name("pull_request")
+            .activity("synchronize")
+            .base_ref(self.base.clone())
+            .build();
+        pull_request.paths.clone_from(&self.paths);
+        vec![push, pull_request]
+    }
+}
+
+fn git(repo: &Path, args: &[&str]) -> io::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {}: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Affected {
+    pub diff: Diff,
+    pub workflows: Vec<AffectedWorkflow>,
+}
+
+/// A workflow listening to one of the events of a diff
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedWorkflow {
+    pub path: String,
+    pub name: String,
+    pub event: String,
+    pub triggered: bool,
+    /// Why the workflow is triggered or not
+    pub reasons: Vec<String>,
+    /// The jobs that would run, empty unless the workflow is triggered
+    pub jobs: Vec<String>,
+}
+
+impl Affected {
+    pub fn new(diff: Diff) -> Self {
+        Self {
+            diff,
+            workflows: Vec::new(),
+        }
+    }
+
+    /// Evaluate the triggers of `workflow`, read from `path`, against the
+    /// events of the diff
+    pub fn add(&mut self, path: impl Into<String>, workflow: &Workflow) {
+        let path = path.into();
+        for event in self.diff.events() {
+            let decision = workflow.on.matches(&event);
+            if matches!(decision.reasons[..], [Reason::NotListening(_)]) {
+                continue;
+            }
+            let jobs = if decision.triggered {
+                workflow.jobs.keys().cloned().collect()
+            } else {
+                Vec::new()
+            };
+            self.workflows.push(AffectedWorkflow {
+                path: path.clone(),
+                name: workflow.name.clone(),
+                event: event.name,
+                triggered: decision.triggered,
+                reasons: decision.reasons.iter().map(ToString::to_string).collect(),
+                jobs,
+            });
+        }
+    }
+
+    /// The workflows that would run
+    pub fn triggered(&self) -> impl Iterator<Item = &AffectedWorkflow> {
+        self.workflows.iter().filter(|w| w.triggered)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("affected workflows are valid json")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut ret = String::from("# Affected workflows\n\n");
+        let _ = writeln!(
+            ret,
+            "{} changed files on {}, targeting `{}`",
+            self.diff.paths.len(),
+            self.diff
+                .branch
+                .as_ref()
+                .map_or("a detached head".to_string(), |b| format!("`{b}`")),
+            self.diff.base,
+        );
+        ret.push_str("\n| Workflow | Event | Runs | Jobs | Why |\n");
+        ret.push_str("| --- | --- | --- | --- | --- |\n");
+        for workflow in &self.workflows {
+            let jobs: Vec<_> = workflow.jobs.iter().map(|j| format!("`{j}`")).collect();
+            let why = workflow.reasons[1..].join(", ").replace('|', "\\|");
+            let _ = writeln!(
+                ret,
+                "| {} (`{}`) | `{}` | {} | {} | {} |",
+                workflow.name,
+                workflow.path,
+                workflow.event,
+                if workflow.triggered { "yes" } else { "no" },
+                jobs.join(", "),
+                why,
+            );
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(yaml: &str) -> Workflow {
+        serde_yml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn affected() {
+        let diff = Diff {
+            base: "main".into(),
+            branch: Some("feature/login".into()),
+            paths: vec!["src/login.rs".into(), "docs/login.md".into()],
+        };
+        let mut affected = Affected::new(diff);
+        affected.add(
+            ".github/workflows/ci.yml",
+            &workflow(
+                r#"name: ci
+on:
+  push:
+    branches: [main]
+  pull_request:
+    paths: ["src/**"]
+jobs:
+  test: {runs-on: ubuntu-latest}
+  lint: {runs-on: ubuntu-latest}
+"#,
+            ),
+        );
+        affected.add(
+            ".github/workflows/docs.yml",
+            &workflow(
+                r#"name: docs
+on:
+  push:
+    paths: ["docs/**"]
+jobs:
+  publish: {runs-on: ubuntu-latest}
+"#,
+            ),
+        );
+        affected.add(
+            ".github/workflows/release.yml",
+            &workflow("name: release\non: workflow_dispatch\njobs: {}\n"),
+        );
+        insta::assert_snapshot!(affected.to_markdown());
+    }
+
+    #[test]
+    fn from_git() {
+        let repo = crate::testing::TempDir::new("affected");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        let run = |args: &[&str]| {
+            git(
+                &repo,
+                &[
+                    &[
+                        "-c",
+                        "user.name=grackle",
+                        "-c",
+                        "user.email=grackle@example.com",
+                    ],
+                    args,
+                ]
+                .concat(),
+            )
+            .unwrap()
+        };
+        run(&["init", "-q", "-b", "main"]);
+        std::fs::write(repo.join("README.md"), "readme\n").unwrap();
+        run(&["add", "-A"]);
+        run(&["commit", "-q", "-m", "init"]);
+        run(&["checkout", "-q", "-b", "feature"]);
+        std::fs::write(repo.join("src/lib.rs"), "\n").unwrap();
+        std::fs::rename(repo.join("README.md"), repo.join("README")).unwrap();
+        run(&["add", "-A"]);
+        run(&["commit", "-q", "-m", "change"]);
+
+        let diff = Diff::from_git(&repo, "main", "HEAD").unwrap();
+        assert_eq!(
+            diff,
+            Diff {
+                base: "main".into(),
+                branch: Some("feature".into()),
+                paths: vec!["README".into(), "README.md".into(), "src/lib.rs".into()],
+            }
+        );
+    }
+}
@@ -0,0 +1,446 @@
+//! Generate a typed builder for an action from its `action.yml`
+//!
+//! The generated module has the same shape as the builders in
+//! [`crate::actions`]: a struct whose fields are the action's inputs, with
+//! required inputs that have no default as mandatory builder fields, a
+//! method per output returning an optional
+//! [`StepOutput`](crate::actions::StepOutput) and a conversion into
+//! [`JobStep`](crate::JobStep).
+//!
+//! From a `build.rs`
+//!
+//! ```no_run
+//! grackle::codegen::build("actions/upload-artifact@v4", "actions/upload-artifact").unwrap();
+//! ```
+//!
+//! and in the crate
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/upload_artifact.rs"));
+//! ```
+
+use crate::{action::Action, uses::Uses};
+use serde_yml::Value;
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+    io,
+    path::{Path, PathBuf},
+};
+
+/// The step fields every generated builder has, inputs with the same name
+/// get an `_input` suffix
+const STEP_FIELDS: &[&str] = &["version", "id", "step_name", "if_cond"];
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self",
+    "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized",
+    "use", "virtual", "where", "while", "yield",
+];
+
+/// Generates the builder for one action
+#[derive(Debug, Clone)]
+pub struct Codegen<'a> {
+    action: &'a Action,
+    uses: String,
+    name: Option<String>,
+}
+
+impl<'a> Codegen<'a> {
+    /// `uses` is what the generated step uses, for actions in another
+    /// repository its ref becomes the default of the `version` field
+    pub fn new(action: &'a Action, uses: impl Into<String>) -> Self {
+        Self {
+            action,
+            uses: uses.into(),
+            name: None,
+        }
+    }
+
+    /// The name of the generated struct, by default the repository and path
+    /// of `uses` in PascalCase
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The name of the generated struct
+    pub fn struct_name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let base = match self.uses.parse::<Uses>() {
+            Ok(Uses::Repository(reference)) => match reference.path {
+                Some(path) => format!("{}-{path}", reference.repo),
+                None => reference.repo,
+            },
+            _ => self
+                .uses
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        let name = pascal_case(&base);
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            format!("Action{name}")
+        } else {
+            name
+        }
+    }
+
+    /// The source of the generated module
+    pub fn generate(&self) -> String {
+        let name = self.struct_name();
+        let repository = match self.uses.parse::<Uses>() {
+            Ok(Uses::Repository(reference)) => Some(reference),
+            _ => None,
+        };
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// Generated by grackle from the metadata of `{}`, do not edit",
+            self.uses
+        )
+        .unwrap();
+        out.push('\n');
+        if self.action.outputs.is_empty() {
+            out.push_str("use ::grackle::{typed_builder::TypedBuilder, JobStep};\n\n");
+        } else {
+            out.push_str(
+                "use ::grackle::{actions::StepOutput, typed_builder::TypedBuilder, JobStep};\n\n",
+            );
+        }
+
+        doc(&mut out, "", &self.action.name);
+        if !self.action.description.is_empty() {
+            out.push_str("///\n");
+            doc(&mut out, "", &self.action.description);
+        }
+        out.push_str("#[derive(Debug, Clone, TypedBuilder)]\n");
+        out.push_str("#[builder(\n");
+        out.push_str("    crate_module_path = ::grackle::typed_builder,\n");
+        out.push_str("    field_defaults(default, setter(strip_option, into))\n");
+        out.push_str(")]\n");
+        writeln!(out, "pub struct {name} {{").unwrap();
+        if let Some(reference) = &repository {
+            out.push_str("    /// The version of the action to use\n");
+            writeln!(
+                out,
+                "    #[builder(default = {:?}.into(), setter(!strip_option))]",
+                reference.git_ref
+            )
+            .unwrap();
+            out.push_str("    pub version: String,\n");
+        }
+        out.push_str("    pub id: Option<String>,\n");
+        out.push_str("    pub step_name: Option<String>,\n");
+        out.push_str("    pub if_cond: Option<String>,\n");
+        let fields = self.fields();
+        for (input, field) in &fields {
+            let input = &self.action.inputs[*input];
+            if let Some(description) = &input.description {
+                doc(&mut out, "    ", description);
+            }
+            if let Some(default) = &input.default {
+                if input.description.is_some() {
+                    out.push_str("    ///\n");
+                }
+                writeln!(out, "    /// Defaults to `{}`", display(default)).unwrap();
+            }
+            if let Some(message) = &input.deprecation_message {
+                writeln!(out, "    #[deprecated = {message:?}]").unwrap();
+            }
+            if is_mandatory(input) {
+                out.push_str("    #[builder(!default, setter(!strip_option))]\n");
+                writeln!(out, "    pub {field}: String,").unwrap();
+            } else {
+                writeln!(out, "    pub {field}: Option<String>,").unwrap();
+            }
+        }
+        out.push_str("}\n");
+
+        if !self.action.outputs.is_empty() {
+            writeln!(out, "\nimpl {name} {{").unwrap();
+            let mut methods = BTreeSet::new();
+            for (idx, (output, def)) in self.action.outputs.iter().enumerate() {
+                if idx > 0 {
+                    out.push('\n');
+                }
+                if let Some(description) = &def.description {
+                    doc(&mut out, "    ", description);
+                }
+                let mut method = snake_case(output);
+                if method == "builder" {
+                    method.push_str("_output");
+                }
+                let method = unique(method, &mut methods);
+                writeln!(out, "    pub fn {method}(&self) -> Option<StepOutput> {{").unwrap();
+                writeln!(
+                    out,
+                    "        StepOutput::of(self.id.as_deref(), {output:?})"
+                )
+                .unwrap();
+                out.push_str("    }\n");
+            }
+            out.push_str("}\n");
+        }
+
+        out.push_str("\n#[allow(deprecated)]\n");
+        writeln!(out, "impl From<{name}> for JobStep {{").unwrap();
+        writeln!(out, "    fn from(action: {name}) -> Self {{").unwrap();
+        let mutable = if fields.is_empty() { "" } else { "mut " };
+        writeln!(out, "        let {mutable}step = JobStep {{").unwrap();
+        match &repository {
+            Some(reference) => {
+                let mut prefix = format!("{}/{}", reference.owner, reference.repo);
+                if let Some(path) = &reference.path {
+                    prefix = format!("{prefix}/{path}");
+                }
+                writeln!(
+                    out,
+                    "            uses: Some(format!(\"{prefix}@{{}}\", action.version)),"
+                )
+                .unwrap();
+            }
+            None => writeln!(out, "            uses: Some({:?}.into()),", self.uses).unwrap(),
+        }
+        out.push_str("            id: action.id,\n");
+        out.push_str("            name: action.step_name,\n");
+        out.push_str("            if_cond: action.if_cond,\n");
+        out.push_str("            ..Default::default()\n");
+        out.push_str("        };\n");
+        for (input, field) in &fields {
+            if is_mandatory(&self.action.inputs[*input]) {
+                writeln!(
+                    out,
+                    "        step.with.insert({input:?}.into(), action.{field}.into());"
+                )
+                .unwrap();
+            } else {
+                writeln!(out, "        if let Some(value) = action.{field} {{").unwrap();
+                writeln!(
+                    out,
+                    "            step.with.insert({input:?}.into(), value.into());"
+                )
+                .unwrap();
+                out.push_str("        }\n");
+            }
+        }
+        out.push_str("        step\n");
+        out.push_str("    }\n");
+        out.push_str("}\n");
+        out
+    }
+
+    /// Each input along with the name of its field, inputs that end up with
+    /// the same name (`foo-bar` and `foo_bar`) get a numbered suffix
+    fn fields(&self) -> Vec<(&'a str, String)> {
+        let mut taken: BTreeSet<String> = STEP_FIELDS.iter().map(|f| f.to_string()).collect();
+        self.action
+            .inputs
+            .keys()
+            .map(|input| {
+                let mut field = snake_case(input);
+                if STEP_FIELDS.contains(&field.as_str()) {
+                    field.push_str("_input");
+                }
+                (input.as_str(), unique(field, &mut taken))
+            })
+            .collect()
+    }
+}
+
+/// Generate the builder for the action in `path`, a directory or its
+/// `action.yml`, into `$OUT_DIR` from a build script
+///
+/// The file is named after the struct in snake_case and its path returned.
+pub fn build(uses: &str, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    let action = load(path)?;
+    println!("cargo:rerun-if-changed={}", path.display());
+    let codegen = Codegen::new(&action, uses);
+    let out_dir = std::env::var_os("OUT_DIR")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "OUT_DIR is not set"))?;
+    let out = Path::new(&out_dir).join(format!("{}.rs", snake_case(&codegen.struct_name())));
+    std::fs::write(&out, codegen.generate())?;
+    Ok(out)
+}
+
+/// Read an action's metadata from a directory or an `action.yml`
+pub fn load(path: impl AsRef<Path>) -> io::Result<Action> {
+    let path = path.as_ref();
+    if path.is_dir() {
+        return Action::load_dir(path);
+    }
+    let text = std::fs::read_to_string(path)?;
+    serde_yml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn doc(out: &mut String, indent: &str, text: &str) {
+    for line in text.trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            writeln!(out, "{indent}///").unwrap();
+        } else {
+            writeln!(out, "{indent}/// {line}").unwrap();
+        }
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => serde_yml::to_string(other)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+/// Inputs that have to be set for the action to work
+fn is_mandatory(input: &crate::action::ActionInput) -> bool {
+    input.required && input.default.is_none()
+}
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// A valid field or method name, `retention-days` is `retention_days`
+fn snake_case(name: &str) -> String {
+    let mut ret = words(name).join("_");
+    if ret.is_empty() || ret.starts_with(|c: char| c.is_ascii_digit()) {
+        ret.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ret.as_str()) {
+        ret.push('_');
+    }
+    ret
+}
+
+/// `name`, or `name` with the first free numbered suffix, marked as taken
+fn unique(name: String, taken: &mut BTreeSet<String>) -> String {
+    let mut ret = name.clone();
+    let mut n = 1;
+    while taken.contains(&ret) {
+        n += 1;
+        ret = format!("{}_{n}", name.trim_end_matches('_'));
+    }
+    taken.insert(ret.clone());
+    ret
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(snake_case("retention-days"), "retention_days");
+        assert_eq!(
+            snake_case("enableCrossOsArchive"),
+            "enable_cross_os_archive"
+        );
+        assert_eq!(snake_case("ref"), "ref_");
+        assert_eq!(snake_case("7zip"), "_7zip");
+        assert_eq!(pascal_case("codeql-action-init"), "CodeqlActionInit");
+        let action: Action =
+            serde_yml::from_str("name: setup\nruns:\n  using: composite\n").unwrap();
+        let codegen = Codegen::new(&action, "./.github/actions/setup-rust");
+        assert_eq!(codegen.struct_name(), "SetupRust");
+        assert_eq!(codegen.name("Setup").struct_name(), "Setup");
+
+        let action: Action = serde_yml::from_str(
+            "name: setup\ninputs:\n  foo-bar: {}\n  foo_bar: {}\n  ref: {}\n  ref_: {}\n  id: {}\n  id-input: {}\nruns:\n  using: composite\n",
+        )
+        .unwrap();
+        let fields: Vec<_> = Codegen::new(&action, "./setup")
+            .fields()
+            .into_iter()
+            .map(|(_, field)| field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "foo_bar",
+                "foo_bar_2",
+                "ref_",
+                "ref_2",
+                "id_input",
+                "id_input_2"
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_builder() {
+        let action: Action = serde_yml::from_str(
+            r#"name: Upload a Build Artifact
+description: Upload a build artifact that can be used by subsequent workflow steps
+inputs:
+  name:
+    description: Artifact name
+    default: artifact
+  path:
+    description: A file, directory or wildcard pattern that describes what to upload
+    required: true
+  ref:
+    description: |
+      The ref to upload from.
+
+      Only used by forks.
+  retention-days:
+    description: Duration after which artifact will expire in days
+  compression:
+    description: Zlib compression level
+    deprecationMessage: use compression-level
+outputs:
+  artifact-id:
+    description: A unique identifier for the artifact that was just uploaded
+runs:
+  using: node20
+  main: dist/upload/index.js
+"#,
+        )
+        .unwrap();
+        let codegen = Codegen::new(&action, "actions/upload-artifact@v4");
+        assert_eq!(codegen.struct_name(), "UploadArtifact");
+        insta::assert_snapshot!(codegen.generate());
+    }
+}
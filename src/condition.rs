@@ -0,0 +1,859 @@
+//! Partial evaluation of job and step `if` conditions for an event
+//!
+//! Context values the event determines, like `github.event_name` and
+//! `github.ref`, are substituted and constant subexpressions folded. What's
+//! left depends on data only known when the workflow runs. Jobs that run are
+//! assumed to succeed unless [`Evaluator::fail`] says otherwise, skipped jobs
+//! skip the jobs that need them unless a status function says otherwise.
+//!
+//! ```
+//! use grackle::{condition::{Evaluator, Outcome}, event::Event, Workflow};
+//!
+//! let workflow: Workflow = serde_yml::from_str(r#"
+//! name: ci
+//! on: [push, pull_request]
+//! jobs:
+//!   test:
+//!     runs-on: ubuntu-latest
+//!   deploy:
+//!     needs: test
+//!     if: github.event_name == 'push' && inputs.deploy
+//!     runs-on: ubuntu-latest
+//! "#).unwrap();
+//! let event = Event::builder().name("push").build();
+//! let analysis = Evaluator::new(&event).evaluate(&workflow);
+//! assert_eq!(analysis.jobs["test"].outcome, Outcome::Run);
+//! assert_eq!(analysis.jobs["deploy"].outcome, Outcome::Runtime);
+//! assert_eq!(analysis.jobs["deploy"].condition.as_deref(), Some("inputs.deploy"));
+//! ```
+
+use crate::{
+    event::{Event, GitRef},
+    expr::{self, BinaryOp, Expr},
+    Job, Workflow,
+};
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+/// Whether a job or step runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Outcome {
+    Run,
+    Skip,
+    /// Depends on values only known when the workflow runs
+    Runtime,
+}
+
+/// The outcome of a condition and what's left of it after folding, `None`
+/// when there's nothing left to evaluate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Evaluation {
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JobEvaluation {
+    pub outcome: Outcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// The steps as if the job runs
+    pub steps: Vec<Evaluation>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Analysis {
+    pub jobs: IndexMap<String, JobEvaluation>,
+}
+
+/// Evaluates the conditions of a workflow for an event
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    /// Known context values by lowercase path
+    contexts: BTreeMap<String, Expr>,
+    failed: BTreeSet<String>,
+}
+
+/// The results a job may end with
+#[derive(Debug, Clone, Copy, Default)]
+struct Results {
+    success: bool,
+    failure: bool,
+    skipped: bool,
+}
+
+impl Evaluator {
+    pub fn new(event: &Event) -> Self {
+        let mut ret = Self {
+            contexts: BTreeMap::new(),
+            failed: BTreeSet::new(),
+        };
+        let string = |s: &str| Expr::String(s.into());
+        ret = ret.context("github.event_name", string(&event.name));
+        if let Some(activity) = &event.activity {
+            ret = ret.context("github.event.action", string(activity));
+        }
+        let pull_request = matches!(event.name.as_str(), "pull_request" | "pull_request_target");
+        // `workflow_call` inherits the context of the caller
+        if !pull_request && event.name != "workflow_call" {
+            ret = ret
+                .context("github.base_ref", string(""))
+                .context("github.head_ref", string(""));
+        }
+        match (&event.git_ref, event.name.as_str()) {
+            (Some(GitRef::Branch(name)), "workflow_run") => {
+                ret = ret.context("github.event.workflow_run.head_branch", string(name));
+            }
+            (Some(git_ref), name) if !pull_request && name != "workflow_call" => {
+                let (prefix, kind) = match git_ref {
+                    GitRef::Branch(_) => ("refs/heads/", "branch"),
+                    GitRef::Tag(_) => ("refs/tags/", "tag"),
+                };
+                ret = ret
+                    .context("github.ref", string(&format!("{prefix}{}", git_ref.name())))
+                    .context("github.ref_name", string(git_ref.name()))
+                    .context("github.ref_type", string(kind));
+            }
+            _ => {}
+        }
+        if let (true, Some(base)) = (pull_request, &event.base_ref) {
+            ret = ret
+                .context("github.base_ref", string(base))
+                .context("github.event.pull_request.base.ref", string(base));
+        }
+        ret
+    }
+
+    /// Treat the context value at `path`, e.g. `inputs.deploy`, as known
+    pub fn context(mut self, path: &str, value: Expr) -> Self {
+        self.contexts.insert(path.to_ascii_lowercase(), value);
+        self
+    }
+
+    /// The known context values by lowercase path
+    pub(crate) fn into_contexts(self) -> BTreeMap<String, Expr> {
+        self.contexts
+    }
+
+    /// Assume the job `name` fails when it runs
+    pub fn fail(mut self, name: impl ToString) -> Self {
+        self.failed.insert(name.to_string());
+        self
+    }
+
+    pub fn evaluate(&self, workflow: &Workflow) -> Analysis {
+        let mut analysis = Analysis::default();
+        let mut results = BTreeMap::new();
+        for name in workflow.jobs.keys() {
+            self.job(workflow, name, &mut analysis, &mut results, &mut Vec::new());
+        }
+        // keep the order of the workflow rather than of the dependencies
+        let mut jobs = std::mem::take(&mut analysis.jobs);
+        analysis.jobs = workflow
+            .jobs
+            .keys()
+            .filter_map(|name| Some((name.clone(), jobs.shift_remove(name)?)))
+            .collect();
+        analysis
+    }
+
+    fn job(
+        &self,
+        workflow: &Workflow,
+        name: &str,
+        analysis: &mut Analysis,
+        results: &mut BTreeMap<String, Results>,
+        visiting: &mut Vec<String>,
+    ) -> Results {
+        if let Some(results) = results.get(name) {
+            return *results;
+        }
+        let Some(job) = workflow.jobs.get(name) else {
+            return Results::default();
+        };
+        if visiting.iter().any(|v| v == name) {
+            // a cycle, the workflow is invalid
+            return Results {
+                success: true,
+                failure: true,
+                skipped: true,
+            };
+        }
+        visiting.push(name.to_string());
+        let mut contexts = self.contexts.clone();
+        let mut ancestors = Vec::new();
+        for need in &job.needs {
+            let need_results = self.job(workflow, need, analysis, results, visiting);
+            let result = match (
+                need_results.success,
+                need_results.failure,
+                need_results.skipped,
+            ) {
+                (true, false, false) => Some("success"),
+                (false, true, false) => Some("failure"),
+                (false, false, true) => Some("skipped"),
+                _ => None,
+            };
+            if let Some(result) = result {
+                contexts.insert(
+                    format!("needs.{}.result", need.to_ascii_lowercase()),
+                    Expr::String(result.into()),
+                );
+            }
+            ancestors.extend(self.ancestors(workflow, need, results));
+        }
+        visiting.pop();
+
+        let status = |function: &str| match function {
+            "always" => Some(true),
+            "cancelled" => Some(false),
+            "success" if ancestors.iter().all(|r| !r.failure && !r.skipped) => Some(true),
+            "success" if ancestors.iter().any(|r| !r.success) => Some(false),
+            "failure"
+                if ancestors
+                    .iter()
+                    .any(|r| r.failure && !r.success && !r.skipped) =>
+            {
+                Some(true)
+            }
+            "failure" if ancestors.iter().all(|r| !r.failure) => Some(false),
+            _ => None,
+        };
+        let evaluation = evaluate(job.if_cond.as_deref(), &contexts, &status);
+        let steps = self.steps(job);
+        let fails = self.failed.contains(name);
+        let job_results = match evaluation.outcome {
+            Outcome::Run => Results {
+                success: !fails,
+                failure: fails,
+                skipped: false,
+            },
+            Outcome::Skip => Results {
+                skipped: true,
+                ..Default::default()
+            },
+            Outcome::Runtime => Results {
+                success: !fails,
+                failure: fails,
+                skipped: true,
+            },
+        };
+        results.insert(name.to_string(), job_results);
+        analysis.jobs.insert(
+            name.to_string(),
+            JobEvaluation {
+                outcome: evaluation.outcome,
+                condition: evaluation.condition,
+                steps,
+            },
+        );
+        job_results
+    }
+
+    /// The results of `name` and every job it transitively needs
+    fn ancestors(
+        &self,
+        workflow: &Workflow,
+        name: &str,
+        results: &BTreeMap<String, Results>,
+    ) -> Vec<Results> {
+        let mut ret = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut queue = vec![name.to_string()];
+        while let Some(name) = queue.pop() {
+            if !seen.insert(name.clone()) {
+                continue;
+            }
+            ret.extend(results.get(&name).copied());
+            if let Some(job) = workflow.jobs.get(&name) {
+                queue.extend(job.needs.iter().cloned());
+            }
+        }
+        ret
+    }
+
+    /// Steps run in order and are assumed to succeed
+    fn steps(&self, job: &Job) -> Vec<Evaluation> {
+        let status = |function: &str| match function {
+            "always" | "success" => Some(true),
+            "failure" | "cancelled" => Some(false),
+            _ => None,
+        };
+        job.steps
+            .iter()
+            .map(|step| evaluate(step.if_cond.as_deref(), &self.contexts, &status))
+            .collect()
+    }
+}
+
+impl Workflow {
+    /// Jobs that skip for every event the workflow is triggered by,
+    /// whatever the ref, inputs or outcome of other jobs
+    ///
+    /// Events that aren't modeled are evaluated with only their name known.
+    pub fn dead_jobs(&self) -> Vec<String> {
+        let on = &self.on;
+        let events = [
+            ("push", on.push.is_some()),
+            ("pull_request", on.pull_request.is_some()),
+            ("pull_request_target", on.pull_request_target.is_some()),
+            ("schedule", on.schedule.is_some()),
+            ("workflow_call", on.workflow_call.is_some()),
+            ("workflow_dispatch", on.workflow_dispatch.is_some()),
+            ("workflow_run", on.workflow_run.is_some()),
+        ];
+        let analyses: Vec<_> = events
+            .into_iter()
+            .filter(|(_, listening)| *listening)
+            .map(|(name, _)| name)
+            .chain(on.other.keys().map(String::as_str))
+            .map(|name| Evaluator::new(&Event::builder().name(name).build()).evaluate(self))
+            .collect();
+        if analyses.is_empty() {
+            return Vec::new();
+        }
+        self.jobs
+            .keys()
+            .filter(|name| {
+                analyses
+                    .iter()
+                    .all(|analysis| analysis.jobs[name.as_str()].outcome == Outcome::Skip)
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// The conditions of a set of workflows for one event
+#[derive(Debug, Clone, Serialize)]
+pub struct Conditions {
+    #[serde(skip)]
+    evaluator: Evaluator,
+    #[serde(serialize_with = "event_name")]
+    pub event: Event,
+    pub workflows: Vec<WorkflowConditions>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowConditions {
+    pub path: String,
+    pub name: String,
+    pub triggered: bool,
+    /// The jobs as if the workflow is triggered
+    pub jobs: IndexMap<String, JobEvaluation>,
+    /// Jobs that can't run for any event, see [`Workflow::dead_jobs`]
+    pub dead: Vec<String>,
+}
+
+impl Conditions {
+    pub fn new(event: Event) -> Self {
+        let evaluator = Evaluator::new(&event);
+        Self::with_evaluator(event, evaluator)
+    }
+
+    /// Evaluate with e.g. known inputs or failing jobs
+    pub fn with_evaluator(event: Event, evaluator: Evaluator) -> Self {
+        Self {
+            evaluator,
+            event,
+            workflows: Vec::new(),
+        }
+    }
+
+    /// Evaluate the conditions of `workflow`, read from `path`
+    pub fn add(&mut self, path: impl Into<String>, workflow: &Workflow) {
+        self.workflows.push(WorkflowConditions {
+            path: path.into(),
+            name: workflow.name.clone(),
+            triggered: workflow.on.matches(&self.event).triggered,
+            jobs: self.evaluator.evaluate(workflow).jobs,
+            dead: workflow.dead_jobs(),
+        });
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("conditions are valid json")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut ret = format!("# Conditions for `{}`\n", self.event.name);
+        for workflow in &self.workflows {
+            let _ = writeln!(ret, "\n## {} (`{}`)\n", workflow.name, workflow.path);
+            if !workflow.triggered {
+                ret.push_str("Not triggered by the event, as if it were:\n\n");
+            }
+            ret.push_str("| Job | Runs | Condition | Steps |\n");
+            ret.push_str("| --- | --- | --- | --- |\n");
+            for (name, job) in &workflow.jobs {
+                let steps: Vec<_> = [Outcome::Run, Outcome::Skip, Outcome::Runtime]
+                    .into_iter()
+                    .filter_map(|outcome| {
+                        let count = job.steps.iter().filter(|s| s.outcome == outcome).count();
+                        (count > 0).then(|| format!("{count} {outcome}"))
+                    })
+                    .collect();
+                let _ = writeln!(
+                    ret,
+                    "| `{name}` | {} | {} | {} |",
+                    job.outcome,
+                    job.condition
+                        .as_ref()
+                        .map(|c| format!("`{}`", c.replace('|', "\\|")))
+                        .unwrap_or_default(),
+                    steps.join(", "),
+                );
+            }
+            if !workflow.dead.is_empty() {
+                let dead: Vec<_> = workflow.dead.iter().map(|j| format!("`{j}`")).collect();
+                let _ = writeln!(ret, "\nNever run: {}", dead.join(", "));
+            }
+        }
+        ret
+    }
+}
+
+fn event_name<S: serde::Serializer>(event: &Event, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&event.name)
+}
+
+const STATUS_FUNCTIONS: [&str; 4] = ["success", "always", "failure", "cancelled"];
+
+/// Evaluate an `if`, which defaults to `success()` and is combined with it
+/// unless it calls a status function
+fn evaluate(
+    condition: Option<&str>,
+    contexts: &BTreeMap<String, Expr>,
+    status: &dyn Fn(&str) -> Option<bool>,
+) -> Evaluation {
+    let Ok(parsed) = parse_condition(condition) else {
+        return Evaluation {
+            outcome: Outcome::Runtime,
+            condition: condition.map(String::from),
+        };
+    };
+    let folder = Folder {
+        contexts,
+        status,
+        strict: false,
+    };
+    let folded = folder.fold(&parsed, true);
+    match truthy(&folded) {
+        Some(true) => Evaluation {
+            outcome: Outcome::Run,
+            condition: None,
+        },
+        Some(false) => Evaluation {
+            outcome: Outcome::Skip,
+            condition: None,
+        },
+        None => Evaluation {
+            outcome: Outcome::Runtime,
+            condition: Some(folded.to_string()),
+        },
+    }
+}
+
+fn parse_condition(condition: Option<&str>) -> Result<Expr, expr::ParseError> {
+    let success = Expr::Call("success".into(), Vec::new());
+    let Some(text) = condition else {
+        return Ok(success);
+    };
+    let text = text.trim();
+    let inner = match expr::embedded(text).as_slice() {
+        [only] if only.start == 0 && only.end == text.len() => only.expr,
+        _ => text,
+    };
+    let parsed = expr::parse(inner)?;
+    Ok(if calls_status(&parsed) {
+        parsed
+    } else {
+        Expr::Binary(Box::new(success), BinaryOp::And, Box::new(parsed))
+    })
+}
+
+/// Evaluate an `if` when the workflow runs, with every context known
+pub(crate) fn check(
+    condition: Option<&str>,
+    contexts: &BTreeMap<String, Expr>,
+    status: &dyn Fn(&str) -> Option<bool>,
+) -> Result<bool, String> {
+    let parsed = parse_condition(condition)
+        .map_err(|e| format!("invalid condition `{}`: {e}", condition.unwrap_or_default()))?;
+    let folded = Folder {
+        contexts,
+        status,
+        strict: true,
+    }
+    .fold(&parsed, true);
+    truthy(&folded).ok_or_else(|| format!("can't evaluate `{folded}` locally"))
+}
+
+/// Replace every `${{ ... }}` in `text` with its value, missing context
+/// values are empty
+pub(crate) fn interpolate(text: &str, contexts: &BTreeMap<String, Expr>) -> Result<String, String> {
+    let folder = Folder {
+        contexts,
+        status: &|_| None,
+        strict: true,
+    };
+    let mut ret = String::new();
+    let mut last = 0;
+    for embedded in expr::embedded(text) {
+        ret.push_str(&text[last..embedded.start]);
+        let parsed = expr::parse(embedded.expr)
+            .map_err(|e| format!("invalid expression `{}`: {e}", embedded.text(text)))?;
+        let folded = folder.fold(&parsed, false);
+        ret.push_str(
+            &to_string(&folded).ok_or_else(|| format!("can't evaluate `{folded}` locally"))?,
+        );
+        last = embedded.end;
+    }
+    ret.push_str(&text[last..]);
+    Ok(ret)
+}
+
+fn calls_status(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(name, args) => {
+            STATUS_FUNCTIONS
+                .iter()
+                .any(|f| f.eq_ignore_ascii_case(name))
+                || args.iter().any(calls_status)
+        }
+        Expr::Property(base, _) | Expr::Filter(base) | Expr::Not(base) => calls_status(base),
+        Expr::Index(base, index) => calls_status(base) || calls_status(index),
+        Expr::Binary(lhs, _, rhs) => calls_status(lhs) || calls_status(rhs),
+        _ => false,
+    }
+}
+
+struct Folder<'a> {
+    contexts: &'a BTreeMap<String, Expr>,
+    status: &'a dyn Fn(&str) -> Option<bool>,
+    /// Missing context values are null rather than unknown
+    strict: bool,
+}
+
+impl Folder<'_> {
+    /// Fold `expr`, when `boolean` only its truthiness matters so e.g.
+    /// `a && true` can be folded to `a`
+    fn fold(&self, expr: &Expr, boolean: bool) -> Expr {
+        match expr {
+            Expr::Context(_) | Expr::Property(..) => {
+                let known = expr
+                    .path()
+                    .and_then(|path| self.contexts.get(&path.to_ascii_lowercase()));
+                match known {
+                    Some(value) => value.clone(),
+                    None if self.strict => Expr::Null,
+                    None => expr.clone(),
+                }
+            }
+            Expr::Not(inner) => match self.fold(inner, true) {
+                folded if truthy(&folded).is_some() => Expr::Bool(truthy(&folded) != Some(true)),
+                Expr::Not(inner) if boolean => *inner,
+                folded => Expr::Not(Box::new(folded)),
+            },
+            Expr::Binary(lhs, op @ (BinaryOp::And | BinaryOp::Or), rhs) => {
+                let lhs = self.fold(lhs, boolean);
+                let rhs = self.fold(rhs, boolean);
+                let and = *op == BinaryOp::And;
+                match (truthy(&lhs), truthy(&rhs)) {
+                    // `a && b` is `a` when it's falsy, `a || b` when it's
+                    // truthy
+                    (Some(l), _) if l != and => lhs,
+                    (Some(_), _) => rhs,
+                    (None, Some(r)) if boolean && r == and => lhs,
+                    (None, Some(r)) if boolean => Expr::Bool(r),
+                    _ => Expr::Binary(Box::new(lhs), *op, Box::new(rhs)),
+                }
+            }
+            Expr::Binary(lhs, op, rhs) => {
+                let lhs = self.fold(lhs, false);
+                let rhs = self.fold(rhs, false);
+                match compare(&lhs, &rhs) {
+                    Some(ordering) => Expr::Bool(match op {
+                        BinaryOp::Eq => ordering == Some(Ordering::Equal),
+                        BinaryOp::Ne => ordering != Some(Ordering::Equal),
+                        BinaryOp::Lt => ordering == Some(Ordering::Less),
+                        BinaryOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                        BinaryOp::Gt => ordering == Some(Ordering::Greater),
+                        BinaryOp::Ge => {
+                            matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+                        }
+                        BinaryOp::And | BinaryOp::Or => unreachable!(),
+                    }),
+                    None => Expr::Binary(Box::new(lhs), *op, Box::new(rhs)),
+                }
+            }
+            Expr::Call(name, args) if args.is_empty() && is_status(name) => {
+                match (self.status)(&name.to_ascii_lowercase()) {
+                    Some(known) => Expr::Bool(known),
+                    None => expr.clone(),
+                }
+            }
+            Expr::Call(name, args) => {
+                let args: Vec<_> = args.iter().map(|arg| self.fold(arg, false)).collect();
+                call(name, &args).unwrap_or_else(|| Expr::Call(name.clone(), args))
+            }
+            Expr::Index(base, index) => {
+                let base = self.fold(base, false);
+                match self.fold(index, false) {
+                    Expr::String(name) => self.fold(&Expr::Property(Box::new(base), name), false),
+                    index => Expr::Index(Box::new(base), Box::new(index)),
+                }
+            }
+            Expr::Filter(base) => Expr::Filter(Box::new(self.fold(base, false))),
+            Expr::Null | Expr::Bool(_) | Expr::Number(_) | Expr::String(_) => expr.clone(),
+        }
+    }
+}
+
+fn is_status(name: &str) -> bool {
+    STATUS_FUNCTIONS
+        .iter()
+        .any(|f| f.eq_ignore_ascii_case(name))
+}
+
+/// The truthiness of a literal, `None` for anything else
+fn truthy(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Null => Some(false),
+        Expr::Bool(b) => Some(*b),
+        Expr::Number(n) => Some(*n != 0.0 && !n.is_nan()),
+        Expr::String(s) => Some(!s.is_empty()),
+        _ => None,
+    }
+}
+
+fn to_number(expr: &Expr) -> f64 {
+    match expr {
+        Expr::Null => 0.0,
+        Expr::Bool(b) => f64::from(u8::from(*b)),
+        Expr::Number(n) => *n,
+        Expr::String(s) if s.trim().is_empty() => 0.0,
+        Expr::String(s) => s.trim().parse().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    }
+}
+
+fn to_string(expr: &Expr) -> Option<String> {
+    Some(match expr {
+        Expr::Null => String::new(),
+        Expr::Bool(b) => b.to_string(),
+        Expr::Number(n) => n.to_string(),
+        Expr::String(s) => s.clone(),
+        _ => return None,
+    })
+}
+
+/// Compare two literals with GitHub's loose rules, strings compare case
+/// insensitively and other types are compared as numbers. The inner `None`
+/// means they can't be ordered, the outer that they aren't both literals.
+fn compare(lhs: &Expr, rhs: &Expr) -> Option<Option<Ordering>> {
+    truthy(lhs)?;
+    truthy(rhs)?;
+    Some(match (lhs, rhs) {
+        (Expr::String(l), Expr::String(r)) => {
+            Some(l.to_ascii_lowercase().cmp(&r.to_ascii_lowercase()))
+        }
+        _ => to_number(lhs).partial_cmp(&to_number(rhs)),
+    })
+}
+
+/// Call a function whose arguments are all literals
+fn call(name: &str, args: &[Expr]) -> Option<Expr> {
+    let strings: Vec<_> = args.iter().map(to_string).collect::<Option<_>>()?;
+    let lower: Vec<_> = strings.iter().map(|s| s.to_ascii_lowercase()).collect();
+    Some(match (name.to_ascii_lowercase().as_str(), &lower[..]) {
+        ("contains", [haystack, needle]) => Expr::Bool(haystack.contains(needle.as_str())),
+        ("startswith", [text, prefix]) => Expr::Bool(text.starts_with(prefix.as_str())),
+        ("endswith", [text, suffix]) => Expr::Bool(text.ends_with(suffix.as_str())),
+        ("format", [_, ..]) => Expr::String(format_string(&strings[0], &strings[1..])?),
+        ("fromjson", [_]) => match serde_json::from_str(&strings[0]).ok()? {
+            serde_json::Value::Null => Expr::Null,
+            serde_json::Value::Bool(b) => Expr::Bool(b),
+            serde_json::Value::Number(n) => Expr::Number(n.as_f64()?),
+            serde_json::Value::String(s) => Expr::String(s),
+            _ => return None,
+        },
+        ("tojson", [_]) => Expr::String(match &args[0] {
+            Expr::Null => "null".into(),
+            Expr::String(s) => serde_json::Value::from(s.as_str()).to_string(),
+            _ => strings[0].clone(),
+        }),
+        _ => return None,
+    })
+}
+
+/// `format('{0} {{1}}', a)`, `None` if the format string is invalid
+fn format_string(format: &str, args: &[String]) -> Option<String> {
+    let mut ret = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                ret.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                ret.push('}');
+            }
+            '{' => {
+                let mut idx = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        c => idx.push(c),
+                    }
+                }
+                ret.push_str(args.get(idx.parse::<usize>().ok()?)?);
+            }
+            '}' => return None,
+            c => ret.push(c),
+        }
+    }
+    Some(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"name: release
+on:
+  push:
+  pull_request:
+  workflow_dispatch:
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo test
+      - if: failure()
+        run: ./upload-logs.sh
+      - if: ${{ github.event_name == 'pull_request' }}
+        run: ./comment.sh
+  publish:
+    needs: test
+    if: startsWith(github.ref, 'refs/tags/') && github.event_name == 'push'
+    runs-on: ubuntu-latest
+  docs:
+    needs: publish
+    runs-on: ubuntu-latest
+  notify:
+    needs: publish
+    if: ${{ always() && needs.publish.result != 'skipped' }}
+    runs-on: ubuntu-latest
+  preview:
+    if: github.event_name == 'pull_request' && !github.event.pull_request.draft
+    runs-on: ubuntu-latest
+  nightly:
+    if: github.event_name == 'schedule'
+    runs-on: ubuntu-latest
+"#;
+
+    #[test]
+    fn events() {
+        let workflow: Workflow = serde_yml::from_str(WORKFLOW).unwrap();
+        let tag = Event::builder()
+            .name("push")
+            .git_ref(GitRef::tag("v1.0.0"))
+            .build();
+        let branch = Event::builder().name("push").git_ref("main").build();
+        let pull_request = Event::builder()
+            .name("pull_request")
+            .base_ref("main")
+            .build();
+        let analyses = [
+            ("tag", Evaluator::new(&tag).evaluate(&workflow)),
+            ("branch", Evaluator::new(&branch).evaluate(&workflow)),
+            (
+                "failed tests",
+                Evaluator::new(&tag).fail("test").evaluate(&workflow),
+            ),
+            (
+                "pull request",
+                Evaluator::new(&pull_request).evaluate(&workflow),
+            ),
+        ];
+        insta::assert_yaml_snapshot!(analyses);
+        assert_eq!(workflow.dead_jobs(), ["nightly"]);
+
+        let mut conditions = Conditions::new(pull_request);
+        conditions.add(".github/workflows/release.yml", &workflow);
+        insta::assert_snapshot!(conditions.to_markdown());
+    }
+
+    #[test]
+    fn unmodeled_events() {
+        let workflow: Workflow = serde_yml::from_str(
+            r#"name: release
+on:
+  push:
+  release:
+    types: [published]
+  merge_group:
+jobs:
+  rel:
+    if: github.event_name == 'release'
+    runs-on: ubuntu-latest
+  queue:
+    if: github.event_name == 'merge_group'
+    runs-on: ubuntu-latest
+  never:
+    if: github.event_name == 'schedule'
+    runs-on: ubuntu-latest
+"#,
+        )
+        .unwrap();
+        assert_eq!(workflow.dead_jobs(), ["never"]);
+        let published = Event::builder()
+            .name("release")
+            .activity("published")
+            .build();
+        assert!(workflow.on.matches(&published).triggered);
+        let created = Event::builder().name("release").activity("created").build();
+        assert!(!workflow.on.matches(&created).triggered);
+        let yaml = serde_yml::to_string(&workflow.on).unwrap();
+        assert!(yaml.contains("merge_group") && yaml.contains("release"));
+    }
+
+    #[test]
+    fn folding() {
+        let contexts = BTreeMap::from([
+            ("github.event_name".into(), Expr::String("push".into())),
+            ("inputs.count".into(), Expr::Number(3.0)),
+        ]);
+        let status = |_: &str| Some(true);
+        let fold = |condition: &str| {
+            let evaluation = evaluate(Some(condition), &contexts, &status);
+            match evaluation.condition {
+                Some(condition) => format!("{}: {condition}", evaluation.outcome),
+                None => evaluation.outcome.to_string(),
+            }
+        };
+        let cases = [
+            "github.event_name == 'PUSH'",
+            "inputs.count > '2' && inputs.count != 3",
+            "github.event_name != 'push' || vars.ENABLED",
+            "vars.ENABLED || github.event_name == 'push'",
+            "!(vars.A && github['event_name'] == 'pull_request')",
+            "format('{0}-{1}', github.event_name, inputs.count) == 'push-3'",
+            "contains(fromJSON(vars.EVENTS), github.event_name)",
+            "null == 0 && '' == false && !''",
+            "github.event_name ==",
+        ];
+        let folded: Vec<_> = cases.iter().map(|c| fold(c)).collect();
+        insta::assert_debug_snapshot!(folded);
+    }
+}
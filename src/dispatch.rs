@@ -0,0 +1,251 @@
+//! Validate the inputs of a `workflow_dispatch` before triggering it
+//!
+//! ```
+//! use grackle::{Input, InputType, WorkflowDispatch};
+//!
+//! let dispatch = WorkflowDispatch::builder()
+//!     .input("level", Input::builder()
+//!         .kind(InputType::Choice(vec!["patch".into(), "minor".into()]))
+//!         .required(true)
+//!         .build())
+//!     .build();
+//! let payload = serde_json::json!({ "level": "minor" });
+//! let request = dispatch.validate_payload(payload.as_object().unwrap()).unwrap();
+//! assert_eq!(
+//!     request.gh_command("release.yml", Some("main")),
+//!     "gh workflow run release.yml --ref main -f level=minor",
+//! );
+//! ```
+
+use crate::{InputType, InvalidValue, WorkflowDispatch};
+use serde_json::{Map, Value};
+use std::{collections::BTreeMap, fmt};
+
+impl WorkflowDispatch {
+    /// The most inputs a `workflow_dispatch` can declare
+    pub const MAX_INPUTS: usize = 25;
+
+    /// Check `payload` against the declared inputs, returning the request to
+    /// trigger the dispatch with or every problem found
+    ///
+    /// Inputs are sent as strings, booleans may be given as `true`/`false`
+    /// strings and numbers as numeric strings, like `gh workflow run -f`
+    /// does. Names are case insensitive and sent as they're declared.
+    pub fn validate_payload(
+        &self,
+        payload: &Map<String, Value>,
+    ) -> Result<DispatchRequest, Vec<PayloadError>> {
+        let mut errors = Vec::new();
+        if self.inputs.len() > Self::MAX_INPUTS {
+            errors.push(PayloadError::TooManyInputs(self.inputs.len()));
+        }
+        let mut inputs = BTreeMap::new();
+        for (name, value) in payload {
+            let Some((declared, input)) = self
+                .inputs
+                .iter()
+                .find(|(declared, _)| declared.eq_ignore_ascii_case(name))
+            else {
+                errors.push(PayloadError::Unknown(name.clone()));
+                continue;
+            };
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Bool(b) => b.to_string(),
+                Value::Number(n) => n.to_string(),
+                other => {
+                    errors.push(PayloadError::NotScalar(name.clone(), other.to_string()));
+                    continue;
+                }
+            };
+            if let Some(kind) = &input.kind {
+                if !accepts(kind, &text) {
+                    errors.push(PayloadError::Invalid(
+                        name.clone(),
+                        InvalidValue {
+                            kind: kind.clone(),
+                            value: serde_yml::Value::String(text),
+                        },
+                    ));
+                    continue;
+                }
+            }
+            inputs.insert(declared.clone(), text);
+        }
+        for (name, input) in &self.inputs {
+            if input.required
+                && input.default.is_none()
+                && !payload.keys().any(|k| k.eq_ignore_ascii_case(name))
+            {
+                errors.push(PayloadError::Missing(name.clone()));
+            }
+        }
+        if errors.is_empty() {
+            Ok(DispatchRequest { inputs })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// If `text` is a valid value for an input of `kind` when dispatching
+fn accepts(kind: &InputType, text: &str) -> bool {
+    match kind {
+        InputType::String | InputType::Environment => true,
+        InputType::Boolean => matches!(text, "true" | "false"),
+        InputType::Number => text.parse::<f64>().is_ok_and(f64::is_finite),
+        InputType::Choice(options) => options.iter().any(|o| o == text),
+    }
+}
+
+/// A validated set of inputs to trigger a `workflow_dispatch` with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchRequest {
+    pub inputs: BTreeMap<String, String>,
+}
+
+impl DispatchRequest {
+    /// The body of `POST /repos/{owner}/{repo}/actions/workflows/{workflow}/dispatches`
+    pub fn rest_body(&self, git_ref: &str) -> Value {
+        serde_json::json!({
+            "ref": git_ref,
+            "inputs": self.inputs,
+        })
+    }
+
+    /// The equivalent `gh workflow run` command line, `git_ref` defaults to
+    /// the default branch of the repository
+    pub fn gh_command(&self, workflow: &str, git_ref: Option<&str>) -> String {
+        let mut args = vec![
+            "gh".to_string(),
+            "workflow".into(),
+            "run".into(),
+            quote(workflow),
+        ];
+        if let Some(git_ref) = git_ref {
+            args.push("--ref".into());
+            args.push(quote(git_ref));
+        }
+        for (name, value) in &self.inputs {
+            args.push("-f".into());
+            args.push(quote(&format!("{name}={value}")));
+        }
+        args.join(" ")
+    }
+}
+
+/// Quote `arg` for a POSIX shell if it needs it
+fn quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./:=@,+%".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// A problem with a dispatch payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum PayloadError {
+    /// The number of inputs declared, more than
+    /// [`WorkflowDispatch::MAX_INPUTS`]
+    TooManyInputs(usize),
+    Unknown(String),
+    Missing(String),
+    /// Objects and arrays can't be passed as inputs
+    NotScalar(String, String),
+    Invalid(String, InvalidValue),
+}
+
+impl fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PayloadError::TooManyInputs(count) => write!(
+                f,
+                "{count} inputs declared, a `workflow_dispatch` can have at most {}",
+                WorkflowDispatch::MAX_INPUTS
+            ),
+            PayloadError::Unknown(name) => write!(f, "unknown input `{name}`"),
+            PayloadError::Missing(name) => write!(f, "missing required input `{name}`"),
+            PayloadError::NotScalar(name, value) => {
+                write!(
+                    f,
+                    "input `{name}` must be a string, number or boolean, found {value}"
+                )
+            }
+            PayloadError::Invalid(name, e) => write!(f, "input `{name}` is invalid, {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PayloadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Input;
+    use serde_json::json;
+
+    fn dispatch() -> WorkflowDispatch {
+        serde_yml::from_str(
+            r#"inputs:
+  level:
+    type: choice
+    options: [patch, minor, major]
+    required: true
+  dry-run:
+    type: boolean
+    default: false
+  replicas:
+    type: number
+  notes:
+    type: string
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn valid_payload() {
+        let payload =
+            json!({ "Level": "minor", "dry-run": true, "replicas": "3", "notes": "it's out" });
+        let request = dispatch()
+            .validate_payload(payload.as_object().unwrap())
+            .unwrap();
+        insta::assert_json_snapshot!(request.rest_body("main"));
+        assert_eq!(
+            request.gh_command(".github/workflows/release.yml", Some("v1.2.0")),
+            r#"gh workflow run .github/workflows/release.yml --ref v1.2.0 -f dry-run=true -f level=minor -f 'notes=it'\''s out' -f replicas=3"#
+        );
+    }
+
+    #[test]
+    fn invalid_payload() {
+        let payload =
+            json!({ "dry-run": "yes", "replicas": "many", "level": ["patch"], "extra": 1 });
+        let errors = dispatch()
+            .validate_payload(payload.as_object().unwrap())
+            .unwrap_err();
+        let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "input `dry-run` is invalid, `yes` is not a boolean",
+                "unknown input `extra`",
+                "input `level` must be a string, number or boolean, found [\"patch\"]",
+                "input `replicas` is invalid, `many` is not a number",
+            ]
+        );
+
+        let mut many = WorkflowDispatch::builder().build();
+        for idx in 0..=WorkflowDispatch::MAX_INPUTS {
+            many.inputs.insert(format!("i{idx}"), Input::default());
+        }
+        let payload = json!({ "i0": "x" });
+        assert_eq!(
+            many.validate_payload(payload.as_object().unwrap())
+                .unwrap_err(),
+            [PayloadError::TooManyInputs(26)]
+        );
+    }
+}
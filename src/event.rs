@@ -0,0 +1,474 @@
+//! Simulate which workflows an event triggers
+//!
+//! ```
+//! use grackle::{event::{Event, GitRef}, Triggers};
+//!
+//! let on: Triggers = serde_yml::from_str("push:\n  paths-ignore: [docs/**]\n").unwrap();
+//! let event = Event::builder()
+//!     .name("push")
+//!     .git_ref(GitRef::branch("main"))
+//!     .path("docs/index.md")
+//!     .build();
+//! let decision = on.matches(&event);
+//! assert!(!decision.triggered);
+//! assert_eq!(
+//!     decision.reasons.last().unwrap().to_string(),
+//!     "every changed path matches `paths-ignore`",
+//! );
+//! ```
+
+use crate::{
+    filter::{last_match, FilterPattern},
+    Triggers,
+};
+use std::{fmt, str::FromStr};
+use typed_builder::TypedBuilder;
+
+/// GitHub only looks at this many changed files when evaluating path
+/// filters
+pub const MAX_DIFF_FILES: usize = 300;
+
+/// The activity types `pull_request` and `pull_request_target` run for when
+/// they don't list `types`
+const DEFAULT_PULL_REQUEST_TYPES: [&str; 3] = ["opened", "synchronize", "reopened"];
+
+/// An event that may trigger a workflow
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(mutators(
+    pub fn path(&mut self, path: impl ToString) {
+        self.paths.push(path.to_string());
+    }
+))]
+pub struct Event {
+    /// e.g. `push` or `pull_request`
+    #[builder(setter(into))]
+    pub name: String,
+    /// The activity type, e.g. `opened`, unknown activities pass `types`
+    #[builder(default, setter(into, strip_option))]
+    pub activity: Option<String>,
+    /// The ref pushed, or the head of the run for `workflow_run`
+    #[builder(default, setter(into, strip_option))]
+    pub git_ref: Option<GitRef>,
+    /// The branch a pull request targets
+    #[builder(default, setter(into, strip_option))]
+    pub base_ref: Option<String>,
+    /// The changed files, in the order of the diff
+    #[builder(via_mutators(init = Vec::new()))]
+    pub paths: Vec<String>,
+}
+
+/// A branch or a tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitRef {
+    Branch(String),
+    Tag(String),
+}
+
+impl GitRef {
+    pub fn branch(name: impl ToString) -> Self {
+        Self::Branch(name.to_string())
+    }
+
+    pub fn tag(name: impl ToString) -> Self {
+        Self::Tag(name.to_string())
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            GitRef::Branch(name) | GitRef::Tag(name) => name,
+        }
+    }
+}
+
+impl FromStr for GitRef {
+    type Err = std::convert::Infallible;
+
+    /// `refs/tags/<name>` is a tag, `refs/heads/<name>` and bare names are
+    /// branches
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.strip_prefix("refs/tags/") {
+            Some(tag) => Self::tag(tag),
+            None => Self::branch(s.strip_prefix("refs/heads/").unwrap_or(s)),
+        })
+    }
+}
+
+impl From<&str> for GitRef {
+    fn from(s: &str) -> Self {
+        s.parse().unwrap_or_else(|e| match e {})
+    }
+}
+
+impl fmt::Display for GitRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitRef::Branch(name) => write!(f, "branch `{name}`"),
+            GitRef::Tag(name) => write!(f, "tag `{name}`"),
+        }
+    }
+}
+
+/// Whether an event triggers a workflow, with the reason for each filter
+/// that was evaluated, the last one decides when it doesn't
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub triggered: bool,
+    pub reasons: Vec<Reason>,
+}
+
+/// Why a filter let an event through or not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The workflow doesn't listen to the event
+    NotListening(String),
+    Listening(String),
+    /// The activity and whether `types` includes it
+    Activity(String, bool),
+    /// The ref, the filter and the last pattern including it, `None` for
+    /// ignore filters none of whose patterns match
+    Included(GitRef, &'static str, Option<String>),
+    /// The ref, the filter and the pattern excluding it if any
+    Excluded(GitRef, &'static str, Option<String>),
+    /// Only the other kind of ref is filtered, e.g. branches when only
+    /// `tags` is set
+    OtherRefKind(GitRef),
+    /// The event has no ref to filter
+    NoRef(&'static str),
+    /// The filter and the first changed path it includes
+    PathIncluded(&'static str, String),
+    /// No changed path is included by the filter, and if the diff was cut
+    /// at [`MAX_DIFF_FILES`]
+    NoPathIncluded(&'static str, bool),
+    /// Path filters aren't evaluated for pushed tags
+    TagPaths,
+}
+
+impl Reason {
+    fn passes(&self) -> bool {
+        match self {
+            Reason::NotListening(_)
+            | Reason::Excluded(..)
+            | Reason::OtherRefKind(_)
+            | Reason::NoRef(_)
+            | Reason::NoPathIncluded(..) => false,
+            Reason::Activity(_, included) => *included,
+            Reason::Listening(_)
+            | Reason::Included(..)
+            | Reason::PathIncluded(..)
+            | Reason::TagPaths => true,
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::NotListening(event) => write!(f, "the workflow isn't triggered by `{event}`"),
+            Reason::Listening(event) => write!(f, "the workflow is triggered by `{event}`"),
+            Reason::Activity(activity, true) => write!(f, "`types` includes `{activity}`"),
+            Reason::Activity(activity, false) => write!(f, "`types` doesn't include `{activity}`"),
+            Reason::Included(git_ref, filter, Some(pattern)) => {
+                write!(f, "{git_ref} matches `{pattern}` in `{filter}`")
+            }
+            Reason::Included(git_ref, filter, None) => {
+                write!(f, "{git_ref} matches no pattern in `{filter}`")
+            }
+            Reason::Excluded(git_ref, filter, Some(pattern)) => {
+                write!(f, "{git_ref} is excluded by `{pattern}` in `{filter}`")
+            }
+            Reason::Excluded(git_ref, filter, None) => {
+                write!(f, "{git_ref} matches no pattern in `{filter}`")
+            }
+            Reason::OtherRefKind(git_ref @ GitRef::Branch(_)) => {
+                write!(f, "{git_ref} doesn't trigger, only tags are filtered")
+            }
+            Reason::OtherRefKind(git_ref @ GitRef::Tag(_)) => {
+                write!(f, "{git_ref} doesn't trigger, only branches are filtered")
+            }
+            Reason::NoRef(filter) => write!(f, "the event has no ref to match `{filter}`"),
+            Reason::PathIncluded(filter, path) => {
+                if *filter == "paths-ignore" {
+                    write!(f, "`{path}` doesn't match `{filter}`")
+                } else {
+                    write!(f, "`{path}` matches `{filter}`")
+                }
+            }
+            Reason::NoPathIncluded(filter, truncated) => {
+                if *filter == "paths-ignore" {
+                    write!(f, "every changed path matches `{filter}`")?;
+                } else {
+                    write!(f, "no changed path matches `{filter}`")?;
+                }
+                if *truncated {
+                    write!(
+                        f,
+                        ", only the first {MAX_DIFF_FILES} files of the diff are considered"
+                    )?;
+                }
+                Ok(())
+            }
+            Reason::TagPaths => write!(f, "path filters aren't evaluated for tags"),
+        }
+    }
+}
+
+/// The filters of a trigger that apply to an event
+#[derive(Default)]
+struct Filters<'a> {
+    types: &'a [String],
+    default_types: &'a [&'a str],
+    /// `branches`, `branches-ignore`, `tags` and `tags-ignore`
+    refs: [&'a [FilterPattern]; 4],
+    paths: &'a [FilterPattern],
+    paths_ignore: &'a [FilterPattern],
+    /// If branch filters apply to the base of a pull request
+    base: bool,
+}
+
+impl Triggers {
+    /// Whether `event` triggers the workflow, following GitHub's semantics
+    /// for activity types and ref and path filters
+    pub fn matches(&self, event: &Event) -> Decision {
+        // the activity types are the only filter most other events have
+        let other_types: Vec<String> = self
+            .other
+            .get(&event.name)
+            .and_then(|config| config.get("types"))
+            .and_then(|types| serde_yml::from_value(types.clone()).ok())
+            .unwrap_or_default();
+        let filters = match event.name.as_str() {
+            "push" => self.push.as_ref().map(|push| Filters {
+                types: &push.types,
+                refs: [
+                    &push.branches,
+                    &push.branches_ignore,
+                    &push.tags,
+                    &push.tags_ignore,
+                ],
+                paths: &push.paths,
+                paths_ignore: &push.paths_ignore,
+                ..Default::default()
+            }),
+            "pull_request" | "pull_request_target" => {
+                let pr = if event.name == "pull_request" {
+                    &self.pull_request
+                } else {
+                    &self.pull_request_target
+                };
+                pr.as_ref().map(|pr| Filters {
+                    types: &pr.types,
+                    default_types: &DEFAULT_PULL_REQUEST_TYPES,
+                    refs: [&pr.branches, &pr.branches_ignore, &[], &[]],
+                    paths: &pr.paths,
+                    paths_ignore: &pr.paths_ignore,
+                    base: true,
+                })
+            }
+            "workflow_run" => self.workflow_run.as_ref().map(|run| Filters {
+                types: &run.types,
+                refs: [&run.branches, &run.branches_ignore, &[], &[]],
+                ..Default::default()
+            }),
+            "workflow_call" => self.workflow_call.as_ref().map(|_| Filters::default()),
+            "workflow_dispatch" => self.workflow_dispatch.as_ref().map(|_| Filters::default()),
+            "schedule" => self.schedule.as_ref().map(|_| Filters::default()),
+            name => self.other.get(name).map(|_| Filters {
+                types: &other_types,
+                ..Default::default()
+            }),
+        };
+        let Some(filters) = filters else {
+            return Decision {
+                triggered: false,
+                reasons: vec![Reason::NotListening(event.name.clone())],
+            };
+        };
+
+        let mut reasons = vec![Reason::Listening(event.name.clone())];
+        let checks = [
+            activity(&filters, event),
+            refs(&filters, event),
+            paths(&filters, event),
+        ];
+        for reason in checks.into_iter().flatten() {
+            let passes = reason.passes();
+            reasons.push(reason);
+            if !passes {
+                return Decision {
+                    triggered: false,
+                    reasons,
+                };
+            }
+        }
+        Decision {
+            triggered: true,
+            reasons,
+        }
+    }
+}
+
+fn activity(filters: &Filters<'_>, event: &Event) -> Option<Reason> {
+    let activity = event.activity.as_ref()?;
+    let included = if filters.types.is_empty() {
+        if filters.default_types.is_empty() {
+            return None;
+        }
+        filters.default_types.contains(&activity.as_str())
+    } else {
+        filters.types.contains(activity)
+    };
+    Some(Reason::Activity(activity.clone(), included))
+}
+
+fn refs(filters: &Filters<'_>, event: &Event) -> Option<Reason> {
+    let [branches, branches_ignore, tags, tags_ignore] = filters.refs;
+    let branch_filtered = !branches.is_empty() || !branches_ignore.is_empty();
+    let tag_filtered = !tags.is_empty() || !tags_ignore.is_empty();
+    if !branch_filtered && !tag_filtered {
+        return None;
+    }
+    let git_ref = if filters.base {
+        event.base_ref.as_deref().map(GitRef::branch)
+    } else {
+        event.git_ref.clone()
+    };
+    let Some(git_ref) = git_ref else {
+        let filter = if branches.is_empty() {
+            "branches-ignore"
+        } else {
+            "branches"
+        };
+        return Some(Reason::NoRef(filter));
+    };
+    let (include, ignore, names) = match git_ref {
+        GitRef::Branch(_) if !branch_filtered => return Some(Reason::OtherRefKind(git_ref)),
+        GitRef::Tag(_) if !tag_filtered => return Some(Reason::OtherRefKind(git_ref)),
+        GitRef::Branch(_) => (branches, branches_ignore, ["branches", "branches-ignore"]),
+        GitRef::Tag(_) => (tags, tags_ignore, ["tags", "tags-ignore"]),
+    };
+    if !include.is_empty() {
+        return Some(match last_match(include, git_ref.name()) {
+            Some(pattern) if pattern.is_negated() => {
+                Reason::Excluded(git_ref, names[0], Some(pattern.to_string()))
+            }
+            Some(pattern) => Reason::Included(git_ref, names[0], Some(pattern.to_string())),
+            None => Reason::Excluded(git_ref, names[0], None),
+        });
+    }
+    Some(match ignore.iter().find(|p| p.matches(git_ref.name())) {
+        Some(pattern) => Reason::Excluded(git_ref, names[1], Some(pattern.to_string())),
+        None => Reason::Included(git_ref, names[1], None),
+    })
+}
+
+fn paths(filters: &Filters<'_>, event: &Event) -> Option<Reason> {
+    if filters.paths.is_empty() && filters.paths_ignore.is_empty() {
+        return None;
+    }
+    if matches!(event.git_ref, Some(GitRef::Tag(_))) && !filters.base {
+        return Some(Reason::TagPaths);
+    }
+    let truncated = event.paths.len() > MAX_DIFF_FILES;
+    let diff = &event.paths[..event.paths.len().min(MAX_DIFF_FILES)];
+    let (filter, included) = if filters.paths.is_empty() {
+        let included = diff
+            .iter()
+            .find(|path| !filters.paths_ignore.iter().any(|p| p.matches(path)));
+        ("paths-ignore", included)
+    } else {
+        let included = diff
+            .iter()
+            .find(|path| last_match(filters.paths, path).is_some_and(|p| !p.is_negated()));
+        ("paths", included)
+    };
+    Some(match included {
+        Some(path) => Reason::PathIncluded(filter, path.clone()),
+        None => Reason::NoPathIncluded(filter, truncated),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triggers() -> Triggers {
+        serde_yml::from_str(
+            r#"push:
+  branches: ["releases/**", "!releases/**-alpha", "releases/1.0-alpha"]
+  paths: ["src/**", "!src/**/*.md"]
+pull_request:
+  types: [opened, labeled]
+  branches-ignore: [gh-pages]
+  paths-ignore: [docs/**]
+workflow_run:
+  branches: [main]
+"#,
+        )
+        .unwrap()
+    }
+
+    fn decide(event: Event) -> (bool, String) {
+        let decision = triggers().matches(&event);
+        let reasons: Vec<_> = decision.reasons.iter().map(ToString::to_string).collect();
+        (decision.triggered, reasons.join("; "))
+    }
+
+    #[test]
+    fn filters() {
+        let push = |git_ref: &str, path: &str| {
+            decide(
+                Event::builder()
+                    .name("push")
+                    .git_ref(git_ref)
+                    .path(path)
+                    .build(),
+            )
+        };
+        let pull_request = |activity: &str, base: &str, path: &str| {
+            decide(
+                Event::builder()
+                    .name("pull_request")
+                    .activity(activity)
+                    .base_ref(base)
+                    .path(path)
+                    .build(),
+            )
+        };
+        let cases = [
+            push("refs/heads/releases/1.0", "src/lib.rs"),
+            push("refs/heads/releases/2.0-alpha", "src/lib.rs"),
+            push("releases/1.0-alpha", "src/lib.rs"),
+            push("main", "src/lib.rs"),
+            push("releases/1.0", "src/docs/guide.md"),
+            push("refs/tags/v1.0", "src/lib.rs"),
+            pull_request("opened", "main", "src/lib.rs"),
+            pull_request("synchronize", "main", "src/lib.rs"),
+            pull_request("labeled", "gh-pages", "src/lib.rs"),
+            pull_request("labeled", "main", "docs/index.md"),
+            decide(
+                Event::builder()
+                    .name("workflow_run")
+                    .git_ref("main")
+                    .build(),
+            ),
+            decide(Event::builder().name("schedule").build()),
+        ];
+        insta::assert_debug_snapshot!(cases);
+    }
+
+    #[test]
+    fn diff_limit() {
+        let on: Triggers = serde_yml::from_str("push:\n  paths: [docs/**]\n").unwrap();
+        let mut event = Event::builder().name("push").git_ref("main").build();
+        event.paths = (0..MAX_DIFF_FILES)
+            .map(|idx| format!("src/{idx}.rs"))
+            .collect();
+        event.paths.push("docs/index.md".into());
+        let decision = on.matches(&event);
+        assert!(!decision.triggered);
+        assert_eq!(
+            decision.reasons.last().unwrap().to_string(),
+            "no changed path matches `paths`, only the first 300 files of the diff are considered"
+        );
+    }
+}
@@ -0,0 +1,640 @@
+//! Helpers for the `${{ ... }}` expressions embedded in workflow values
+
+use std::fmt;
+
+/// An expression embedded in a string value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Embedded<'a> {
+    /// The byte offset of the opening `${{`
+    pub start: usize,
+    /// The byte offset just after the closing `}}`
+    pub end: usize,
+    /// The text between the braces with surrounding whitespace trimmed
+    pub expr: &'a str,
+}
+
+impl<'a> Embedded<'a> {
+    /// The full `${{ ... }}` text including the delimiters
+    pub fn text<'b>(&self, value: &'b str) -> &'b str {
+        &value[self.start..self.end]
+    }
+}
+
+/// Find every `${{ ... }}` in `value`
+pub fn embedded(value: &str) -> Vec<Embedded<'_>> {
+    let mut ret = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = value[offset..].find("${{") {
+        let start = offset + start;
+        let inner_start = start + 3;
+        let Some(len) = find_close(&value[inner_start..]) else {
+            break;
+        };
+        let end = inner_start + len + 2;
+        ret.push(Embedded {
+            start,
+            end,
+            expr: value[inner_start..inner_start + len].trim(),
+        });
+        offset = end;
+    }
+    ret
+}
+
+/// Find the closing `}}` skipping over any string literals
+fn find_close(text: &str) -> Option<usize> {
+    let mut in_string = false;
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\'' => in_string = !in_string,
+            b'}' if !in_string && bytes.get(idx + 1) == Some(&b'}') => return Some(idx),
+            _ => {}
+        }
+        idx += 1;
+    }
+    None
+}
+
+/// A reference to a context value, e.g. `github.event.issue.title`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    /// The normalized dotted path, index access is rewritten as a property
+    /// access (`steps['a'].outputs` is `steps.a.outputs`) and numeric or `*`
+    /// indexes become `*`
+    pub path: String,
+    /// The byte offset of the reference in the expression
+    pub offset: usize,
+}
+
+impl Reference {
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.path.split('.')
+    }
+
+    /// The context this references, e.g. `github` or `steps`
+    pub fn context(&self) -> &str {
+        self.segments().next().unwrap_or_default()
+    }
+
+    /// If this references `prefix` or something inside of it, context names
+    /// and properties are case insensitive
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        let mut segments = self.segments();
+        prefix
+            .split('.')
+            .all(|expected| segments.next().map(|s| s.eq_ignore_ascii_case(expected)) == Some(true))
+    }
+}
+
+/// Every context reference in the expression `expr`, function names and
+/// literals are skipped
+pub fn references(expr: &str) -> Vec<Reference> {
+    let bytes = expr.as_bytes();
+    let mut ret = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let c = bytes[idx];
+        if c == b'\'' {
+            idx += 1;
+            while idx < bytes.len() {
+                if bytes[idx] == b'\'' {
+                    if bytes.get(idx + 1) == Some(&b'\'') {
+                        idx += 2;
+                        continue;
+                    }
+                    break;
+                }
+                idx += 1;
+            }
+            idx += 1;
+            continue;
+        }
+        if !(c.is_ascii_alphabetic() || c == b'_') {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        let mut path = String::new();
+        idx = ident_end(bytes, idx);
+        path.push_str(&expr[start..idx]);
+        loop {
+            match bytes.get(idx) {
+                Some(b'.') => {
+                    let seg_start = idx + 1;
+                    if bytes.get(seg_start) == Some(&b'*') {
+                        path.push_str(".*");
+                        idx = seg_start + 1;
+                        continue;
+                    }
+                    let seg_end = ident_end(bytes, seg_start);
+                    if seg_end == seg_start {
+                        break;
+                    }
+                    path.push('.');
+                    path.push_str(&expr[seg_start..seg_end]);
+                    idx = seg_end;
+                }
+                Some(b'[') => {
+                    let Some(close) = expr[idx..].find(']') else {
+                        break;
+                    };
+                    let inner = expr[idx + 1..idx + close].trim();
+                    if let Some(key) = inner
+                        .strip_prefix('\'')
+                        .and_then(|inner| inner.strip_suffix('\''))
+                    {
+                        path.push('.');
+                        path.push_str(key);
+                    } else {
+                        path.push_str(".*");
+                    }
+                    idx += close + 1;
+                }
+                _ => break,
+            }
+        }
+        let rest = expr[idx..].trim_start();
+        let is_call = rest.starts_with('(');
+        let is_literal = matches!(path.as_str(), "true" | "false" | "null");
+        if !is_call && !is_literal {
+            ret.push(Reference {
+                path,
+                offset: start,
+            });
+        }
+    }
+    ret
+}
+
+fn ident_end(bytes: &[u8], start: usize) -> usize {
+    let mut idx = start;
+    while idx < bytes.len()
+        && (bytes[idx].is_ascii_alphanumeric() || matches!(bytes[idx], b'_' | b'-'))
+    {
+        idx += 1;
+    }
+    idx
+}
+
+/// A parsed expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    /// A named context, e.g. `github`
+    Context(String),
+    /// `.name` or `['name']`
+    Property(Box<Expr>, String),
+    /// `[expr]` where `expr` isn't a string literal
+    Index(Box<Expr>, Box<Expr>),
+    /// `.*` or `[*]`
+    Filter(Box<Expr>),
+    Call(String, Vec<Expr>),
+    Not(Box<Expr>),
+    Binary(Box<Expr>, BinaryOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum BinaryOp {
+    #[strum(serialize = "||")]
+    Or,
+    #[strum(serialize = "&&")]
+    And,
+    #[strum(serialize = "==")]
+    Eq,
+    #[strum(serialize = "!=")]
+    Ne,
+    #[strum(serialize = "<")]
+    Lt,
+    #[strum(serialize = "<=")]
+    Le,
+    #[strum(serialize = ">")]
+    Gt,
+    #[strum(serialize = ">=")]
+    Ge,
+}
+
+impl BinaryOp {
+    /// Higher binds tighter
+    fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Ne => 3,
+            BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => 4,
+        }
+    }
+}
+
+impl Expr {
+    /// The dotted path of a context reference, `None` if any part of it
+    /// isn't a literal property
+    pub fn path(&self) -> Option<String> {
+        match self {
+            Expr::Context(name) => Some(name.clone()),
+            Expr::Property(base, name) => Some(format!("{}.{name}", base.path()?)),
+            _ => None,
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary(_, op, _) => op.precedence(),
+            Expr::Not(_) => 5,
+            _ => 6,
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operand = |f: &mut fmt::Formatter<'_>, expr: &Expr, min: u8| {
+            if expr.precedence() < min {
+                write!(f, "({expr})")
+            } else {
+                write!(f, "{expr}")
+            }
+        };
+        match self {
+            Expr::Null => f.write_str("null"),
+            Expr::Bool(b) => write!(f, "{b}"),
+            Expr::Number(n) => write!(f, "{n}"),
+            Expr::String(s) => write!(f, "'{}'", s.replace('\'', "''")),
+            Expr::Context(name) => f.write_str(name),
+            Expr::Property(base, name) => {
+                operand(f, base, 6)?;
+                let ident = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && ident_end(name.as_bytes(), 0) == name.len();
+                if ident {
+                    write!(f, ".{name}")
+                } else {
+                    write!(f, "[{}]", Expr::String(name.clone()))
+                }
+            }
+            Expr::Index(base, index) => {
+                operand(f, base, 6)?;
+                write!(f, "[{index}]")
+            }
+            Expr::Filter(base) => {
+                operand(f, base, 6)?;
+                f.write_str(".*")
+            }
+            Expr::Call(name, args) => {
+                write!(f, "{name}(")?;
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{arg}")?;
+                }
+                f.write_str(")")
+            }
+            Expr::Not(inner) => {
+                f.write_str("!")?;
+                operand(f, inner, 5)
+            }
+            Expr::Binary(lhs, op, rhs) => {
+                operand(f, lhs, op.precedence())?;
+                write!(f, " {op} ")?;
+                operand(f, rhs, op.precedence() + 1)
+            }
+        }
+    }
+}
+
+/// Why an expression couldn't be parsed, with the byte offset of the
+/// problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Ident(String),
+    Op(BinaryOp),
+    Not,
+    Dot,
+    Star,
+    Comma,
+    Open,
+    Close,
+    OpenBracket,
+    CloseBracket,
+}
+
+fn tokenize(expr: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let bytes = expr.as_bytes();
+    let mut ret = Vec::new();
+    let mut idx = 0;
+    let error = |offset: usize, message: &str| ParseError {
+        offset,
+        message: message.into(),
+    };
+    while idx < bytes.len() {
+        let start = idx;
+        let c = bytes[idx];
+        let two = bytes.get(idx..idx + 2);
+        let token = match c {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                idx += 1;
+                continue;
+            }
+            b'\'' => {
+                let mut value = String::new();
+                idx += 1;
+                loop {
+                    let Some(len) = expr[idx..].find('\'') else {
+                        return Err(error(start, "unterminated string"));
+                    };
+                    value.push_str(&expr[idx..idx + len]);
+                    idx += len + 1;
+                    if bytes.get(idx) == Some(&b'\'') {
+                        value.push('\'');
+                        idx += 1;
+                    } else {
+                        break;
+                    }
+                }
+                ret.push((start, Token::String(value)));
+                continue;
+            }
+            _ if two == Some(b"||") => Token::Op(BinaryOp::Or),
+            _ if two == Some(b"&&") => Token::Op(BinaryOp::And),
+            _ if two == Some(b"==") => Token::Op(BinaryOp::Eq),
+            _ if two == Some(b"!=") => Token::Op(BinaryOp::Ne),
+            _ if two == Some(b"<=") => Token::Op(BinaryOp::Le),
+            _ if two == Some(b">=") => Token::Op(BinaryOp::Ge),
+            b'<' => Token::Op(BinaryOp::Lt),
+            b'>' => Token::Op(BinaryOp::Gt),
+            b'!' => Token::Not,
+            b'.' if !bytes.get(idx + 1).is_some_and(u8::is_ascii_digit) => Token::Dot,
+            b'*' => Token::Star,
+            b',' => Token::Comma,
+            b'(' => Token::Open,
+            b')' => Token::Close,
+            b'[' => Token::OpenBracket,
+            b']' => Token::CloseBracket,
+            b'0'..=b'9' | b'-' | b'+' | b'.' => {
+                let len = expr[idx..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+                    .unwrap_or(expr.len() - idx);
+                let text = &expr[idx..idx + len];
+                idx += len;
+                let number = match text.strip_prefix("0x") {
+                    Some(hex) => i64::from_str_radix(hex, 16).ok().map(|n| n as f64),
+                    None => text.parse::<f64>().ok(),
+                };
+                let Some(number) = number else {
+                    return Err(error(start, "invalid number"));
+                };
+                ret.push((start, Token::Number(number)));
+                continue;
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                idx = ident_end(bytes, idx);
+                let token = match &expr[start..idx] {
+                    "null" => Token::Null,
+                    "true" => Token::Bool(true),
+                    "false" => Token::Bool(false),
+                    ident => Token::Ident(ident.into()),
+                };
+                ret.push((start, token));
+                continue;
+            }
+            _ => return Err(error(start, "unexpected character")),
+        };
+        idx += match token {
+            Token::Op(BinaryOp::Lt | BinaryOp::Gt) => 1,
+            Token::Op(_) => 2,
+            _ => 1,
+        };
+        ret.push((start, token));
+    }
+    Ok(ret)
+}
+
+/// Parse the expression `expr`, without the `${{ }}` delimiters
+pub fn parse(expr: &str) -> Result<Expr, ParseError> {
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        idx: 0,
+        end: expr.len(),
+    };
+    let ret = parser.binary(1)?;
+    match parser.peek() {
+        None => Ok(ret),
+        Some(_) => Err(parser.error("unexpected token")),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    idx: usize,
+    end: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.idx += 1;
+        self.tokens.get(self.idx - 1).map(|(_, t)| t)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            offset: self.tokens.get(self.idx).map_or(self.end, |(o, _)| *o),
+            message: message.into(),
+        }
+    }
+
+    fn expect(&mut self, token: Token, message: &str) -> Result<(), ParseError> {
+        if self.peek() == Some(&token) {
+            self.idx += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn binary(&mut self, min: u8) -> Result<Expr, ParseError> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op)) = self.peek() {
+            let op = *op;
+            if op.precedence() < min {
+                break;
+            }
+            self.idx += 1;
+            let rhs = self.binary(op.precedence() + 1)?;
+            lhs = Expr::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.idx += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        let mut expr = self.primary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.idx += 1;
+                    expr = match self.next() {
+                        Some(Token::Star) => Expr::Filter(Box::new(expr)),
+                        Some(Token::Ident(name)) => Expr::Property(Box::new(expr), name.clone()),
+                        // `null`, `true` and `false` are valid property names
+                        Some(Token::Null) => Expr::Property(Box::new(expr), "null".into()),
+                        Some(Token::Bool(b)) => Expr::Property(Box::new(expr), b.to_string()),
+                        _ => {
+                            self.idx -= 1;
+                            return Err(self.error("expected a property name"));
+                        }
+                    };
+                }
+                Some(Token::OpenBracket) => {
+                    self.idx += 1;
+                    if self.peek() == Some(&Token::Star) {
+                        self.idx += 1;
+                        expr = Expr::Filter(Box::new(expr));
+                    } else {
+                        expr = match self.binary(1)? {
+                            Expr::String(name) => Expr::Property(Box::new(expr), name),
+                            index => Expr::Index(Box::new(expr), Box::new(index)),
+                        };
+                    }
+                    self.expect(Token::CloseBracket, "expected `]`")?;
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        let Some(token) = self.next().cloned() else {
+            self.idx -= 1;
+            return Err(self.error("expected an expression"));
+        };
+        Ok(match token {
+            Token::Null => Expr::Null,
+            Token::Bool(b) => Expr::Bool(b),
+            Token::Number(n) => Expr::Number(n),
+            Token::String(s) => Expr::String(s),
+            Token::Open => {
+                let inner = self.binary(1)?;
+                self.expect(Token::Close, "expected `)`")?;
+                inner
+            }
+            Token::Ident(name) if self.peek() == Some(&Token::Open) => {
+                self.idx += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::Close) {
+                    loop {
+                        args.push(self.binary(1)?);
+                        if self.peek() != Some(&Token::Comma) {
+                            break;
+                        }
+                        self.idx += 1;
+                    }
+                }
+                self.expect(Token::Close, "expected `)`")?;
+                Expr::Call(name, args)
+            }
+            Token::Ident(name) => Expr::Context(name),
+            _ => {
+                self.idx -= 1;
+                return Err(self.error("expected an expression"));
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_embedded() {
+        let value = "echo ${{ github.head_ref }} ${{ format('{0}}}', x) }}";
+        let found = embedded(value);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].expr, "github.head_ref");
+        assert_eq!(found[0].text(value), "${{ github.head_ref }}");
+        assert_eq!(found[1].expr, "format('{0}}}', x)");
+    }
+
+    #[test]
+    fn find_references() {
+        let refs: Vec<_> = references(
+            "contains(github.event.commits[0].message, 'skip ci') && steps['meta'].outputs.tag-name || true",
+        )
+        .into_iter()
+        .map(|r| r.path)
+        .collect();
+        assert_eq!(
+            refs,
+            [
+                "github.event.commits.*.message",
+                "steps.meta.outputs.tag-name"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_expressions() {
+        let cases = [
+            "github.event_name == 'push' && !contains(github.ref, 'refs/tags/')",
+            "(a || b) && c",
+            "a || b && c",
+            "!(a == 1)",
+            "steps['meta'].outputs.tag-name",
+            "matrix['os name']",
+            "github.event.commits[0].message",
+            "fromJSON(needs.plan.outputs.val).ci[*].name",
+            "format('{0}''s', 0x10, 1.5e3, -2)",
+            "x < 1 == true",
+        ];
+        let parsed: Vec<_> = cases
+            .iter()
+            .map(|expr| parse(expr).unwrap().to_string())
+            .collect();
+        insta::assert_debug_snapshot!(parsed);
+        let errors: Vec<_> = ["a &&", "a b", "f(a", "'open", "a.", "a ~ b"]
+            .iter()
+            .map(|expr| parse(expr).unwrap_err().to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "expected an expression at offset 4",
+                "unexpected token at offset 2",
+                "expected `)` at offset 3",
+                "unterminated string at offset 0",
+                "expected a property name at offset 2",
+                "unexpected character at offset 2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,462 @@
+//! GitHub's glob dialect for `branches`, `tags` and `paths` filters
+//!
+//! `*` matches anything but `/`, `**` matches anything, `?` and `+` make the
+//! previous character optional or repeatable, `[]` matches one of the
+//! alphanumeric characters or ranges it lists and a leading `!` negates the
+//! pattern. `\` escapes the special characters.
+//!
+//! ```
+//! use grackle::filter::FilterPattern;
+//!
+//! let pattern: FilterPattern = "v[0-9]+.[0-9]+".parse().unwrap();
+//! assert!(pattern.matches("v10.2"));
+//! assert!(!pattern.matches("v10.2-rc"));
+//!
+//! let regex = FilterPattern::new(r"^v\d+\.\d+$");
+//! assert_eq!(regex.problems().len(), 4);
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// A compiled filter pattern
+///
+/// Patterns that don't parse are still usable, the invalid parts are matched
+/// literally and [`FilterPattern::problems`] lists what's wrong with them.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct FilterPattern {
+    source: String,
+    negated: bool,
+    tokens: Vec<Token>,
+    /// The ranges of each `[]`
+    classes: Vec<Vec<(char, char)>>,
+    problems: Vec<PatternError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Atom {
+    Char(char),
+    /// An index into the classes of the pattern
+    Class(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Atom(Atom),
+    /// `?`
+    Optional(Atom),
+    /// `+`
+    OneOrMore(Atom),
+    /// `*`
+    Star,
+    /// `**`
+    DoubleStar,
+    /// `**/`, which also matches nothing so `**/a` matches `a`
+    DoubleStarSlash,
+}
+
+/// A problem with a filter pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    Empty,
+    /// The `[` at this offset is never closed
+    UnclosedClass(usize),
+    EmptyClass,
+    /// Classes may only contain alphanumeric characters and ranges of them
+    InvalidClassChar(char),
+    /// `?` or `+` that doesn't follow a character or class
+    NothingToRepeat(char),
+    TrailingEscape,
+    /// Regex syntax that GitHub matches literally, and how to write it as a
+    /// glob
+    Regex(String, &'static str),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternError::Empty => write!(f, "the pattern is empty"),
+            PatternError::UnclosedClass(offset) => {
+                write!(f, "the `[` at offset {offset} is never closed")
+            }
+            PatternError::EmptyClass => write!(f, "`[]` matches nothing"),
+            PatternError::InvalidClassChar(c) => write!(
+                f,
+                "`{c}` can't be used in `[]`, only alphanumeric characters and ranges can"
+            ),
+            PatternError::NothingToRepeat(c) => {
+                write!(f, "`{c}` has no character before it to repeat")
+            }
+            PatternError::TrailingEscape => write!(f, "the pattern ends with `\\`"),
+            PatternError::Regex(found, hint) => {
+                write!(f, "`{found}` is regex syntax, {hint}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+impl FilterPattern {
+    /// Compile `source`, keeping any problems with it
+    pub fn new(source: impl Into<String>) -> Self {
+        let source = source.into();
+        let (negated, body) = match source.strip_prefix('!') {
+            Some(body) => (true, body),
+            None => (false, source.as_str()),
+        };
+        let parser = Parser::new(body).parse();
+        Self {
+            negated,
+            tokens: parser.tokens,
+            classes: parser.classes,
+            problems: parser.problems,
+            source,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// If the pattern starts with `!`
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    pub fn problems(&self) -> &[PatternError] {
+        &self.problems
+    }
+
+    /// If `name` matches the pattern, ignoring a leading `!`
+    pub fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        self.matches_at(&self.tokens, &name)
+    }
+
+    fn matches_at(&self, tokens: &[Token], name: &[char]) -> bool {
+        let Some((token, rest)) = tokens.split_first() else {
+            return name.is_empty();
+        };
+        let first = |atom: &Atom| name.first().is_some_and(|c| self.matches_atom(atom, *c));
+        match token {
+            Token::Atom(atom) => first(atom) && self.matches_at(rest, &name[1..]),
+            Token::Optional(atom) => {
+                self.matches_at(rest, name) || (first(atom) && self.matches_at(rest, &name[1..]))
+            }
+            Token::OneOrMore(atom) => {
+                let count = name
+                    .iter()
+                    .take_while(|c| self.matches_atom(atom, **c))
+                    .count();
+                (1..=count).any(|idx| self.matches_at(rest, &name[idx..]))
+            }
+            Token::Star => {
+                let count = name.iter().take_while(|c| **c != '/').count();
+                (0..=count).any(|idx| self.matches_at(rest, &name[idx..]))
+            }
+            Token::DoubleStar => (0..=name.len()).any(|idx| self.matches_at(rest, &name[idx..])),
+            Token::DoubleStarSlash => {
+                self.matches_at(rest, name)
+                    || (1..=name.len())
+                        .filter(|idx| name[idx - 1] == '/')
+                        .any(|idx| self.matches_at(rest, &name[idx..]))
+            }
+        }
+    }
+
+    fn matches_atom(&self, atom: &Atom, c: char) -> bool {
+        match atom {
+            Atom::Char(expected) => *expected == c,
+            Atom::Class(idx) => self.classes[*idx]
+                .iter()
+                .any(|(lo, hi)| (*lo..=*hi).contains(&c)),
+        }
+    }
+}
+
+/// The last of `patterns` matching `name`, which includes it unless it's
+/// negated
+pub fn last_match<'a>(patterns: &'a [FilterPattern], name: &str) -> Option<&'a FilterPattern> {
+    patterns.iter().rev().find(|pattern| pattern.matches(name))
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    idx: usize,
+    classes: Vec<Vec<(char, char)>>,
+    tokens: Vec<Token>,
+    problems: Vec<PatternError>,
+    source: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            idx: 0,
+            classes: Vec::new(),
+            tokens: Vec::new(),
+            problems: Vec::new(),
+            source,
+        }
+    }
+
+    fn regex(&mut self, found: impl ToString, hint: &'static str) {
+        let problem = PatternError::Regex(found.to_string(), hint);
+        if !self.problems.contains(&problem) {
+            self.problems.push(problem);
+        }
+    }
+
+    fn parse(mut self) -> Self {
+        if self.source.is_empty() {
+            self.problems.push(PatternError::Empty);
+        }
+        if self.source.starts_with('^') {
+            self.regex("^", "patterns always match the whole name");
+        }
+        if self.source.ends_with('$') && !self.source.ends_with("\\$") {
+            self.regex("$", "patterns always match the whole name");
+        }
+        while let Some(&c) = self.chars.get(self.idx) {
+            self.idx += 1;
+            match c {
+                '\\' => self.escape(),
+                '*' => self.star(),
+                '?' | '+' => self.repeat(c),
+                '[' => self.class(),
+                '(' | ')' => {
+                    self.regex("()", "groups aren't supported");
+                    self.literal(c);
+                }
+                '|' => {
+                    self.regex("|", "list each alternative as its own pattern");
+                    self.literal(c);
+                }
+                '{' => {
+                    self.regex(
+                        "{",
+                        "braces aren't supported, list each alternative as its own pattern",
+                    );
+                    self.literal(c);
+                }
+                '.' if self.chars.get(self.idx) == Some(&'+') => {
+                    self.regex(".+", "use `*` to match any characters");
+                    self.literal(c);
+                }
+                '.' if self.chars.get(self.idx) == Some(&'*')
+                    && self.idx >= 2
+                    && !matches!(self.chars[self.idx - 2], '/' | '*') =>
+                {
+                    self.regex(".*", "use `*` to match any characters");
+                    self.literal(c);
+                }
+                _ => self.literal(c),
+            }
+        }
+        self
+    }
+
+    fn literal(&mut self, c: char) {
+        self.tokens.push(Token::Atom(Atom::Char(c)));
+    }
+
+    fn escape(&mut self) {
+        let Some(&c) = self.chars.get(self.idx) else {
+            self.problems.push(PatternError::TrailingEscape);
+            self.literal('\\');
+            return;
+        };
+        self.idx += 1;
+        match c {
+            '*' | '[' | ']' | '!' | '?' | '+' | '\\' => {}
+            'd' => self.regex("\\d", "use `[0-9]` to match a digit"),
+            'w' | 's' | 'D' | 'W' | 'S' | 'b' => {
+                self.regex(format!("\\{c}"), "use `[]` to list the characters to match")
+            }
+            _ => self.regex(format!("\\{c}"), "only special characters need escaping"),
+        }
+        self.literal(c);
+    }
+
+    fn star(&mut self) {
+        if self.chars.get(self.idx) != Some(&'*') {
+            self.tokens.push(Token::Star);
+            return;
+        }
+        self.idx += 1;
+        if self.chars.get(self.idx) == Some(&'/') {
+            self.idx += 1;
+            self.tokens.push(Token::DoubleStarSlash);
+        } else {
+            self.tokens.push(Token::DoubleStar);
+        }
+    }
+
+    fn repeat(&mut self, c: char) {
+        let atom = match self.tokens.last() {
+            Some(Token::Atom(atom)) => *atom,
+            _ => {
+                self.problems.push(PatternError::NothingToRepeat(c));
+                self.literal(c);
+                return;
+            }
+        };
+        self.tokens.pop();
+        self.tokens.push(if c == '?' {
+            Token::Optional(atom)
+        } else {
+            Token::OneOrMore(atom)
+        });
+    }
+
+    fn class(&mut self) {
+        let start = self.idx - 1;
+        let Some(len) = self.chars[self.idx..].iter().position(|c| *c == ']') else {
+            self.problems.push(PatternError::UnclosedClass(start));
+            self.literal('[');
+            return;
+        };
+        let body: Vec<char> = self.chars[self.idx..self.idx + len].to_vec();
+        self.idx += len + 1;
+        if body.is_empty() {
+            self.problems.push(PatternError::EmptyClass);
+        }
+        if body.first() == Some(&'^') {
+            self.regex("[^", "negated classes aren't supported");
+        }
+        let mut ranges = Vec::new();
+        let mut idx = 0;
+        while idx < body.len() {
+            let c = body[idx];
+            if !(c.is_ascii_alphanumeric() || c == '^' && idx == 0) {
+                self.problems.push(PatternError::InvalidClassChar(c));
+            }
+            match body.get(idx + 1..idx + 3) {
+                Some(['-', hi]) => {
+                    ranges.push((c, *hi));
+                    idx += 3;
+                }
+                _ => {
+                    ranges.push((c, c));
+                    idx += 1;
+                }
+            }
+        }
+        self.classes.push(ranges);
+        self.tokens
+            .push(Token::Atom(Atom::Class(self.classes.len() - 1)));
+    }
+}
+
+impl From<String> for FilterPattern {
+    fn from(source: String) -> Self {
+        Self::new(source)
+    }
+}
+
+impl From<&str> for FilterPattern {
+    fn from(source: &str) -> Self {
+        Self::new(source)
+    }
+}
+
+impl From<FilterPattern> for String {
+    fn from(pattern: FilterPattern) -> Self {
+        pattern.source
+    }
+}
+
+impl FromStr for FilterPattern {
+    type Err = PatternError;
+
+    /// Compile `s`, failing on the first problem with it
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pattern = Self::new(s);
+        match pattern.problems.first() {
+            Some(problem) => Err(problem.clone()),
+            None => Ok(pattern),
+        }
+    }
+}
+
+impl PartialEq for FilterPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for FilterPattern {}
+
+impl fmt::Debug for FilterPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FilterPattern").field(&self.source).finish()
+    }
+}
+
+impl fmt::Display for FilterPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching() {
+        let cases = [
+            ("feature/*", "feature/my-branch", true),
+            ("feature/*", "feature/your/branch", false),
+            ("feature/**", "feature/your/branch", true),
+            ("**/README.md", "README.md", true),
+            ("**/README.md", "docs/README.md", true),
+            ("docs/*.md", "docs/a/b.md", false),
+            ("v2*", "v2.1", true),
+            ("v?1", "1", true),
+            ("v?1", "v1", true),
+            ("v[12].[0-9]+", "v1.10", true),
+            ("v[12].[0-9]+", "v3.1", false),
+            ("v[12].[0-9]+", "v1.", false),
+            ("ma+in", "maaain", true),
+            ("\\*", "*", true),
+            ("\\*", "a", false),
+            ("!main", "main", true),
+            ("releases/**-alpha", "releases/beta/3-alpha", true),
+        ];
+        for (pattern, name, expected) in cases {
+            let pattern: FilterPattern = pattern.parse().unwrap();
+            assert_eq!(pattern.matches(name), expected, "{pattern} {name}");
+        }
+    }
+
+    #[test]
+    fn problems() {
+        let problems = |pattern: &str| -> Vec<String> {
+            FilterPattern::new(pattern)
+                .problems()
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+        assert_eq!(problems(""), ["the pattern is empty"]);
+        assert_eq!(problems("+a"), ["`+` has no character before it to repeat"]);
+        assert_eq!(
+            problems("v[0-9_]"),
+            ["`_` can't be used in `[]`, only alphanumeric characters and ranges can"]
+        );
+        assert_eq!(
+            problems("release-.*"),
+            ["`.*` is regex syntax, use `*` to match any characters"]
+        );
+        assert!(problems(".github/**/.*").is_empty());
+        assert!(problems("*.*").is_empty());
+        assert_eq!(
+            "v\\".parse::<FilterPattern>().unwrap_err(),
+            PatternError::TrailingEscape
+        );
+    }
+}
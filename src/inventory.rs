@@ -0,0 +1,441 @@
+//! An inventory of the secrets, variables and environment variables
+//! referenced by workflows
+
+use crate::{expr, Job, JobStep, Workflow};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_yml::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Kind {
+    /// `secrets.*`, including `github.token`
+    Secret,
+    /// `vars.*`
+    Var,
+    /// `env.*` or a shell reference to an environment variable declared in
+    /// the workflow
+    Env,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Reference {
+    pub kind: Kind,
+    pub name: String,
+    /// Where the reference is relative to its workflow, job or step, e.g.
+    /// `env.TOKEN` or `with.token`
+    pub location: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Inventory {
+    pub workflows: Vec<WorkflowInventory>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowInventory {
+    pub path: String,
+    pub name: String,
+    /// References outside of any job, e.g. in the top level `env`
+    pub references: Vec<Reference>,
+    pub jobs: IndexMap<String, JobInventory>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobInventory {
+    /// The name of the deployment environment the job runs under, its secrets
+    /// and variables take precedence over the repository's
+    pub environment: Option<String>,
+    /// `secrets: inherit` passes every secret to the called workflow
+    pub inherits_secrets: bool,
+    /// References outside of the job's steps
+    pub references: Vec<Reference>,
+    pub steps: Vec<StepInventory>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepInventory {
+    pub index: usize,
+    /// The step's `name`, `id`, `uses` or the first line of its `run`
+    pub label: String,
+    /// The step uses an action or command that reads the `GITHUB_TOKEN`
+    /// without it being passed explicitly
+    pub implicit_github_token: bool,
+    pub references: Vec<Reference>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the references of `workflow`, read from `path`
+    pub fn add(&mut self, path: impl Into<String>, workflow: &Workflow) {
+        self.workflows.push(WorkflowInventory::new(path, workflow));
+    }
+
+    /// Every secret and variable, with the `workflow/job` pairs that use it
+    pub fn summary(&self) -> BTreeMap<(Kind, String), BTreeSet<String>> {
+        let mut ret: BTreeMap<_, BTreeSet<_>> = BTreeMap::new();
+        for workflow in &self.workflows {
+            let mut add = |r: &Reference, user: String| {
+                if r.kind != Kind::Env {
+                    ret.entry((r.kind, r.name.clone()))
+                        .or_default()
+                        .insert(user);
+                }
+            };
+            for r in &workflow.references {
+                add(r, workflow.path.clone());
+            }
+            for (name, job) in &workflow.jobs {
+                let user = format!("{}/{name}", workflow.path);
+                let step_refs = job.steps.iter().flat_map(|s| &s.references);
+                for r in job.references.iter().chain(step_refs) {
+                    add(r, user.clone());
+                }
+            }
+        }
+        ret
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("inventory is valid json")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut ret = String::from("# Secrets and variables\n");
+        let summary = self.summary();
+        if !summary.is_empty() {
+            ret.push_str("\n| Kind | Name | Used by |\n| --- | --- | --- |\n");
+            for ((kind, name), users) in &summary {
+                let users: Vec<_> = users.iter().map(|u| format!("`{u}`")).collect();
+                let _ = writeln!(ret, "| {kind} | `{name}` | {} |", users.join(", "));
+            }
+        }
+        for workflow in &self.workflows {
+            let _ = write!(ret, "\n## {} (`{}`)\n\n", workflow.name, workflow.path);
+            ret.push_str("| Job | Step | Environment | Kind | Name | Location |\n");
+            ret.push_str("| --- | --- | --- | --- | --- | --- |\n");
+            for r in &workflow.references {
+                row(&mut ret, "", "", "", r);
+            }
+            for (name, job) in &workflow.jobs {
+                let environment = job.environment.as_deref().unwrap_or_default();
+                if job.inherits_secrets {
+                    let _ = writeln!(
+                        ret,
+                        "| {name} |  | {environment} | secret | * | `secrets: inherit` |"
+                    );
+                }
+                for r in &job.references {
+                    row(&mut ret, name, "", environment, r);
+                }
+                for step in &job.steps {
+                    let label = format!("{}: {}", step.index, step.label.replace('|', "\\|"));
+                    if step.implicit_github_token {
+                        let _ = writeln!(
+                            ret,
+                            "| {name} | {label} | {environment} | secret | `GITHUB_TOKEN` | implicit |"
+                        );
+                    }
+                    for r in &step.references {
+                        row(&mut ret, name, &label, environment, r);
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
+
+fn row(out: &mut String, job: &str, step: &str, environment: &str, r: &Reference) {
+    let _ = writeln!(
+        out,
+        "| {job} | {step} | {environment} | {} | `{}` | `{}` |",
+        r.kind, r.name, r.location
+    );
+}
+
+impl WorkflowInventory {
+    pub fn new(path: impl Into<String>, workflow: &Workflow) -> Self {
+        let declared: BTreeSet<&str> = workflow.env.keys().map(String::as_str).collect();
+        let mut references = Vec::new();
+        if let Ok(Value::Mapping(map)) = serde_yml::to_value(workflow) {
+            for (key, value) in map {
+                if key.as_str() != Some("jobs") {
+                    collect(&value, key.as_str().unwrap_or_default(), &mut references);
+                }
+            }
+        }
+        let jobs = workflow
+            .jobs
+            .iter()
+            .map(|(name, job)| {
+                let mut declared = declared.clone();
+                declared.extend(job.env.keys().map(String::as_str));
+                let mut env = workflow.env.clone();
+                env.extend(job.env.clone());
+                let token_env = env
+                    .into_iter()
+                    .filter(|(_, value)| references_token(value))
+                    .map(|(key, _)| key)
+                    .collect();
+                (name.clone(), JobInventory::new(job, &declared, &token_env))
+            })
+            .collect();
+        Self {
+            path: path.into(),
+            name: workflow.name.clone(),
+            references,
+            jobs,
+        }
+    }
+}
+
+impl JobInventory {
+    /// `token_env` are the workflow and job env vars holding the token
+    fn new(job: &Job, declared: &BTreeSet<&str>, token_env: &BTreeSet<String>) -> Self {
+        let mut references = Vec::new();
+        if let Ok(Value::Mapping(map)) = serde_yml::to_value(job) {
+            for (key, value) in map {
+                if key.as_str() != Some("steps") {
+                    collect(&value, key.as_str().unwrap_or_default(), &mut references);
+                }
+            }
+        }
+        let steps = job
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                let mut declared = declared.clone();
+                declared.extend(step.env.keys().map(String::as_str));
+                let mut references = Vec::new();
+                if let Ok(value) = serde_yml::to_value(step) {
+                    collect(&value, "", &mut references);
+                }
+                if let Some(run) = &step.run {
+                    for r in shell_references(run, &declared) {
+                        if !references.contains(&r) {
+                            references.push(r);
+                        }
+                    }
+                }
+                let explicit_token = references
+                    .iter()
+                    .any(|r| r.kind == Kind::Secret && r.name.eq_ignore_ascii_case("GITHUB_TOKEN"));
+                let inherited =
+                    |name: &str| token_env.contains(name) && !step.env.contains_key(name);
+                // `gh` reads the token from either variable on its own
+                let gh = step.run.as_deref().map(runs_gh).unwrap_or(false)
+                    && (inherited("GH_TOKEN") || inherited("GITHUB_TOKEN"));
+                let inherited_token = gh
+                    || references
+                        .iter()
+                        .any(|r| r.kind == Kind::Env && inherited(&r.name));
+                StepInventory {
+                    index,
+                    label: label(step),
+                    implicit_github_token: !explicit_token
+                        && (inherited_token || token_default(step)),
+                    references,
+                }
+            })
+            .collect();
+        Self {
+            environment: job.environment.name.clone(),
+            inherits_secrets: job.secrets.is_inherit(),
+            references,
+            steps,
+        }
+    }
+}
+
+/// Actions whose input defaults to `github.token`, by `owner/repo` and the
+/// name of that input
+const TOKEN_DEFAULTS: &[(&str, &str)] = &[
+    ("actions/add-to-project", "github-token"),
+    ("actions/dependency-review-action", "repo-token"),
+    ("actions/first-interaction", "repo-token"),
+    ("actions/github-script", "github-token"),
+    ("actions/labeler", "repo-token"),
+    ("actions/stale", "repo-token"),
+    ("github/codeql-action", "token"),
+    ("peter-evans/create-or-update-comment", "token"),
+    ("peter-evans/create-pull-request", "token"),
+    ("softprops/action-gh-release", "token"),
+];
+
+/// The step uses an action that falls back to `github.token` without the
+/// input being set
+fn token_default(step: &JobStep) -> bool {
+    let Some(uses) = step.uses.as_deref() else {
+        return false;
+    };
+    let action = uses.split('@').next().unwrap_or_default().to_lowercase();
+    TOKEN_DEFAULTS.iter().any(|(repo, input)| {
+        (action == *repo || action.starts_with(&format!("{repo}/")))
+            && !step.with.contains_key(*input)
+    })
+}
+
+/// `script` runs the `gh` cli
+fn runs_gh(script: &str) -> bool {
+    script
+        .split(|c: char| c.is_whitespace() || matches!(c, ';' | '|' | '&' | '(' | '`'))
+        .any(|word| word == "gh")
+}
+
+/// `value` expands `github.token` or `secrets.GITHUB_TOKEN`
+fn references_token(value: &str) -> bool {
+    expr::embedded(value).iter().any(|embedded| {
+        expr::references(embedded.expr).iter().any(|r| {
+            r.starts_with("github.token") || r.path.eq_ignore_ascii_case("secrets.GITHUB_TOKEN")
+        })
+    })
+}
+
+fn label(step: &JobStep) -> String {
+    step.name
+        .clone()
+        .or_else(|| step.id.clone())
+        .or_else(|| step.uses.clone())
+        .or_else(|| {
+            let run = step.run.as_deref()?;
+            Some(run.lines().next().unwrap_or_default().trim().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Collect the references in every string inside of `value`
+fn collect(value: &Value, location: &str, references: &mut Vec<Reference>) {
+    let child = |key: &str| {
+        if location.is_empty() {
+            key.to_string()
+        } else {
+            format!("{location}.{key}")
+        }
+    };
+    match value {
+        Value::String(s) => {
+            for embedded in expr::embedded(s) {
+                for r in expr::references(embedded.expr) {
+                    let mut segments = r.segments();
+                    let kind = match segments.next() {
+                        Some("secrets") => Kind::Secret,
+                        Some("vars") => Kind::Var,
+                        Some("env") => Kind::Env,
+                        Some("github") if r.starts_with("github.token") => {
+                            references.push(Reference {
+                                kind: Kind::Secret,
+                                name: "GITHUB_TOKEN".to_string(),
+                                location: location.to_string(),
+                            });
+                            continue;
+                        }
+                        _ => continue,
+                    };
+                    let Some(name) = segments.next().filter(|n| *n != "*") else {
+                        continue;
+                    };
+                    let reference = Reference {
+                        kind,
+                        name: name.to_string(),
+                        location: location.to_string(),
+                    };
+                    if !references.contains(&reference) {
+                        references.push(reference);
+                    }
+                }
+            }
+        }
+        Value::Sequence(seq) => {
+            for (idx, value) in seq.iter().enumerate() {
+                collect(value, &format!("{location}[{idx}]"), references);
+            }
+        }
+        Value::Mapping(map) => {
+            for (key, value) in map {
+                collect(value, &child(key.as_str().unwrap_or_default()), references);
+            }
+        }
+        Value::Tagged(tagged) => collect(&tagged.value, location, references),
+        _ => {}
+    }
+}
+
+/// `$NAME` and `${NAME}` in a script for the environment variables declared
+/// in the workflow
+fn shell_references(script: &str, declared: &BTreeSet<&str>) -> Vec<Reference> {
+    let mut seen = BTreeSet::new();
+    let mut ret = Vec::new();
+    for (idx, _) in script.match_indices('$') {
+        let rest = &script[idx + 1..];
+        let rest = rest.strip_prefix('{').unwrap_or(rest);
+        let rest = rest.strip_prefix("env:").unwrap_or(rest);
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let name = &rest[..end];
+        if declared.contains(name) && seen.insert(name) {
+            ret.push(Reference {
+                kind: Kind::Env,
+                name: name.to_string(),
+                location: "run".to_string(),
+            });
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"name: release
+on: push
+env:
+  REGISTRY: ${{ vars.REGISTRY }}
+jobs:
+  publish:
+    runs-on: ubuntu-latest
+    environment: production
+    env:
+      NPM_TOKEN: ${{ secrets.NPM_TOKEN }}
+      GH_TOKEN: ${{ github.token }}
+    steps:
+      - uses: actions/checkout@v4
+      - name: Publish
+        run: |
+          npm publish --registry "$REGISTRY"
+          echo "${{ env.NPM_TOKEN != '' }}"
+      - run: gh release upload "$GITHUB_REF_NAME" dist/*
+        env:
+          GITHUB_REF_NAME: ${{ github.ref_name }}
+      - run: gh release view "$GITHUB_REF_NAME" --repo "$GH_REPO"
+      - uses: softprops/action-gh-release@v2
+      - uses: softprops/action-gh-release@v2
+        with:
+          token: ${{ secrets.GITHUB_TOKEN }}
+  call:
+    uses: octo-org/workflows/.github/workflows/deploy.yml@v1
+    secrets: inherit
+    with:
+      region: ${{ vars.REGION }}
+"#;
+
+    #[test]
+    fn inventory() {
+        let workflow: Workflow = serde_yml::from_str(WORKFLOW).unwrap();
+        let mut inventory = Inventory::new();
+        inventory.add(".github/workflows/release.yml", &workflow);
+        insta::assert_yaml_snapshot!(inventory);
+        insta::assert_snapshot!(inventory.to_markdown());
+    }
+}
@@ -1,9 +1,37 @@
+pub mod action;
+pub mod actions;
+pub mod affected;
+pub mod codegen;
+pub mod condition;
+pub mod dispatch;
+pub mod event;
+pub mod expr;
+pub mod filter;
+pub mod inventory;
+pub mod lint;
+pub mod metadata;
+pub mod permissions;
+pub mod report;
+pub mod reusable;
+#[cfg(unix)]
+pub mod runner;
+pub mod sarif;
+pub mod span;
+#[cfg(test)]
+mod testing;
+pub mod uses;
+
+use filter::FilterPattern;
 use indexmap::IndexMap;
+use reusable::ReusableWorkflow;
 use serde::{Deserialize, Serialize};
 use serde_yml::Value;
 use std::collections::BTreeMap;
 use typed_builder::TypedBuilder;
 
+/// Re-exported for the builders generated by [`codegen`]
+pub use typed_builder;
+
 #[derive(Debug, Clone, Serialize, Deserialize, TypedBuilder)]
 #[serde(rename_all = "kebab-case")]
 #[builder(mutators(
@@ -25,6 +53,9 @@ use typed_builder::TypedBuilder;
     pub fn on_workflow_dispatch(&mut self, disp: WorkflowDispatch) {
         self.on.workflow_dispatch = Some(disp);
     }
+    pub fn on_workflow_run(&mut self, run: WorkflowRun) {
+        self.on.workflow_run = Some(run);
+    }
     pub fn actions_write_permission(&mut self) {
         if let Some(perms) = self.permissions.as_mut() {
             perms.actions = PermissionValue::Write;
@@ -273,20 +304,85 @@ pub struct Workflow {
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
-#[serde(rename_all = "snake_case")]
+#[serde(remote = "Self", rename_all = "snake_case")]
 pub struct Triggers {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pull_request: Option<PullRequest>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pull_request_target: Option<PullRequest>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub push: Option<Push>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub schedule: Option<Schedule>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub workflow_call: Option<WorkflowCall>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub workflow_dispatch: Option<WorkflowDispatch>,
+    #[serde(
+        default,
+        deserialize_with = "null_as_default",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub workflow_run: Option<WorkflowRun>,
+    /// The events that aren't modeled, e.g. `release` or `merge_group`, with
+    /// their configuration as written
+    #[serde(flatten)]
+    #[builder(default)]
+    pub other: BTreeMap<String, Value>,
+}
+
+impl Serialize for Triggers {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Triggers::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Triggers {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // `on` may be a single event name, a list of event names or a map of
+        // event names to their filters
+        let value = match Value::deserialize(deserializer)? {
+            Value::String(event) => Value::Mapping(serde_yml::Mapping::from_iter([(
+                Value::String(event),
+                Value::Null,
+            )])),
+            Value::Sequence(events) => Value::Mapping(serde_yml::Mapping::from_iter(
+                events.into_iter().map(|event| (event, Value::Null)),
+            )),
+            other => other,
+        };
+        Triggers::deserialize(value).map_err(serde::de::Error::custom)
+    }
 }
 
 impl Triggers {
@@ -297,6 +393,8 @@ impl Triggers {
             && self.schedule.is_none()
             && self.workflow_call.is_none()
             && self.workflow_dispatch.is_none()
+            && self.workflow_run.is_none()
+            && self.other.is_empty()
     }
 }
 
@@ -304,35 +402,35 @@ impl Triggers {
 #[serde(rename_all = "kebab-case")]
 #[builder(mutators(
     pub fn branch(&mut self, branch: impl ToString) {
-        self.branches.push(branch.to_string());
+        self.branches.push(FilterPattern::new(branch.to_string()));
     }
     pub fn ignore_branch(&mut self, branch: impl ToString) {
-        self.branches_ignore.push(branch.to_string());
+        self.branches_ignore.push(FilterPattern::new(branch.to_string()));
     }
     pub fn path(&mut self, path: impl ToString) {
-        self.paths.push(path.to_string());
+        self.paths.push(FilterPattern::new(path.to_string()));
     }
     pub fn ignore_path(&mut self, path: impl ToString) {
-        self.paths_ignore.push(path.to_string());
+        self.paths_ignore.push(FilterPattern::new(path.to_string()));
     }
     pub fn r#type(&mut self, value: impl ToString) {
         self.types.push(value.to_string());
     }
 ))]
 pub struct PullRequest {
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    pub branches: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    pub branches_ignore: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub branches_ignore: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    pub paths: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    pub paths_ignore: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths_ignore: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub types: Vec<String>,
 }
@@ -351,47 +449,47 @@ impl PullRequest {
 #[serde(rename_all = "kebab-case")]
 #[builder(mutators(
     pub fn branch(&mut self, branch: impl ToString) {
-        self.branches.push(branch.to_string());
+        self.branches.push(FilterPattern::new(branch.to_string()));
     }
     pub fn ignore_branch(&mut self, branch: impl ToString) {
-        self.branches_ignore.push(branch.to_string());
+        self.branches_ignore.push(FilterPattern::new(branch.to_string()));
     }
     pub fn tag(&mut self, tag: impl ToString) {
-        self.tags.push(tag.to_string());
+        self.tags.push(FilterPattern::new(tag.to_string()));
     }
     pub fn ignore_tag(&mut self, tag: impl ToString) {
-        self.tags_ignore.push(tag.to_string());
+        self.tags_ignore.push(FilterPattern::new(tag.to_string()));
     }
     pub fn path(&mut self, path: impl ToString) {
-        self.paths.push(path.to_string());
+        self.paths.push(FilterPattern::new(path.to_string()));
     }
     pub fn ignore_path(&mut self, path: impl ToString) {
-        self.paths_ignore.push(path.to_string());
+        self.paths_ignore.push(FilterPattern::new(path.to_string()));
     }
     pub fn r#type(&mut self, value: impl ToString) {
         self.types.push(value.to_string());
     }
 ))]
 pub struct Push {
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    branches: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    branches: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    tags: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    branches_ignore: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    branches_ignore: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    tags_ignore: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags_ignore: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    paths: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    paths: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    paths_ignore: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    paths_ignore: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     types: Vec<String>,
 }
@@ -419,10 +517,10 @@ impl Push {
     }
 ))]
 pub struct Schedule {
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     cron: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     types: Vec<String>,
 }
@@ -456,27 +554,32 @@ impl Schedule {
     }
 ))]
 pub struct WorkflowCall {
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub inputs: BTreeMap<String, Input>,
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub outputs: BTreeMap<String, Output>,
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub secrets: BTreeMap<String, Secret>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub branches: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub branches_ignore: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub types: Vec<String>,
 }
 
 impl WorkflowCall {
+    /// A handle to call the workflow this is the trigger of, found at `uses`
+    pub fn handle(&self, uses: impl ToString) -> ReusableWorkflow {
+        ReusableWorkflow::new(uses, self.clone())
+    }
+
     pub fn is_empty(&self) -> bool {
         self.inputs.is_empty()
             && self.outputs.is_empty()
@@ -498,10 +601,10 @@ impl WorkflowCall {
     }
 ))]
 pub struct WorkflowDispatch {
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub inputs: BTreeMap<String, Input>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub types: Vec<String>,
 }
@@ -513,53 +616,224 @@ impl WorkflowDispatch {
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[serde(rename_all = "kebab-case")]
+#[builder(mutators(
+    pub fn workflow(&mut self, name: impl ToString) {
+        self.workflows.push(name.to_string());
+    }
+    pub fn branch(&mut self, branch: impl ToString) {
+        self.branches.push(FilterPattern::new(branch.to_string()));
+    }
+    pub fn ignore_branch(&mut self, branch: impl ToString) {
+        self.branches_ignore.push(FilterPattern::new(branch.to_string()));
+    }
+    pub fn r#type(&mut self, value: impl ToString) {
+        self.types.push(value.to_string());
+    }
+))]
+pub struct WorkflowRun {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub workflows: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub branches: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub branches_ignore: Vec<FilterPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(via_mutators(init = Default::default()))]
+    pub types: Vec<String>,
+}
+
+impl WorkflowRun {
+    pub fn is_empty(&self) -> bool {
+        self.workflows.is_empty()
+            && self.branches.is_empty()
+            && self.branches_ignore.is_empty()
+            && self.types.is_empty()
+    }
+}
+
+/// An input of `workflow_call` or `workflow_dispatch`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TypedBuilder)]
+#[serde(try_from = "InputRepr", into = "InputRepr")]
 #[builder(field_defaults(default, setter(into)))]
 pub struct Input {
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(setter(strip_option))]
     pub description: Option<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(setter(strip_option))]
     pub default: Option<Value>,
-    #[serde(default)]
     pub required: bool,
-    #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
+    /// The `type`, along with the `options` of choice inputs
     #[builder(setter(strip_option))]
-    pub kind: Option<String>,
+    pub kind: Option<InputType>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
-#[builder(field_defaults(default, setter(into)))]
-pub struct Output {
+impl Input {
+    /// Check that the default is a valid value of the input's type
+    pub fn validate_default(&self) -> Result<(), InvalidValue> {
+        match (&self.kind, &self.default) {
+            (Some(kind), Some(default)) if !kind.accepts(default) => Err(InvalidValue {
+                kind: kind.clone(),
+                value: default.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The `type` of an input, `choice` and `environment` are only supported
+/// by `workflow_dispatch`
+#[derive(Debug, Clone, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum InputType {
+    String,
+    Boolean,
+    Number,
+    /// One of the options
+    Choice(Vec<String>),
+    /// The name of one of the repository's environments
+    Environment,
+}
+
+impl InputType {
+    /// If `value` is valid for an input of this type, expressions are only
+    /// known when the workflow runs and are always accepted
+    pub fn accepts(&self, value: &Value) -> bool {
+        match value {
+            Value::String(s) if !expr::embedded(s).is_empty() => true,
+            Value::Bool(_) => matches!(self, InputType::Boolean | InputType::String),
+            Value::Number(_) => matches!(self, InputType::Number | InputType::String),
+            Value::String(s) => match self {
+                InputType::String | InputType::Environment => true,
+                InputType::Choice(options) => options.contains(s),
+                InputType::Boolean | InputType::Number => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// A value that isn't valid for an input of type `kind`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidValue {
+    pub kind: InputType,
+    pub value: Value,
+}
+
+impl std::fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = match &self.value {
+            Value::String(s) => s.clone(),
+            other => serde_yml::to_string(other)
+                .map(|s| s.trim().to_string())
+                .unwrap_or_default(),
+        };
+        match &self.kind {
+            InputType::Choice(options) => {
+                write!(
+                    f,
+                    "`{value}` is not one of the options {}",
+                    options.join(", ")
+                )
+            }
+            kind => write!(f, "`{value}` is not a {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidValue {}
+
+/// How an [`Input`] is written, with the `options` of a choice next to its
+/// `type`
+#[derive(Serialize, Deserialize)]
+struct InputRepr {
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[builder(setter(strip_option))]
-    pub description: Option<String>,
+    description: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[builder(setter(strip_option))]
-    pub default: Option<Value>,
-    #[serde(default)]
-    pub required: bool,
+    default: Option<Value>,
+    #[serde(default, deserialize_with = "lenient_bool")]
+    required: bool,
     #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
-    #[builder(setter(strip_option))]
-    pub kind: Option<String>,
+    kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    options: Vec<String>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
-#[builder(field_defaults(default, setter(strip_option, into)))]
-pub struct Secret {
+impl TryFrom<InputRepr> for Input {
+    type Error = String;
+
+    fn try_from(repr: InputRepr) -> Result<Self, Self::Error> {
+        let kind = match repr.kind.as_deref() {
+            None => None,
+            Some("string") => Some(InputType::String),
+            Some("boolean") => Some(InputType::Boolean),
+            Some("number") => Some(InputType::Number),
+            Some("choice") => Some(InputType::Choice(repr.options)),
+            Some("environment") => Some(InputType::Environment),
+            Some(other) => return Err(format!("unknown input type `{other}`")),
+        };
+        Ok(Self {
+            description: repr.description,
+            default: repr.default,
+            required: repr.required,
+            kind,
+        })
+    }
+}
+
+impl From<Input> for InputRepr {
+    fn from(input: Input) -> Self {
+        let (kind, options) = match input.kind {
+            Some(InputType::Choice(options)) => (Some("choice".to_string()), options),
+            Some(kind) => (Some(kind.to_string()), Vec::new()),
+            None => (None, Vec::new()),
+        };
+        Self {
+            description: input.description,
+            default: input.default,
+            required: input.required,
+            kind,
+            options,
+        }
+    }
+}
+
+/// An output of `workflow_call`, mapped from an output of one of its jobs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TypedBuilder)]
+pub struct Output {
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub description: Option<Value>,
+    #[builder(default, setter(strip_option, into))]
+    pub description: Option<String>,
+    /// The expression the output is mapped from, e.g.
+    /// `${{ jobs.build.outputs.version }}`
+    #[builder(setter(into))]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TypedBuilder)]
+#[builder(field_defaults(default, setter(into)))]
+pub struct Secret {
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub required: Option<Value>,
+    #[builder(setter(strip_option))]
+    pub description: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "lenient_bool",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub required: bool,
 }
 
 impl Secret {
     pub fn is_empty(&self) -> bool {
-        self.description.is_none() && self.required.is_none()
+        self.description.is_none() && !self.required
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, TypedBuilder)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, TypedBuilder)]
+#[serde(remote = "Self")]
 #[builder(field_defaults(default))]
 pub struct Permissions {
     #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
@@ -572,7 +846,12 @@ pub struct Permissions {
     pub contents: PermissionValue,
     #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
     pub deployments: PermissionValue,
-    #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
+    #[serde(
+        default,
+        rename = "id-token",
+        alias = "id",
+        skip_serializing_if = "PermissionValue::is_none"
+    )]
     pub id: PermissionValue,
     #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
     pub issues: PermissionValue,
@@ -582,17 +861,128 @@ pub struct Permissions {
     pub packages: PermissionValue,
     #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
     pub pages: PermissionValue,
-    #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
+    #[serde(
+        default,
+        rename = "pull-requests",
+        alias = "pull",
+        skip_serializing_if = "PermissionValue::is_none"
+    )]
     pub pull: PermissionValue,
-    #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
+    #[serde(
+        default,
+        rename = "repository-projects",
+        alias = "repository",
+        skip_serializing_if = "PermissionValue::is_none"
+    )]
     pub repository: PermissionValue,
-    #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
+    #[serde(
+        default,
+        rename = "security-events",
+        alias = "security",
+        skip_serializing_if = "PermissionValue::is_none"
+    )]
     pub security: PermissionValue,
     #[serde(default, skip_serializing_if = "PermissionValue::is_none")]
     pub statuses: PermissionValue,
 }
 
+impl Serialize for Permissions {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Permissions::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Permissions {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // `permissions` may also be `read-all` or `write-all` as a shorthand
+        // for every scope
+        match Value::deserialize(deserializer)? {
+            Value::String(s) if s == "read-all" => Ok(Self::all(PermissionValue::Read)),
+            Value::String(s) if s == "write-all" => Ok(Self::all(PermissionValue::Write)),
+            other => Permissions::deserialize(other).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 impl Permissions {
+    /// Every scope set to `value`
+    pub fn all(value: PermissionValue) -> Self {
+        let mut ret = Self::default();
+        for (scope, _) in Self::default().scopes() {
+            ret.set(scope, value);
+        }
+        ret
+    }
+
+    /// The value of a scope by the name used in a workflow file
+    pub fn get(&self, scope: &str) -> Option<PermissionValue> {
+        self.scopes()
+            .into_iter()
+            .find(|(name, _)| *name == scope)
+            .map(|(_, value)| value)
+    }
+
+    /// Set a scope by the name used in a workflow file, returns `false` if
+    /// `scope` isn't a known scope
+    pub fn set(&mut self, scope: &str, value: PermissionValue) -> bool {
+        let field = match scope {
+            "actions" => &mut self.actions,
+            "attestations" => &mut self.attestations,
+            "checks" => &mut self.checks,
+            "contents" => &mut self.contents,
+            "deployments" => &mut self.deployments,
+            "id-token" => &mut self.id,
+            "issues" => &mut self.issues,
+            "discussions" => &mut self.discussions,
+            "packages" => &mut self.packages,
+            "pages" => &mut self.pages,
+            "pull-requests" => &mut self.pull,
+            "repository-projects" => &mut self.repository,
+            "security-events" => &mut self.security,
+            "statuses" => &mut self.statuses,
+            _ => return false,
+        };
+        *field = value;
+        true
+    }
+
+    /// Combine two sets of permissions keeping the higher access of each
+    /// scope
+    pub fn union(&self, other: &Permissions) -> Self {
+        let mut ret = *self;
+        for (scope, value) in other.scopes() {
+            let current = ret.get(scope).unwrap_or_default();
+            ret.set(scope, current.max(value));
+        }
+        ret
+    }
+
+    /// Each scope by the name used in a workflow file
+    pub fn scopes(&self) -> [(&'static str, PermissionValue); 14] {
+        [
+            ("actions", self.actions),
+            ("attestations", self.attestations),
+            ("checks", self.checks),
+            ("contents", self.contents),
+            ("deployments", self.deployments),
+            ("id-token", self.id),
+            ("issues", self.issues),
+            ("discussions", self.discussions),
+            ("packages", self.packages),
+            ("pages", self.pages),
+            ("pull-requests", self.pull),
+            ("repository-projects", self.repository),
+            ("security-events", self.security),
+            ("statuses", self.statuses),
+        ]
+    }
+
     pub fn is_empty(&self) -> bool {
         self.actions.is_none()
             && self.attestations.is_none()
@@ -611,11 +1001,28 @@ impl Permissions {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, strum::Display, strum::EnumString)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
 #[strum(serialize_all = "lowercase")]
 pub enum PermissionValue {
+    // the capitalized names grackle used to write are still read
+    #[serde(alias = "Read")]
     Read,
+    #[serde(alias = "Write")]
     Write,
+    #[default]
+    #[serde(alias = "None")]
     None,
 }
 
@@ -623,11 +1030,27 @@ impl PermissionValue {
     pub fn is_none(&self) -> bool {
         matches!(self, Self::None)
     }
-}
 
-impl std::default::Default for PermissionValue {
-    fn default() -> Self {
-        Self::None
+    fn rank(&self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Read => 1,
+            Self::Write => 2,
+        }
+    }
+
+    /// The higher access of `self` and `other`
+    pub fn max(self, other: Self) -> Self {
+        if other.rank() > self.rank() {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// If `self` grants at least the access of `other`
+    pub fn covers(&self, other: Self) -> bool {
+        self.rank() >= other.rank()
     }
 }
 
@@ -891,8 +1314,8 @@ impl Concurrency {
     pub fn env_var(&mut self, key: impl ToString, value: impl ToString) {
         self.env.insert(key.to_string(), value.to_string());
     }
-    pub fn add_step(&mut self, step: JobStep) {
-        self.steps.push(step);
+    pub fn add_step(&mut self, step: impl Into<JobStep>) {
+        self.steps.push(step.into());
     }
     pub fn service(&mut self, name: impl ToString, container: Container) {
         self.services.insert(name.to_string(), container);
@@ -901,7 +1324,13 @@ impl Concurrency {
         self.with.insert(key.to_string(), value.into());
     }
     pub fn secret(&mut self, key: impl ToString, value: impl ToString) {
-        self.secrets.insert(key.to_string(), value.to_string());
+        self.secrets.insert(key, value);
+    }
+    pub fn inherit_secrets(&mut self) {
+        self.secrets = JobSecrets::Inherit;
+    }
+    pub fn uses(&mut self, value: impl Into<Value>) {
+        self.uses = Some(value.into());
     }
 ))]
 pub struct Job {
@@ -914,7 +1343,11 @@ pub struct Job {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(via_mutators(init = Default::default()))]
     pub permissions: Option<Permissions>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
     #[builder(via_mutators(init = Default::default()))]
     pub needs: Vec<String>,
     #[serde(rename = "if")]
@@ -923,7 +1356,7 @@ pub struct Job {
     pub if_cond: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option, into))]
-    pub runs_on: Option<String>,
+    pub runs_on: Option<Value>,
     #[serde(default, skip_serializing_if = "Environment::is_empty")]
     #[builder(default)]
     pub environment: Environment,
@@ -947,6 +1380,9 @@ pub struct Job {
     pub strategy: Strategy,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option, into))]
+    pub continue_on_error: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default, setter(strip_option, into))]
     pub timeout_minutes: Option<Value>,
     #[serde(default, skip_serializing_if = "Container::is_empty")]
     #[builder(default)]
@@ -955,17 +1391,88 @@ pub struct Job {
     #[builder(via_mutators(init = Default::default()))]
     pub services: BTreeMap<String, Container>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[builder(default, setter(strip_option, into))]
+    #[builder(via_mutators(init = None))]
     pub uses: Option<Value>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
     pub with: BTreeMap<String, Value>,
-    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default, skip_serializing_if = "JobSecrets::is_empty")]
     #[builder(via_mutators(init = Default::default()))]
-    pub secrets: BTreeMap<String, String>,
+    pub secrets: JobSecrets,
+}
+
+/// The secrets passed to a reusable workflow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobSecrets {
+    /// `secrets: inherit`, every secret the caller can access
+    Inherit,
+    Map(BTreeMap<String, String>),
+}
+
+impl Default for JobSecrets {
+    fn default() -> Self {
+        Self::Map(BTreeMap::new())
+    }
+}
+
+impl JobSecrets {
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Map(map) if map.is_empty())
+    }
+
+    pub fn is_inherit(&self) -> bool {
+        matches!(self, Self::Inherit)
+    }
+
+    /// Pass a single secret, replacing `inherit` if it was set
+    pub fn insert(&mut self, key: impl ToString, value: impl ToString) {
+        if let Self::Inherit = self {
+            *self = Self::default();
+        }
+        if let Self::Map(map) = self {
+            map.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    /// The explicitly passed secrets, empty for `inherit`
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &String)> {
+        let map = match self {
+            Self::Inherit => None,
+            Self::Map(map) => Some(map),
+        };
+        map.into_iter().flatten()
+    }
+}
+
+impl Serialize for JobSecrets {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Inherit => serializer.serialize_str("inherit"),
+            Self::Map(map) => map.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JobSecrets {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match Value::deserialize(deserializer)? {
+            Value::String(s) if s == "inherit" => Ok(Self::Inherit),
+            Value::Null => Ok(Self::default()),
+            other => BTreeMap::deserialize(other)
+                .map(Self::Map)
+                .map_err(serde::de::Error::custom),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
+#[serde(remote = "Self")]
 #[builder(field_defaults(default, setter(strip_option, into)))]
 pub struct Environment {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -980,6 +1487,31 @@ impl Environment {
     }
 }
 
+impl Serialize for Environment {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Environment::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Environment {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // `environment: production` is a shorthand for only the name
+        match Value::deserialize(deserializer)? {
+            Value::String(name) => Ok(Self {
+                name: Some(name),
+                url: None,
+            }),
+            other => Environment::deserialize(other).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
 #[serde(rename_all = "kebab-case")]
 #[builder(mutators(
@@ -1024,10 +1556,10 @@ pub struct JobStep {
     pub stragegy: Option<Strategy>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option, into))]
-    pub continue_on_error: Option<String>,
+    pub continue_on_error: Option<Value>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(strip_option, into))]
-    pub timeout_minutes: Option<String>,
+    pub timeout_minutes: Option<Value>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TypedBuilder)]
@@ -1076,12 +1608,32 @@ pub struct Strategy {
     pub max_parallel: Option<Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
 pub enum Matrix {
     String(String),
     Map(BTreeMap<String, Vec<Value>>),
 }
 
+impl<'de> Deserialize<'de> for Matrix {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // grackle used to write the variant as a `!String` or `!Map` tag
+        let value = match Value::deserialize(deserializer)? {
+            Value::Tagged(tagged) => tagged.value,
+            other => other,
+        };
+        match value {
+            Value::String(s) => Ok(Self::String(s)),
+            other => serde_yml::from_value(other)
+                .map(Self::Map)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
 impl Default for Matrix {
     fn default() -> Self {
         Self::Map(Default::default())
@@ -1095,6 +1647,73 @@ impl Matrix {
             Matrix::Map(m) => m.is_empty(),
         }
     }
+
+    /// The combinations the matrix expands to, `None` when it's an
+    /// expression. `include` entries extend every combination they don't
+    /// change an original value of, or are added as their own combination.
+    pub fn combinations(&self) -> Option<Vec<BTreeMap<String, Value>>> {
+        let Matrix::Map(map) = self else {
+            return None;
+        };
+        let entries = |key: &str| -> Vec<&serde_yml::Mapping> {
+            map.get(key)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_mapping)
+                .collect()
+        };
+        let mut ret: Vec<BTreeMap<String, Value>> = vec![BTreeMap::new()];
+        let dimensions = map
+            .iter()
+            .filter(|(key, _)| *key != "include" && *key != "exclude");
+        let mut any = false;
+        for (key, values) in dimensions {
+            any = true;
+            ret = ret
+                .into_iter()
+                .flat_map(|combination| {
+                    values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+                        combination.insert(key.clone(), value.clone());
+                        combination
+                    })
+                })
+                .collect();
+        }
+        if !any {
+            ret.clear();
+        }
+        let matches = |combination: &BTreeMap<String, Value>, entry: &serde_yml::Mapping| {
+            entry.iter().all(|(key, value)| {
+                key.as_str()
+                    .and_then(|key| combination.get(key))
+                    .is_some_and(|v| v == value)
+            })
+        };
+        ret.retain(|combination| !entries("exclude").iter().any(|e| matches(combination, e)));
+
+        let original = ret.len();
+        for entry in entries("include") {
+            let entry: BTreeMap<String, Value> = entry
+                .iter()
+                .filter_map(|(key, value)| Some((key.as_str()?.to_string(), value.clone())))
+                .collect();
+            let mut added = false;
+            for combination in &mut ret[..original] {
+                let compatible = entry.iter().all(|(key, value)| {
+                    !map.contains_key(key) || combination.get(key).is_none_or(|v| v == value)
+                });
+                if compatible {
+                    combination.extend(entry.clone());
+                    added = true;
+                }
+            }
+            if !added {
+                ret.push(entry);
+            }
+        }
+        Some(ret)
+    }
 }
 
 impl Strategy {
@@ -1167,6 +1786,49 @@ impl Credentials {
     }
 }
 
+/// `required` is often written as a string, `required: 'true'`
+pub(crate) fn lenient_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    match Value::deserialize(deserializer)? {
+        Value::Bool(b) => Ok(b),
+        Value::String(s) => Ok(s.eq_ignore_ascii_case("true")),
+        Value::Null => Ok(false),
+        other => Err(serde::de::Error::custom(format!(
+            "expected a boolean, found {other:?}"
+        ))),
+    }
+}
+
+/// Deserialize an event that may be listed without any filters (`push:`) as
+/// the empty filter set rather than `None`
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(|v| Some(v.unwrap_or_default()))
+}
+
+/// Deserialize a value that may be written as either a single string or a
+/// list of strings (`needs: plan` or `needs: [plan, build]`)
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(one) => vec![one],
+        OneOrMany::Many(many) => many,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1192,13 +1854,10 @@ mod tests {
                             .with("key", "value")
                             .with(
                                 "complex",
-                                serde_yml::Mapping::from_iter(
-                                    [
-                                        (Value::from("inner-key"), Value::from("inner-value")),
-                                        (Value::from("inner-key2"), Value::from(1)),
-                                    ]
-                                    .into_iter(),
-                                ),
+                                serde_yml::Mapping::from_iter([
+                                    (Value::from("inner-key"), Value::from("inner-value")),
+                                    (Value::from("inner-key2"), Value::from(1)),
+                                ]),
                             )
                             .build(),
                     )
@@ -1207,4 +1866,169 @@ mod tests {
             .build();
         insta::assert_yaml_snapshot!(workflow);
     }
+
+    #[test]
+    fn shorthand_syntax() {
+        let workflow: Workflow = serde_yml::from_str(
+            r#"name: ci
+on: [push, workflow_dispatch]
+permissions:
+  contents: read
+  id-token: write
+  pull-requests: write
+jobs:
+  build:
+    needs: plan
+"#,
+        )
+        .unwrap();
+        assert!(workflow.on.push.is_some());
+        assert!(workflow.on.workflow_dispatch.is_some());
+        assert!(workflow.on.pull_request.is_none());
+        let permissions = workflow.permissions.unwrap();
+        assert_eq!(permissions.contents, PermissionValue::Read);
+        assert_eq!(permissions.id, PermissionValue::Write);
+        assert_eq!(permissions.pull, PermissionValue::Write);
+        assert_eq!(workflow.jobs["build"].needs, ["plan"]);
+
+        let on: Triggers = serde_yml::from_str("push").unwrap();
+        assert!(on.push.is_some());
+        let on: Triggers = serde_yml::from_str("pull_request:\npush:\n  branches: [main]").unwrap();
+        assert!(on.pull_request.is_some());
+        assert!(on.push.is_some());
+    }
+
+    #[test]
+    fn values_and_expressions() {
+        let workflow: Workflow = serde_yml::from_str(
+            r#"name: ci
+jobs:
+  build:
+    runs-on: [self-hosted, linux]
+    strategy:
+      matrix: ${{ fromJSON(needs.plan.outputs.matrix) }}
+    steps:
+      - run: make
+        continue-on-error: ${{ matrix.experimental }}
+        timeout-minutes: 10
+      - run: make test
+        continue-on-error: true
+"#,
+        )
+        .unwrap();
+        let job = &workflow.jobs["build"];
+        assert_eq!(
+            job.runs_on,
+            Some(Value::Sequence(vec!["self-hosted".into(), "linux".into()]))
+        );
+        let steps = &job.steps;
+        assert_eq!(
+            steps[0].continue_on_error,
+            Some("${{ matrix.experimental }}".into())
+        );
+        assert_eq!(steps[0].timeout_minutes, Some(10.into()));
+        assert_eq!(steps[1].continue_on_error, Some(true.into()));
+        let yaml = serde_yml::to_string(&job.strategy).unwrap();
+        assert_eq!(yaml, "matrix: ${{ fromJSON(needs.plan.outputs.matrix) }}\n");
+    }
+
+    #[test]
+    fn legacy_spellings() {
+        let workflow: Workflow = serde_yml::from_str(
+            r#"name: ci
+permissions:
+  contents: Read
+  id: Write
+  pull: None
+jobs:
+  build:
+    strategy:
+      matrix: !String ${{ fromJSON(needs.plan.outputs.matrix) }}
+  test:
+    strategy:
+      matrix: !Map
+        os: [linux, macos]
+"#,
+        )
+        .unwrap();
+        let permissions = workflow.permissions.unwrap();
+        assert_eq!(permissions.contents, PermissionValue::Read);
+        assert_eq!(permissions.id, PermissionValue::Write);
+        assert_eq!(permissions.pull, PermissionValue::None);
+        let yaml = serde_yml::to_string(&permissions).unwrap();
+        assert_eq!(yaml, "contents: read\nid-token: write\n");
+        let matrix = |job: &str| workflow.jobs[job].strategy.matrix.clone();
+        assert!(matches!(matrix("build"), Matrix::String(_)));
+        assert_eq!(matrix("test").combinations().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn matrix_combinations() {
+        let matrix: Matrix = serde_yml::from_str(
+            r#"os: [linux, windows]
+node: [18, 20]
+exclude:
+  - os: windows
+    node: 18
+include:
+  - os: linux
+    experimental: true
+  - node: 20
+    npm: 10
+  - os: macos
+    node: 22
+"#,
+        )
+        .unwrap();
+        let combinations: Vec<_> = matrix
+            .combinations()
+            .unwrap()
+            .iter()
+            .map(|c| serde_json::to_string(c).unwrap())
+            .collect();
+        insta::assert_debug_snapshot!(combinations);
+        assert_eq!(Matrix::default().combinations(), Some(vec![]));
+        assert_eq!(
+            Matrix::String("${{ fromJSON(x) }}".into()).combinations(),
+            None
+        );
+    }
+
+    #[test]
+    fn inputs() {
+        let dispatch: WorkflowDispatch = serde_yml::from_str(
+            r#"inputs:
+  level:
+    type: choice
+    options: [patch, minor, major]
+    default: minor
+  dry-run:
+    type: boolean
+    required: 'true'
+"#,
+        )
+        .unwrap();
+        let level = &dispatch.inputs["level"];
+        assert_eq!(
+            level.kind,
+            Some(InputType::Choice(vec![
+                "patch".into(),
+                "minor".into(),
+                "major".into()
+            ]))
+        );
+        assert!(level.validate_default().is_ok());
+        assert!(dispatch.inputs["dry-run"].required);
+        let emitted = serde_yml::to_string(&dispatch).unwrap();
+        assert!(emitted.contains("type: choice\n    options:"), "{emitted}");
+
+        let invalid = serde_yml::from_str::<Input>("type: text");
+        assert!(invalid
+            .unwrap_err()
+            .to_string()
+            .contains("unknown input type `text`"));
+
+        let secret: Secret = serde_yml::from_str("required: true").unwrap();
+        assert!(secret.required);
+    }
 }
@@ -0,0 +1,181 @@
+use super::{Error, Rule, Severity};
+use crate::metadata::MetadataConfig;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The name of the configuration file [`Config::discover`] looks for
+pub const CONFIG_FILE_NAME: &str = "grackle.toml";
+
+/// Lint configuration, usually read from a `grackle.toml`
+///
+/// ```toml
+/// [rules]
+/// missing-runs-on = "error"
+/// some-rule = { level = "warn", some-option = ["value"] }
+///
+/// [[overrides]]
+/// files = [".github/workflows/experimental/**"]
+/// rules = { missing-runs-on = "off" }
+///
+/// [metadata]
+/// cache = ".grackle/actions"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleSetting>,
+    /// Settings for specific files, applied in order after `rules`
+    #[serde(default)]
+    pub overrides: Vec<Override>,
+    /// Where the metadata of the actions used by workflows is found
+    #[serde(default)]
+    pub metadata: MetadataConfig,
+    /// The directory the configuration was loaded from, paths in rule
+    /// options are relative to it
+    #[serde(skip)]
+    pub dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Override {
+    /// Glob patterns matched against the path of the linted file
+    pub files: Vec<String>,
+    #[serde(default)]
+    pub rules: BTreeMap<String, RuleSetting>,
+}
+
+/// The configuration for a single rule, either just a level or a table with
+/// an optional `level` and any rule specific options
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum RuleSetting {
+    Level(RuleLevel),
+    Table {
+        #[serde(default)]
+        level: Option<RuleLevel>,
+        #[serde(flatten)]
+        options: toml::Table,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    /// Use the rule's default severity
+    #[default]
+    Default,
+    Info,
+    #[serde(alias = "warning")]
+    Warn,
+    Error,
+}
+
+impl RuleLevel {
+    /// The severity for diagnostics at this level, `None` if the rule is off
+    pub fn severity(&self, default: Severity) -> Option<Severity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Default => Some(default),
+            RuleLevel::Info => Some(Severity::Info),
+            RuleLevel::Warn => Some(Severity::Warning),
+            RuleLevel::Error => Some(Severity::Error),
+        }
+    }
+}
+
+/// The effective settings of a rule for a single file
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Resolved {
+    pub level: RuleLevel,
+    pub options: toml::Table,
+}
+
+impl Resolved {
+    fn apply(&mut self, setting: &RuleSetting) {
+        match setting {
+            RuleSetting::Level(level) => self.level = *level,
+            RuleSetting::Table { level, options } => {
+                if let Some(level) = level {
+                    self.level = *level;
+                }
+                self.options
+                    .extend(options.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let mut ret: Self = text.parse()?;
+        ret.dir = path.parent().map(Path::to_path_buf);
+        Ok(ret)
+    }
+
+    /// Look for a `grackle.toml` in `dir` and each of its ancestors
+    pub fn discover(dir: impl AsRef<Path>) -> Result<Option<(PathBuf, Self)>, Error> {
+        for dir in dir.as_ref().ancestors() {
+            let path = dir.join(CONFIG_FILE_NAME);
+            if path.is_file() {
+                let config = Self::load(&path)?;
+                return Ok(Some((path, config)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn resolve(&self, rule: &dyn Rule, path: &str) -> Resolved {
+        let mut ret = Resolved::default();
+        if let Some(setting) = self.rules.get(rule.id()) {
+            ret.apply(setting);
+        }
+        for o in &self.overrides {
+            if !o.files.iter().any(|pattern| glob_match(pattern, path)) {
+                continue;
+            }
+            if let Some(setting) = o.rules.get(rule.id()) {
+                ret.apply(setting);
+            }
+        }
+        ret
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+/// Match `path` against a glob where `*` matches within a path segment, `**`
+/// matches across segments and `?` matches any single character
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    fn inner(pattern: &[u8], path: &[u8]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            [b'*', b'*', rest @ ..] => {
+                let rest = rest.strip_prefix(b"/").unwrap_or(rest);
+                (0..=path.len()).any(|idx| inner(rest, &path[idx..]))
+            }
+            [b'*', rest @ ..] => (0..=path.len())
+                .take_while(|idx| *idx == 0 || path[idx - 1] != b'/')
+                .any(|idx| inner(rest, &path[idx..])),
+            [b'?', rest @ ..] => matches!(path, [c, ..] if *c != b'/') && inner(rest, &path[1..]),
+            [c, rest @ ..] => path.first() == Some(c) && inner(rest, &path[1..]),
+        }
+    }
+    inner(pattern.as_bytes(), path.as_bytes())
+}
@@ -0,0 +1,134 @@
+//! Helpers for building format preserving [`TextEdit`]s
+
+use super::TextEdit;
+use crate::span::{Segment, Source, YamlPath};
+
+/// Add `key: value` to the block mapping at `map`, creating the mapping as
+/// the last entry of its parent when it doesn't exist yet
+///
+/// `None` when the mapping is written in flow style or neither it nor its
+/// parent can be located. A `value` starting with a newline is a block
+/// already indented for the new entry.
+pub fn insert_entry(source: &Source, map: &YamlPath, key: &str, value: &str) -> Option<TextEdit> {
+    let sep = if value.starts_with('\n') { "" } else { " " };
+    if let Some(node) = source.locate(map) {
+        let text = source.slice(node.value);
+        if node.value.is_empty() || text.starts_with('{') {
+            return None;
+        }
+        let indent = source.position(node.value.start).column - 1;
+        return Some(TextEdit::insert(
+            line_end(source, node.value.end),
+            format!("\n{:indent$}{key}:{sep}{value}", ""),
+        ));
+    }
+    let Some(Segment::Key(name)) = map.segments().last() else {
+        return None;
+    };
+    let parent = source.locate(&map.parent()?)?;
+    let text = source.slice(parent.value);
+    if parent.value.is_empty() || text.starts_with('{') {
+        return None;
+    }
+    let indent = source.position(parent.value.start).column - 1;
+    Some(TextEdit::insert(
+        line_end(source, parent.value.end),
+        format!(
+            "\n{:indent$}{name}:\n{:child$}{key}:{sep}{value}",
+            "",
+            "",
+            child = indent + 2
+        ),
+    ))
+}
+
+/// The end of the line `offset` is on, after any trailing comment
+fn line_end(source: &Source, offset: usize) -> usize {
+    source.line_span(source.position(offset).line).end
+}
+
+/// Find the source spans of `needle` inside of the value at `path`
+pub fn find_in_value(source: &Source, path: &YamlPath, needle: &str) -> Vec<crate::span::Span> {
+    let Some(node) = source.locate(path) else {
+        return Vec::new();
+    };
+    let text = source.slice(node.value);
+    text.match_indices(needle)
+        .map(|(idx, m)| {
+            crate::span::Span::new(node.value.start + idx, node.value.start + idx + m.len())
+        })
+        .collect()
+}
+
+/// An edit described by the location of a node in the workflow rather than
+/// by byte offsets, resolved into a [`TextEdit`] against the [`Source`] it
+/// applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructuralEdit {
+    /// Replace the value at `path`
+    Replace { path: YamlPath, value: String },
+    /// Add `key: value` to the mapping at `map`, see [`insert_entry`]
+    Insert {
+        map: YamlPath,
+        key: String,
+        value: String,
+    },
+    /// Remove the entry at `path` along with its key
+    Remove { path: YamlPath },
+}
+
+impl StructuralEdit {
+    /// The equivalent [`TextEdit`], `None` if the node can't be located or
+    /// the edit can't be made without reformatting the file
+    pub fn resolve(&self, source: &Source) -> Option<TextEdit> {
+        match self {
+            StructuralEdit::Replace { path, value } => {
+                let node = source.locate(path)?;
+                Some(TextEdit::new(node.value, value.clone()))
+            }
+            StructuralEdit::Insert { map, key, value } => insert_entry(source, map, key, value),
+            StructuralEdit::Remove { path } => {
+                let node = source.locate(path)?;
+                let start = node.key.unwrap_or(node.value).start;
+                let first = source.position(start).line;
+                let line_start = source.line_span(first).start;
+                // only whole lines are removed, a key sharing its line with a
+                // sequence dash would leave the rest of the item dangling
+                if !source.text()[line_start..start].trim().is_empty() {
+                    return None;
+                }
+                let last = source.position(node.value.end).line;
+                let end = source.line_span(last).end;
+                let end = if source.text()[end..].starts_with("\r\n") {
+                    end + 2
+                } else if source.text()[end..].starts_with('\n') {
+                    end + 1
+                } else {
+                    end
+                };
+                Some(TextEdit::new(crate::span::Span::new(line_start, end), ""))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_after_comment() {
+        let source = Source::new(
+            "ci.yml",
+            "jobs:\n  test:\n    env:\n      A: 1 # first\n    steps: []\n",
+        );
+        let env = YamlPath::job("test").key("env");
+        let edit = insert_entry(&source, &env, "B", "2").unwrap();
+        let mut text = source.text().to_string();
+        text.replace_range(edit.span.start..edit.span.end, &edit.replacement);
+        assert_eq!(
+            text,
+            "jobs:\n  test:\n    env:\n      A: 1 # first\n      B: 2\n    steps: []\n"
+        );
+    }
+}
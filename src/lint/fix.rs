@@ -0,0 +1,292 @@
+//! Apply the [`Fix`]es attached to [`Diagnostic`]s
+//!
+//! Fixes are only ever applied as [`TextEdit`]s so everything outside of the
+//! edited spans, comments and formatting included, is left untouched. When
+//! the edits of two fixes overlap only the first is applied, the file is
+//! then linted again and the remaining fixes are retried against the new
+//! text until nothing changes.
+
+use super::{Applicability, Diagnostic, Error, Fix, Linter, TextEdit};
+use crate::{span::Source, Workflow};
+use std::collections::BTreeSet;
+
+/// The most times a file is re-linted to apply fixes that overlapped an
+/// earlier one
+const MAX_PASSES: usize = 10;
+
+/// Which [`Applicability::Unsafe`] fixes may be applied
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum UnsafeFixes {
+    /// Only safe fixes
+    #[default]
+    Deny,
+    /// Every fix
+    Allow,
+    /// Unsafe fixes from these rules
+    Rules(BTreeSet<String>),
+}
+
+/// Why a fix wasn't applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum SkipReason {
+    /// The fix is unsafe and wasn't opted into
+    Unsafe,
+    /// The fix overlaps another fix that was applied, it is retried after
+    /// re-linting until the passes run out
+    Overlap,
+    /// The fix's edits are out of bounds or overlap each other
+    Invalid,
+    /// The fixed file was no longer a valid workflow
+    Unparsable,
+}
+
+#[derive(Debug, Clone)]
+pub struct Skipped {
+    pub diagnostic: Diagnostic,
+    pub reason: SkipReason,
+}
+
+/// The result of fixing a file
+#[derive(Debug, Clone)]
+pub struct Fixed {
+    pub original: Source,
+    /// The source with every applied fix
+    pub source: Source,
+    /// The diagnostics whose fixes were applied
+    pub fixed: Vec<Diagnostic>,
+    /// The fixes that couldn't be applied
+    pub skipped: Vec<Skipped>,
+    /// The diagnostics reported against the fixed source
+    pub remaining: Vec<Diagnostic>,
+}
+
+impl Fixed {
+    pub fn is_changed(&self) -> bool {
+        self.original.text() != self.source.text()
+    }
+
+    /// A unified diff of the changes, empty if nothing changed
+    pub fn diff(&self) -> String {
+        if !self.is_changed() {
+            return String::new();
+        }
+        similar::TextDiff::from_lines(self.original.text(), self.source.text())
+            .unified_diff()
+            .header(
+                &format!("a/{}", self.original.path().trim_start_matches('/')),
+                &format!("b/{}", self.source.path().trim_start_matches('/')),
+            )
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Fixer {
+    unsafe_fixes: UnsafeFixes,
+}
+
+impl Fixer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn unsafe_fixes(mut self, unsafe_fixes: UnsafeFixes) -> Self {
+        self.unsafe_fixes = unsafe_fixes;
+        self
+    }
+
+    fn allows(&self, diagnostic: &Diagnostic, fix: &Fix) -> bool {
+        match (&self.unsafe_fixes, fix.applicability) {
+            (_, Applicability::Safe) | (UnsafeFixes::Allow, _) => true,
+            (UnsafeFixes::Deny, _) => false,
+            (UnsafeFixes::Rules(rules), _) => rules.contains(&diagnostic.rule),
+        }
+    }
+
+    /// Apply every allowed fix in `diagnostics` that doesn't overlap another
+    /// one, fixes for errors are preferred over those for warnings and then
+    /// the earliest in the file. Returns the new text, the indexes of the
+    /// diagnostics that were applied and the indexes of those that weren't
+    pub fn apply(
+        &self,
+        source: &Source,
+        diagnostics: &[Diagnostic],
+    ) -> (String, Vec<usize>, Vec<(usize, SkipReason)>) {
+        let mut candidates: Vec<_> = diagnostics
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, d)| Some((idx, d, d.fix.as_ref()?)))
+            .collect();
+        candidates.sort_by_key(|(idx, d, _)| (std::cmp::Reverse(d.severity), d.span, *idx));
+        let mut accepted: Vec<&TextEdit> = Vec::new();
+        let mut applied = Vec::new();
+        let mut skipped = Vec::new();
+        for (idx, diagnostic, fix) in candidates {
+            if !self.allows(diagnostic, fix) {
+                skipped.push((idx, SkipReason::Unsafe));
+                continue;
+            }
+            let valid = fix.edits.iter().enumerate().all(|(i, edit)| {
+                edit.span.start <= edit.span.end
+                    && edit.span.end <= source.text().len()
+                    && source.text().is_char_boundary(edit.span.start)
+                    && source.text().is_char_boundary(edit.span.end)
+                    && !fix.edits[..i].iter().any(|other| conflicts(edit, other))
+            });
+            if !valid {
+                skipped.push((idx, SkipReason::Invalid));
+                continue;
+            }
+            if fix
+                .edits
+                .iter()
+                .any(|edit| accepted.iter().any(|other| conflicts(edit, other)))
+            {
+                skipped.push((idx, SkipReason::Overlap));
+                continue;
+            }
+            accepted.extend(&fix.edits);
+            applied.push(idx);
+        }
+        // applying from the end keeps the offsets of earlier edits valid
+        accepted.sort_by_key(|edit| std::cmp::Reverse((edit.span.start, edit.span.end)));
+        let mut text = source.text().to_string();
+        for edit in accepted {
+            text.replace_range(edit.span.start..edit.span.end, &edit.replacement);
+        }
+        applied.sort_unstable();
+        (text, applied, skipped)
+    }
+
+    /// Lint `source` and apply fixes until there is nothing left to fix
+    pub fn fix(&self, linter: &Linter, source: &Source) -> Result<Fixed, Error> {
+        let mut current = source.clone();
+        let mut diagnostics = linter.lint(source)?;
+        let mut fixed = Vec::new();
+        let mut passes = 0;
+        let skipped = loop {
+            let (text, applied, skipped) = self.apply(&current, &diagnostics);
+            let mut skipped: Vec<_> = skipped
+                .into_iter()
+                .map(|(idx, reason)| Skipped {
+                    diagnostic: diagnostics[idx].clone(),
+                    reason,
+                })
+                .collect();
+            if applied.is_empty() {
+                break skipped;
+            }
+            if passes == MAX_PASSES {
+                skipped.extend(applied.iter().map(|idx| Skipped {
+                    diagnostic: diagnostics[*idx].clone(),
+                    reason: SkipReason::Overlap,
+                }));
+                break skipped;
+            }
+            passes += 1;
+            let next = Source::new(current.path(), text);
+            let Ok(workflow) = serde_yml::from_str::<Workflow>(next.text()) else {
+                skipped.extend(applied.iter().map(|idx| Skipped {
+                    diagnostic: diagnostics[*idx].clone(),
+                    reason: SkipReason::Unparsable,
+                }));
+                break skipped;
+            };
+            fixed.extend(applied.iter().map(|idx| diagnostics[*idx].clone()));
+            diagnostics = linter.lint_workflow(&workflow, &next)?;
+            current = next;
+        };
+        Ok(Fixed {
+            original: source.clone(),
+            source: current,
+            fixed,
+            skipped,
+            remaining: diagnostics,
+        })
+    }
+}
+
+/// Whether two edits can't both be applied, insertions at the same offset
+/// conflict since their order would be ambiguous
+fn conflicts(lhs: &TextEdit, rhs: &TextEdit) -> bool {
+    if lhs.span.overlaps(&rhs.span) {
+        return true;
+    }
+    match (lhs.span.is_empty(), rhs.span.is_empty()) {
+        (true, true) => lhs.span.start == rhs.span.start,
+        (true, false) => rhs.span.start < lhs.span.start && lhs.span.start < rhs.span.end,
+        (false, true) => lhs.span.start < rhs.span.start && rhs.span.start < lhs.span.end,
+        (false, false) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lint::{Config, Registry},
+        span::Span,
+    };
+
+    const WORKFLOW: &str = r#"name: ci
+on: pull_request
+jobs:
+  # comments and formatting are kept
+  greet:
+    runs-on: ubuntu-latest
+    permissions: write-all
+    steps:
+      - run: echo "${{ github.event.pull_request.title }}"   # trailing
+      - run: |
+          echo "${{ github.head_ref }}"
+"#;
+
+    fn linter() -> Linter {
+        Linter::new(Registry::builtin(), Config::default())
+    }
+
+    #[test]
+    fn safe_fixes_only() {
+        let source = Source::new(".github/workflows/ci.yml", WORKFLOW);
+        let fixed = Fixer::new().fix(&linter(), &source).unwrap();
+        assert_eq!(fixed.fixed.len(), 2);
+        assert!(fixed.skipped.iter().all(|s| s.reason == SkipReason::Unsafe));
+        insta::assert_snapshot!(fixed.diff());
+    }
+
+    #[test]
+    fn unsafe_fixes_by_rule() {
+        let source = Source::new(".github/workflows/ci.yml", WORKFLOW);
+        let rules = ["excessive-permissions".to_string()].into();
+        let fixed = Fixer::new()
+            .unsafe_fixes(UnsafeFixes::Rules(rules))
+            .fix(&linter(), &source)
+            .unwrap();
+        assert!(fixed.source.text().contains("    permissions: {}\n"));
+        assert!(fixed.remaining.iter().all(|d| d.fix.is_none()));
+    }
+
+    #[test]
+    fn overlapping_fixes() {
+        let source = Source::new("ci.yml", "abcdef");
+        let fix = |start, end, text: &str| Fix {
+            message: String::new(),
+            applicability: Applicability::Safe,
+            edits: vec![TextEdit::new(Span::new(start, end), text)],
+        };
+        let diagnostics = [
+            Diagnostic::new("a", "", Span::new(0, 3)).with_fix(fix(0, 3, "ABC")),
+            Diagnostic::new("b", "", Span::new(2, 4)).with_fix(fix(2, 4, "CD")),
+            Diagnostic::new("c", "", Span::new(4, 4)).with_fix(fix(4, 4, "-")),
+            Diagnostic::new("d", "", Span::new(6, 6)).with_fix(fix(6, 9, "!")),
+        ];
+        let (text, applied, skipped) = Fixer::new().apply(&source, &diagnostics);
+        assert_eq!(text, "ABCd-ef");
+        assert_eq!(applied, [0, 2]);
+        assert_eq!(
+            skipped,
+            [(1, SkipReason::Overlap), (3, SkipReason::Invalid)]
+        );
+    }
+}
@@ -0,0 +1,524 @@
+//! A pluggable set of rules that check a [`Workflow`] and report
+//! [`Diagnostic`]s against the [`Source`] it was parsed from.
+//!
+//! Rules are collected in a [`Registry`] and run by a [`Linter`] which
+//! applies the enabled rules and severities from a [`Config`] (usually read
+//! from a `grackle.toml`) and drops anything suppressed by an inline
+//! `# grackle: ignore[rule-id]` comment.
+
+mod config;
+pub mod edit;
+mod fix;
+pub mod rules;
+mod suppress;
+
+pub use config::{Config, Override, RuleLevel, RuleSetting};
+pub use edit::StructuralEdit;
+pub use fix::{Fixed, Fixer, SkipReason, Skipped, UnsafeFixes};
+
+use crate::{
+    metadata::MetadataStore,
+    span::{Source, Span},
+    Workflow,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use suppress::Suppressions;
+
+/// How serious a [`Diagnostic`] is
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    strum::Display,
+    strum::EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A single finding reported by a [`Rule`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    /// The id of the rule that reported this
+    pub rule: String,
+    /// The severity, assigned by the [`Linter`] from its configuration
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    /// Additional context, e.g. the other locations involved in a finding
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<Fix>,
+}
+
+impl Diagnostic {
+    pub fn new(rule: impl Into<String>, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            rule: rule.into(),
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+            fix: None,
+        }
+    }
+
+    pub fn with_note(mut self, message: impl Into<String>, span: Option<Span>) -> Self {
+        self.notes.push(Note {
+            message: message.into(),
+            span,
+        });
+        self
+    }
+
+    pub fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Note {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+/// A set of edits that resolve a [`Diagnostic`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Fix {
+    pub message: String,
+    pub applicability: Applicability,
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    /// A fix made of [`StructuralEdit`]s, `None` if any of them can't be
+    /// resolved against `source`
+    pub fn structural(
+        message: impl Into<String>,
+        applicability: Applicability,
+        source: &Source,
+        edits: &[StructuralEdit],
+    ) -> Option<Self> {
+        let edits = edits
+            .iter()
+            .map(|edit| edit.resolve(source))
+            .collect::<Option<_>>()?;
+        Some(Self {
+            message: message.into(),
+            applicability,
+            edits,
+        })
+    }
+}
+
+/// Whether a [`Fix`] can be applied without changing the workflow's behavior
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Applicability {
+    Safe,
+    Unsafe,
+}
+
+/// Replace the text at `span` with `replacement`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TextEdit {
+    pub span: Span,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(span: Span, replacement: impl Into<String>) -> Self {
+        Self {
+            span,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn insert(at: usize, text: impl Into<String>) -> Self {
+        Self::new(Span::empty(at), text)
+    }
+}
+
+/// A check over a parsed [`Workflow`]
+pub trait Rule: Send + Sync {
+    /// The kebab-case id used in configuration and ignore comments
+    fn id(&self) -> &'static str;
+    /// A one line summary of what this rule checks
+    fn description(&self) -> &'static str;
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error>;
+}
+
+/// Everything a [`Rule`] has access to while checking a workflow
+pub struct Context<'a> {
+    pub workflow: &'a Workflow,
+    pub source: &'a Source,
+    /// The rule specific options from the configuration
+    pub options: &'a toml::Table,
+    /// The metadata of the actions used by the workflow
+    pub metadata: &'a MetadataStore,
+    /// The directory of the configuration file, see [`Context::config_path`]
+    pub config_dir: Option<&'a Path>,
+}
+
+impl Context<'_> {
+    /// Deserialize the configured option `key`, `None` if it wasn't provided
+    pub fn option<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>, Error> {
+        self.options
+            .get(key)
+            .cloned()
+            .map(|value| value.try_into())
+            .transpose()
+            .map_err(Error::Config)
+    }
+
+    /// A path given in an option, relative to the configuration file
+    pub fn config_path(&self, path: impl AsRef<Path>) -> PathBuf {
+        match self.config_dir {
+            Some(dir) => dir.join(path),
+            None => path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+/// The set of rules available to a [`Linter`]
+#[derive(Default)]
+pub struct Registry {
+    rules: Vec<Box<dyn Rule>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with all of grackle's own rules
+    pub fn builtin() -> Self {
+        let mut ret = Self::new();
+        rules::register_builtin(&mut ret);
+        ret
+    }
+
+    /// Add a rule, replacing any existing rule with the same id
+    pub fn register(&mut self, rule: impl Rule + 'static) -> &mut Self {
+        self.rules.retain(|r| r.id() != rule.id());
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Rule> {
+        self.rules().find(|r| r.id() == id)
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &dyn Rule> {
+        self.rules.iter().map(|r| r.as_ref())
+    }
+}
+
+/// Runs the rules of a [`Registry`] as configured by a [`Config`]
+pub struct Linter {
+    registry: Registry,
+    config: Config,
+    metadata: MetadataStore,
+}
+
+impl Linter {
+    pub fn new(registry: Registry, config: Config) -> Self {
+        let mut metadata = config.metadata.clone();
+        if let Some(dir) = &config.dir {
+            metadata.root = metadata.root.map(|root| dir.join(root));
+            metadata.cache = metadata.cache.map(|cache| dir.join(cache));
+        }
+        let metadata = MetadataStore::new(metadata);
+        Self {
+            registry,
+            config,
+            metadata,
+        }
+    }
+
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn metadata(&self) -> &MetadataStore {
+        &self.metadata
+    }
+
+    pub fn lint_file(&self, path: impl AsRef<Path>) -> Result<Vec<Diagnostic>, Error> {
+        let source = Source::load(path)?;
+        self.lint(&source)
+    }
+
+    pub fn lint(&self, source: &Source) -> Result<Vec<Diagnostic>, Error> {
+        let workflow: Workflow = serde_yml::from_str(source.text())?;
+        self.lint_workflow(&workflow, source)
+    }
+
+    /// Lint an already parsed `workflow`, `source` should be the text it was
+    /// parsed from
+    pub fn lint_workflow(
+        &self,
+        workflow: &Workflow,
+        source: &Source,
+    ) -> Result<Vec<Diagnostic>, Error> {
+        let suppressions = Suppressions::parse(source);
+        let mut ret = Vec::new();
+        for rule in self.registry.rules() {
+            let resolved = self.config.resolve(rule, source.path());
+            let Some(severity) = resolved.level.severity(rule.default_severity()) else {
+                continue;
+            };
+            let cx = Context {
+                workflow,
+                source,
+                options: &resolved.options,
+                metadata: &self.metadata,
+                config_dir: self.config.dir.as_deref(),
+            };
+            let mut diagnostics = Vec::new();
+            rule.check(&cx, &mut diagnostics)?;
+            ret.extend(
+                diagnostics
+                    .into_iter()
+                    .filter(|d| {
+                        let line = source.position(d.span.start).line;
+                        !suppressions.is_suppressed(&d.rule, line)
+                    })
+                    .map(|mut d| {
+                        d.severity = severity;
+                        d
+                    }),
+            );
+        }
+        ret.sort_by(|lhs, rhs| lhs.span.cmp(&rhs.span).then(lhs.rule.cmp(&rhs.rule)));
+        Ok(ret)
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Parse(serde_yml::Error),
+    Config(toml::de::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "io error: {e}"),
+            Error::Parse(e) => write!(f, "invalid workflow: {e}"),
+            Error::Config(e) => write!(f, "invalid configuration: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_yml::Error> for Error {
+    fn from(value: serde_yml::Error) -> Self {
+        Self::Parse(value)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(value: toml::de::Error) -> Self {
+        Self::Config(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"name: ci
+on: push
+jobs:
+  build:
+    steps:
+      - run: cargo build
+  test: # grackle: ignore[missing-runs-on]
+    steps:
+      - run: cargo test
+  # grackle: ignore
+  lint:
+    steps:
+      - run: cargo clippy
+  release:
+    runs-on: ubuntu-latest
+    steps:
+      - run: cargo publish
+"#;
+
+    fn lint(config: &str, path: &str) -> Vec<Diagnostic> {
+        let config: Config = config.parse().unwrap();
+        let linter = Linter::new(Registry::builtin(), config);
+        linter.lint(&Source::new(path, WORKFLOW)).unwrap()
+    }
+
+    #[test]
+    fn builtin_rules_and_suppressions() {
+        insta::assert_yaml_snapshot!(lint("", ".github/workflows/ci.yml"));
+    }
+
+    #[test]
+    fn severity_overrides() {
+        let config = r#"
+[rules]
+missing-runs-on = "warn"
+
+[[overrides]]
+files = [".github/workflows/experimental/**"]
+rules = { missing-runs-on = "off" }
+"#;
+        let diagnostics = lint(config, ".github/workflows/ci.yml");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(lint(config, ".github/workflows/experimental/ci.yml").is_empty());
+    }
+
+    #[test]
+    fn markers_outside_comments_are_ignored() {
+        let linter = Linter::new(Registry::builtin(), Config::default());
+        let workflow = r#"name: ci
+on: push
+jobs:
+  build:
+    steps:
+      - run: |
+          cargo build
+          # grackle: ignore
+  test:
+    steps:
+      - run: cargo test
+"#;
+        let diagnostics = linter.lint(&Source::new("ci.yml", workflow)).unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.iter().all(|d| d.rule == "missing-runs-on"));
+    }
+
+    #[test]
+    fn metadata_relative_to_config() {
+        let dir = crate::testing::TempDir::new("metadata-config");
+        std::fs::write(
+            dir.join("grackle.toml"),
+            "[metadata]\nroot = \"repo\"\ncache = \".grackle/actions\"\n",
+        )
+        .unwrap();
+        let (_, config) = Config::discover(dir.join("repo/.github/workflows"))
+            .unwrap()
+            .unwrap();
+        let linter = Linter::new(Registry::builtin(), config);
+        let source = Source::new("ci.yml", "");
+        assert_eq!(linter.metadata().root(&source), dir.join("repo"));
+        let Ok(crate::uses::Uses::Repository(checkout)) = "actions/checkout@v4".parse() else {
+            panic!("a repository action");
+        };
+        assert_eq!(
+            linter.metadata().cached(&checkout)[0],
+            dir.join(".grackle/actions/actions/checkout/v4")
+        );
+    }
+
+    #[test]
+    fn markers_must_end_at_the_word() {
+        let linter = Linter::new(Registry::builtin(), Config::default());
+        let workflow = r#"name: ci
+on: push
+jobs:
+  build: # grackle: ignored for now
+    steps: []
+  test: # grackle: ignore-next-line
+    steps: []
+  lint: # grackle: ignore  runners are set by the caller
+    steps: []
+"#;
+        let source = Source::new("ci.yml", workflow);
+        let diagnostics = linter.lint(&source).unwrap();
+        let lines: Vec<_> = diagnostics
+            .iter()
+            .map(|d| source.position(d.span.start).line)
+            .collect();
+        assert_eq!(lines, [4, 6]);
+    }
+
+    struct NoDebugJobs;
+
+    impl Rule for NoDebugJobs {
+        fn id(&self) -> &'static str {
+            "no-debug-jobs"
+        }
+        fn description(&self) -> &'static str {
+            "jobs should not be named debug"
+        }
+        fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+            let forbidden: Vec<String> = cx.option("names")?.unwrap_or_default();
+            for name in cx.workflow.jobs.keys() {
+                if forbidden.contains(name) {
+                    diagnostics.push(Diagnostic::new(
+                        self.id(),
+                        format!("job `{name}` is forbidden"),
+                        cx.source.key_span_of(&crate::span::YamlPath::job(name)),
+                    ));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_rule_options() {
+        let config: Config = r#"
+[rules]
+missing-runs-on = "off"
+no-debug-jobs = { level = "error", names = ["lint"] }
+"#
+        .parse()
+        .unwrap();
+        let mut registry = Registry::builtin();
+        registry.register(NoDebugJobs);
+        let linter = Linter::new(registry, config);
+        let diagnostics = linter.lint(&Source::new("ci.yml", WORKFLOW)).unwrap();
+        // the `lint` job is suppressed by the `grackle: ignore` comment
+        assert!(diagnostics.is_empty());
+        let diagnostics = linter
+            .lint(&Source::new(
+                "ci.yml",
+                WORKFLOW.replace("  # grackle: ignore\n", ""),
+            ))
+            .unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, "no-debug-jobs");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+    }
+}
@@ -0,0 +1,194 @@
+use super::resolved_steps;
+use crate::{
+    action::Runs,
+    lint::{Applicability, Context, Diagnostic, Error, Fix, Rule, Severity, TextEdit},
+};
+
+/// Inputs passed to an action that it doesn't declare, which GitHub
+/// silently ignores, and required inputs that aren't passed
+///
+/// The action's metadata is read from the repository for local actions and
+/// from the `[metadata]` cache for everything else, steps whose action
+/// can't be found aren't checked.
+pub struct ActionInputs;
+
+impl Rule for ActionInputs {
+    fn id(&self) -> &'static str {
+        "action-inputs"
+    }
+
+    fn description(&self) -> &'static str {
+        "steps must only pass inputs the action declares and every required input"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        for resolved in resolved_steps(cx) {
+            let action = &resolved.action;
+            let docker = matches!(action.runs, Runs::Docker(_));
+            for key in resolved.step.with.keys() {
+                if find(action.inputs.keys(), key).is_some()
+                    || (docker && matches!(key.as_str(), "args" | "entrypoint"))
+                {
+                    continue;
+                }
+                let span = cx
+                    .source
+                    .key_span_of(&resolved.path.clone().key("with").key(key));
+                let mut diagnostic = Diagnostic::new(
+                    self.id(),
+                    format!("`{}` has no input `{key}`", resolved.uses),
+                    span,
+                );
+                if let Some((suggestion, applicability)) = suggest(action.inputs.keys(), key) {
+                    diagnostic = diagnostic.with_fix(Fix {
+                        message: format!("rename to `{suggestion}`"),
+                        applicability,
+                        edits: vec![TextEdit::new(span, suggestion)],
+                    });
+                } else if !action.inputs.is_empty() {
+                    let inputs: Vec<_> = action.inputs.keys().map(|k| format!("`{k}`")).collect();
+                    diagnostic = diagnostic
+                        .with_note(format!("expected one of {}", inputs.join(", ")), None);
+                }
+                diagnostics.push(diagnostic);
+            }
+            for (name, input) in &action.inputs {
+                if !input.required
+                    || input.default.is_some()
+                    || find(resolved.step.with.keys(), name).is_some()
+                {
+                    continue;
+                }
+                let mut diagnostic = Diagnostic::new(
+                    self.id(),
+                    format!("`{}` requires the input `{name}`", resolved.uses),
+                    cx.source.span_of(&resolved.path.clone().key("uses")),
+                );
+                if let Some(description) = &input.description {
+                    diagnostic = diagnostic.with_note(format!("`{name}`: {description}"), None);
+                }
+                diagnostics.push(diagnostic);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Input names are case insensitive
+pub(crate) fn find<'a>(
+    mut names: impl Iterator<Item = &'a String>,
+    name: &str,
+) -> Option<&'a String> {
+    names.find(|n| n.eq_ignore_ascii_case(name))
+}
+
+/// The declared input `key` was probably meant to be, renaming is safe when
+/// the two only differ by `-` and `_`
+pub(crate) fn suggest<'a>(
+    names: impl Iterator<Item = &'a String> + Clone,
+    key: &str,
+) -> Option<(&'a str, Applicability)> {
+    let normalize = |s: &str| s.to_ascii_lowercase().replace('_', "-");
+    if let Some(name) = names.clone().find(|n| normalize(n) == normalize(key)) {
+        return Some((name, Applicability::Safe));
+    }
+    names
+        .map(|n| {
+            (
+                n,
+                distance(&n.to_ascii_lowercase(), &key.to_ascii_lowercase()),
+            )
+        })
+        .filter(|(_, d)| *d <= 2)
+        .min_by_key(|(_, d)| *d)
+        .map(|(n, _)| (n.as_str(), Applicability::Unsafe))
+}
+
+/// The Levenshtein distance between two strings
+fn distance(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+    let mut prev: Vec<usize> = (0..=rhs.len()).collect();
+    for (i, l) in lhs.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, r) in rhs.iter().enumerate() {
+            let cost = usize::from(l != *r);
+            current.push((prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+    prev[rhs.len()]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::ActionInputs;
+    use crate::testing::TempDir;
+
+    /// A metadata cache holding `actions/upload-artifact@v4` in a directory
+    /// unique to `test`, returned along with the config pointing at it
+    pub(crate) fn cache(test: &str) -> (TempDir, String) {
+        let dir = TempDir::new(&format!("action-metadata-{test}"));
+        let upload = dir.join("actions/upload-artifact/v4");
+        std::fs::create_dir_all(&upload).unwrap();
+        std::fs::write(
+            upload.join("action.yml"),
+            r#"name: Upload a Build Artifact
+description: Upload a build artifact that can be used by subsequent workflow steps
+inputs:
+  name:
+    description: Artifact name
+    default: artifact
+  path:
+    description: A file, directory or wildcard pattern that describes what to upload
+    required: true
+  retention-days:
+    description: Duration after which artifact will expire in days
+  overwrite:
+    description: Overwrite an existing artifact with the same name
+    default: false
+  compression:
+    description: Zlib compression level
+    deprecationMessage: use compression-level
+outputs:
+  artifact-id:
+    description: A unique identifier for the artifact that was just uploaded
+runs:
+  using: node20
+  main: dist/upload/index.js
+"#,
+        )
+        .unwrap();
+        let config = format!("[metadata]\ncache = {:?}\n", dir.display().to_string());
+        (dir, config)
+    }
+
+    const WORKFLOW: &str = r#"name: ci
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/upload-artifact@v4
+        with:
+          name: dist
+          retention_days: 3
+          overwite: true
+      - uses: actions/upload-artifact@v4
+        with:
+          name: other
+      - uses: actions/checkout@v4
+        with:
+          anything: goes
+"#;
+
+    #[test]
+    fn unknown_and_missing_inputs() {
+        let (_dir, config) = cache("inputs");
+        let diagnostics = crate::lint::rules::check(ActionInputs, &config, WORKFLOW);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+}
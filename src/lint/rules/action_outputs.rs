@@ -0,0 +1,106 @@
+use super::{resolved_steps, walk_strings};
+use crate::{
+    expr,
+    lint::{edit::find_in_value, Context, Diagnostic, Error, Rule, Severity},
+    span::YamlPath,
+};
+
+/// References to `steps.<id>.outputs.<name>` where the action the step uses
+/// doesn't declare the output, these always evaluate to an empty string
+pub struct ActionOutputs;
+
+impl Rule for ActionOutputs {
+    fn id(&self) -> &'static str {
+        "action-outputs"
+    }
+
+    fn description(&self) -> &'static str {
+        "step outputs must be declared by the action the step uses"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let resolved = resolved_steps(cx);
+        for (job, def) in &cx.workflow.jobs {
+            let Ok(value) = serde_yml::to_value(def) else {
+                continue;
+            };
+            walk_strings(&value, &YamlPath::job(job), &mut |text, path| {
+                for embedded in expr::embedded(text) {
+                    for reference in expr::references(embedded.expr) {
+                        let segments: Vec<_> = reference.segments().collect();
+                        let [context, id, outputs, name, ..] = segments[..] else {
+                            continue;
+                        };
+                        if !context.eq_ignore_ascii_case("steps")
+                            || !outputs.eq_ignore_ascii_case("outputs")
+                            || name == "*"
+                        {
+                            continue;
+                        }
+                        let Some(step) = resolved
+                            .iter()
+                            .find(|r| r.job == job && r.step.id.as_deref() == Some(id))
+                        else {
+                            continue;
+                        };
+                        let declared = &step.action.outputs;
+                        if declared.keys().any(|k| k.eq_ignore_ascii_case(name)) {
+                            continue;
+                        }
+                        let needle = format!("{id}.outputs.{name}");
+                        let span = find_in_value(cx.source, path, &needle)
+                            .into_iter()
+                            .next()
+                            .unwrap_or_else(|| cx.source.span_of(path));
+                        let mut diagnostic = Diagnostic::new(
+                            self.id(),
+                            format!("`{}` has no output `{name}`", step.uses),
+                            span,
+                        );
+                        if !declared.is_empty() {
+                            let names: Vec<_> = declared.keys().map(|k| format!("`{k}`")).collect();
+                            diagnostic = diagnostic.with_note(
+                                format!("the action declares {}", names.join(", ")),
+                                None,
+                            );
+                        }
+                        diagnostics.push(diagnostic);
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActionOutputs;
+    use crate::lint::rules::action_inputs::tests::cache;
+
+    #[test]
+    fn undeclared_outputs() {
+        let workflow = r#"name: ci
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    outputs:
+      id: ${{ steps.upload.outputs.artifact-id }}
+      url: ${{ steps.upload.outputs.artifact-url }}
+    steps:
+      - id: upload
+        uses: actions/upload-artifact@v4
+        with:
+          path: dist
+      - run: echo "${{ steps.upload.outputs.artifact_id }}"
+"#;
+        let (_dir, config) = cache("outputs");
+        let diagnostics = crate::lint::rules::check(ActionOutputs, &config, workflow);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+}
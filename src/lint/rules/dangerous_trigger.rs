@@ -0,0 +1,461 @@
+use crate::{
+    expr,
+    lint::{Context, Diagnostic, Error, Rule, Severity},
+    span::{Span, YamlPath},
+    uses::Uses,
+    Job, JobSecrets, JobStep, PermissionValue,
+};
+use serde_yml::Value;
+use std::collections::BTreeSet;
+
+/// References to the code of the pull request that triggered a
+/// `pull_request_target` workflow
+const PULL_REQUEST_REFS: &[&str] = &[
+    "github.head_ref",
+    "github.event.number",
+    "github.event.pull_request.head",
+    "github.event.pull_request.number",
+    "github.event.pull_request.merge_commit_sha",
+];
+
+/// References to the code or artifacts of the workflow run that triggered a
+/// `workflow_run` workflow
+const WORKFLOW_RUN_REFS: &[&str] = &[
+    "github.event.workflow_run.head_sha",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_repository",
+    "github.event.workflow_run.id",
+];
+
+/// Commands that run code from the checked out repository, e.g. build
+/// scripts, package manager hooks or test suites
+const BUILD_COMMANDS: &[&str] = &[
+    "npm",
+    "npx",
+    "yarn",
+    "pnpm",
+    "node",
+    "make",
+    "cmake",
+    "cargo",
+    "pip",
+    "python",
+    "python3",
+    "pytest",
+    "tox",
+    "poetry",
+    "gradle",
+    "./gradlew",
+    "mvn",
+    "./mvnw",
+    "bundle",
+    "rake",
+    "go",
+    "composer",
+    "docker",
+    "bash",
+    "sh",
+];
+
+/// `pull_request_target` and `workflow_run` workflows that check out and
+/// run code from the pull request or workflow run that triggered them, or
+/// pass it to a reusable workflow
+///
+/// These events run in the context of the base repository with access to its
+/// secrets and a token that may be able to write to it, so any code from the
+/// pull request that runs can steal both. Each trigger is checked against
+/// the references to what triggered it.
+pub struct DangerousTrigger;
+
+impl Rule for DangerousTrigger {
+    fn id(&self) -> &'static str {
+        "dangerous-trigger"
+    }
+
+    fn description(&self) -> &'static str {
+        "privileged workflows must not run code from untrusted pull requests"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let on = &cx.workflow.on;
+        let triggers = [
+            (
+                on.pull_request_target.is_some(),
+                Trigger {
+                    name: "pull_request_target",
+                    source: "pull request",
+                    refs: PULL_REQUEST_REFS,
+                },
+            ),
+            (
+                on.workflow_run.is_some(),
+                Trigger {
+                    name: "workflow_run",
+                    source: "workflow run",
+                    refs: WORKFLOW_RUN_REFS,
+                },
+            ),
+        ];
+        for (_, trigger) in triggers.iter().filter(|(listening, _)| *listening) {
+            for (name, job) in &cx.workflow.jobs {
+                if let Some(d) = self.check_job(cx, trigger, name, job) {
+                    diagnostics.push(d);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A privileged trigger and the references to what triggered it
+struct Trigger {
+    name: &'static str,
+    /// What triggers the workflow, for messages
+    source: &'static str,
+    refs: &'static [&'static str],
+}
+
+impl DangerousTrigger {
+    fn check_job(
+        &self,
+        cx: &Context<'_>,
+        trigger: &Trigger,
+        name: &str,
+        job: &Job,
+    ) -> Option<Diagnostic> {
+        let mut diagnostic = match &job.uses {
+            Some(uses) => self.check_call(cx, trigger, name, job, uses.as_str()?)?,
+            None => self.check_steps(cx, trigger, name, job)?,
+        };
+        for (message, path) in exposed_secrets(name, job) {
+            diagnostic = diagnostic.with_note(message, Some(cx.source.span_of(&path)));
+        }
+        let (permissions, path) = match (job.permissions, cx.workflow.permissions) {
+            (Some(perms), _) => (Some(perms), YamlPath::job(name).key("permissions")),
+            (None, Some(perms)) => (Some(perms), YamlPath::root().key("permissions")),
+            (None, None) => (None, YamlPath::job(name)),
+        };
+        match permissions {
+            Some(perms) => {
+                let writes: Vec<_> = perms
+                    .scopes()
+                    .into_iter()
+                    .filter(|(_, value)| *value == PermissionValue::Write)
+                    .map(|(scope, _)| format!("`{scope}: write`"))
+                    .collect();
+                if !writes.is_empty() {
+                    diagnostic = diagnostic.with_note(
+                        format!("the job's token has {}", writes.join(", ")),
+                        Some(cx.source.span_of(&path)),
+                    );
+                }
+            }
+            None => {
+                diagnostic = diagnostic.with_note(
+                    "`permissions` aren't restricted so the job's token may have write access",
+                    Some(cx.source.key_span_of(&path)),
+                );
+            }
+        }
+        Some(diagnostic)
+    }
+
+    fn privileged(
+        &self,
+        cx: &Context<'_>,
+        trigger: &Trigger,
+        message: String,
+        span: Span,
+    ) -> Diagnostic {
+        Diagnostic::new(self.id(), message, span).with_note(
+            format!(
+                "`{}` runs with the base repository's secrets and a token that may be able to write to it",
+                trigger.name
+            ),
+            Some(cx.source.key_span_of(&YamlPath::root().key("on").key(trigger.name))),
+        )
+    }
+
+    /// A job that checks out or downloads untrusted code and maybe runs it
+    fn check_steps(
+        &self,
+        cx: &Context<'_>,
+        trigger: &Trigger,
+        name: &str,
+        job: &Job,
+    ) -> Option<Diagnostic> {
+        let (checkout_idx, checkout) = job
+            .steps
+            .iter()
+            .enumerate()
+            .find(|(_, step)| fetches_untrusted(step, trigger.refs))?;
+        let checkout_path = YamlPath::step(name, checkout_idx);
+        let executing: Vec<_> = job
+            .steps
+            .iter()
+            .enumerate()
+            .skip(checkout_idx + 1)
+            .filter(|(_, step)| executes_checkout(step))
+            .collect();
+        let message = if executing.is_empty() {
+            format!(
+                "job `{name}` fetches untrusted code in a privileged `{}` workflow",
+                trigger.name
+            )
+        } else {
+            format!(
+                "job `{name}` runs untrusted code in a privileged `{}` workflow",
+                trigger.name
+            )
+        };
+        let ref_path = checkout_path.clone().key("with").key("ref");
+        let span = if checkout.with.contains_key("ref") {
+            cx.source.span_of(&ref_path)
+        } else {
+            cx.source.span_of(&checkout_path)
+        };
+        let mut diagnostic = self.privileged(cx, trigger, message, span).with_note(
+            format!(
+                "this step fetches code or artifacts from the triggering {}",
+                trigger.source
+            ),
+            Some(cx.source.span_of(&checkout_path)),
+        );
+        for (idx, _) in &executing {
+            diagnostic = diagnostic.with_note(
+                "this step runs the untrusted code",
+                Some(cx.source.span_of(&YamlPath::step(name, *idx))),
+            );
+        }
+        let persisted = checkout
+            .with
+            .get("persist-credentials")
+            .map(|v| !matches!(v, Value::Bool(false)) && v.as_str() != Some("false"))
+            .unwrap_or(true);
+        if is_checkout(checkout) && persisted && !executing.is_empty() {
+            diagnostic = diagnostic.with_note(
+                "`persist-credentials` isn't disabled so the token is readable from `.git/config`",
+                Some(cx.source.span_of(&checkout_path)),
+            );
+        }
+        Some(diagnostic)
+    }
+
+    /// A job calling a reusable workflow with a reference to untrusted code,
+    /// which the called workflow runs with the caller's privileges
+    fn check_call(
+        &self,
+        cx: &Context<'_>,
+        trigger: &Trigger,
+        name: &str,
+        job: &Job,
+        uses: &str,
+    ) -> Option<Diagnostic> {
+        let (input, _) = job
+            .with
+            .iter()
+            .find(|(_, value)| references_untrusted(value, trigger.refs))?;
+        let path = YamlPath::job(name).key("with").key(input);
+        let message = format!(
+            "job `{name}` passes untrusted code to `{uses}` in a privileged `{}` workflow",
+            trigger.name
+        );
+        Some(
+            self.privileged(cx, trigger, message, cx.source.span_of(&path))
+                .with_note(
+                    format!(
+                        "`{input}` refers to code or artifacts from the triggering {}",
+                        trigger.source
+                    ),
+                    Some(cx.source.span_of(&path)),
+                ),
+        )
+    }
+}
+
+fn is_checkout(step: &JobStep) -> bool {
+    uses_action(step, "actions/checkout")
+}
+
+fn uses_action(step: &JobStep, action: &str) -> bool {
+    let Some(Ok(Uses::Repository(r))) = step.uses.as_deref().map(str::parse::<Uses>) else {
+        return false;
+    };
+    let name = match &r.path {
+        Some(path) => format!("{}/{path}", r.repository()),
+        None => r.repository(),
+    };
+    name.eq_ignore_ascii_case(action)
+}
+
+fn references_untrusted(value: &Value, refs: &[&str]) -> bool {
+    let Some(value) = value.as_str() else {
+        return false;
+    };
+    expr::embedded(value).iter().any(|e| {
+        expr::references(e.expr)
+            .iter()
+            .any(|r| refs.iter().any(|u| r.starts_with(u)))
+    })
+}
+
+/// A checkout of the pull request's code or a download of the artifacts its
+/// workflow run produced
+fn fetches_untrusted(step: &JobStep, refs: &[&str]) -> bool {
+    let with_untrusted = |key: &str| {
+        step.with
+            .get(key)
+            .is_some_and(|v| references_untrusted(v, refs))
+    };
+    if is_checkout(step) {
+        return with_untrusted("ref") || with_untrusted("repository");
+    }
+    if uses_action(step, "actions/download-artifact")
+        || uses_action(step, "dawidd6/action-download-artifact")
+    {
+        return with_untrusted("run-id") || with_untrusted("run_id");
+    }
+    false
+}
+
+/// A step that runs code from the workspace, either a local action or a
+/// `run` script calling a build tool
+fn executes_checkout(step: &JobStep) -> bool {
+    if let Some(Ok(Uses::Local(_))) = step.uses.as_deref().map(str::parse::<Uses>) {
+        return true;
+    }
+    let Some(run) = step.run.as_deref() else {
+        return false;
+    };
+    run.split(|c: char| c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')'))
+        .any(|word| word.starts_with("./") || BUILD_COMMANDS.contains(&word))
+}
+
+/// A note for every secret referenced by the job, other than the
+/// `GITHUB_TOKEN` which is covered by its permissions, and for passing every
+/// secret to a reusable workflow
+fn exposed_secrets(name: &str, job: &Job) -> Vec<(String, YamlPath)> {
+    let mut seen = BTreeSet::new();
+    let mut ret = Vec::new();
+    let mut visit = |value: &str, path: YamlPath| {
+        for embedded in expr::embedded(value) {
+            for r in expr::references(embedded.expr) {
+                let mut segments = r.segments();
+                if segments.next() != Some("secrets") {
+                    continue;
+                }
+                let Some(secret) = segments.next() else {
+                    continue;
+                };
+                if secret.eq_ignore_ascii_case("GITHUB_TOKEN") || !seen.insert(secret.to_string()) {
+                    continue;
+                }
+                ret.push((
+                    format!("`secrets.{secret}` is available to the job"),
+                    path.clone(),
+                ));
+            }
+        }
+    };
+    for (key, value) in &job.env {
+        visit(value, YamlPath::job(name).key("env").key(key));
+    }
+    for (idx, step) in job.steps.iter().enumerate() {
+        let path = YamlPath::step(name, idx);
+        for (key, value) in &step.env {
+            visit(value, path.clone().key("env").key(key));
+        }
+        for (key, value) in &step.with {
+            if let Some(value) = value.as_str() {
+                visit(value, path.clone().key("with").key(key));
+            }
+        }
+        if let Some(run) = &step.run {
+            visit(run, path.clone().key("run"));
+        }
+    }
+    match &job.secrets {
+        JobSecrets::Inherit => ret.push((
+            "every secret is passed to the called workflow with `secrets: inherit`".to_string(),
+            YamlPath::job(name).key("secrets"),
+        )),
+        JobSecrets::Map(secrets) => {
+            for (key, value) in secrets {
+                visit(value, YamlPath::job(name).key("secrets").key(key));
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DangerousTrigger;
+
+    const WORKFLOW: &str = r#"name: pr
+on:
+  pull_request_target:
+    types: [opened, synchronize]
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          ref: ${{ github.event.pull_request.head.sha }}
+      - run: npm ci && npm test
+        env:
+          NPM_TOKEN: ${{ secrets.NPM_TOKEN }}
+  label:
+    runs-on: ubuntu-latest
+    permissions:
+      pull-requests: write
+    steps:
+      - uses: actions/checkout@v4
+      - uses: actions/labeler@v5
+"#;
+
+    #[test]
+    fn pull_request_target_checkout() {
+        let diagnostics = crate::lint::rules::check(DangerousTrigger, "", WORKFLOW);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+
+    #[test]
+    fn each_trigger() {
+        let workflow = r#"name: pr
+on:
+  pull_request_target:
+  workflow_run:
+    workflows: [ci]
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          ref: ${{ github.event.pull_request.head.sha || github.event.workflow_run.head_sha }}
+      - run: make test
+  deploy:
+    uses: ./.github/workflows/deploy.yml
+    with:
+      ref: ${{ github.event.workflow_run.head_sha }}
+    secrets: inherit
+"#;
+        let diagnostics = crate::lint::rules::check(DangerousTrigger, "", workflow);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        insta::assert_debug_snapshot!(messages);
+        let inherit = diagnostics.last().unwrap().notes.iter().map(|n| &n.message);
+        assert!(inherit.into_iter().any(|n| n.contains("secrets: inherit")));
+    }
+
+    #[test]
+    fn ignores_other_triggers() {
+        let workflow = WORKFLOW.replace("pull_request_target", "pull_request");
+        assert!(crate::lint::rules::check(DangerousTrigger, "", &workflow).is_empty());
+    }
+}
@@ -0,0 +1,67 @@
+use super::{action_inputs::find, resolved_steps};
+use crate::lint::{Context, Diagnostic, Error, Rule};
+
+/// Inputs the action has marked with a `deprecationMessage`
+pub struct DeprecatedInput;
+
+impl Rule for DeprecatedInput {
+    fn id(&self) -> &'static str {
+        "deprecated-input"
+    }
+
+    fn description(&self) -> &'static str {
+        "steps should not pass inputs the action has deprecated"
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        for resolved in resolved_steps(cx) {
+            for key in resolved.step.with.keys() {
+                let Some(name) = find(resolved.action.inputs.keys(), key) else {
+                    continue;
+                };
+                let Some(message) = &resolved.action.inputs[name].deprecation_message else {
+                    continue;
+                };
+                diagnostics.push(
+                    Diagnostic::new(
+                        self.id(),
+                        format!("the `{key}` input of `{}` is deprecated", resolved.uses),
+                        cx.source
+                            .key_span_of(&resolved.path.clone().key("with").key(key)),
+                    )
+                    .with_note(message.clone(), None),
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DeprecatedInput;
+    use crate::lint::rules::action_inputs::tests::cache;
+
+    #[test]
+    fn deprecated_inputs() {
+        let workflow = r#"name: ci
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/upload-artifact@v4
+        with:
+          path: dist
+          Compression: 9
+"#;
+        let (_dir, config) = cache("deprecated");
+        let diagnostics = crate::lint::rules::check(DeprecatedInput, &config, workflow);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "the `Compression` input of `actions/upload-artifact@v4` is deprecated"
+        );
+        assert_eq!(diagnostics[0].notes[0].message, "use compression-level");
+    }
+}
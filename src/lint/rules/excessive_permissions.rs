@@ -0,0 +1,210 @@
+use crate::{
+    lint::{edit, Applicability, Context, Diagnostic, Error, Fix, Rule, TextEdit},
+    permissions::{Database, JobPermissions},
+    span::{Source, Span, YamlPath},
+    Permissions,
+};
+
+/// Jobs whose token has more permissions than the steps in it need
+///
+/// Only jobs that declare `permissions`, or inherit them from the workflow,
+/// are checked since the default permissions depend on repository settings.
+/// Jobs with a step whose needs aren't in the database aren't reported,
+/// since whatever it needs may be what looks unneeded.
+///
+/// Options:
+/// - `database`: the path to a permissions database, relative to the
+///   configuration file, that extends the one bundled with grackle
+/// - `unrestricted`: also report jobs that don't declare any permissions
+pub struct ExcessivePermissions;
+
+impl Rule for ExcessivePermissions {
+    fn id(&self) -> &'static str {
+        "excessive-permissions"
+    }
+
+    fn description(&self) -> &'static str {
+        "jobs should only be granted the permissions they need"
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let mut database = Database::bundled();
+        if let Some(path) = cx.option::<String>("database")? {
+            database.extend(Database::load(cx.config_path(path))?);
+        }
+        let unrestricted = cx.option::<bool>("unrestricted")?.unwrap_or(false);
+        let inference = database.infer(cx.workflow);
+        for (name, inferred) in inference.over_privileged() {
+            if (inferred.declared.is_none() && !unrestricted) || !inferred.unknown.is_empty() {
+                continue;
+            }
+            diagnostics.push(self.check_job(cx, name, inferred));
+        }
+        Ok(())
+    }
+}
+
+impl ExcessivePermissions {
+    fn check_job(&self, cx: &Context<'_>, name: &str, inferred: &JobPermissions) -> Diagnostic {
+        let job = &cx.workflow.jobs[name];
+        let job_path = YamlPath::job(name);
+        let (message, span) = match (job.permissions, cx.workflow.permissions) {
+            (Some(_), _) => (
+                format!("job `{name}` is granted more permissions than it needs"),
+                cx.source.key_span_of(&job_path.clone().key("permissions")),
+            ),
+            (None, Some(_)) => (
+                format!("job `{name}` inherits more permissions than it needs from the workflow"),
+                cx.source.key_span_of(&job_path),
+            ),
+            (None, None) => (
+                format!("job `{name}` doesn't restrict the permissions of its token"),
+                cx.source.key_span_of(&job_path),
+            ),
+        };
+        let excess: Vec<_> = inferred
+            .excess()
+            .into_iter()
+            .map(|(scope, granted, required)| {
+                if required.is_none() {
+                    format!("`{scope}: {granted}`")
+                } else {
+                    format!("`{scope}: {granted}` (needs `{required}`)")
+                }
+            })
+            .collect();
+        let mut diagnostic = Diagnostic::new(self.id(), message, span)
+            .with_note(format!("unneeded: {}", excess.join(", ")), None);
+        for reason in &inferred.reasons {
+            diagnostic = diagnostic.with_note(
+                format!(
+                    "`{}` needs `{}: {}`",
+                    reason.source, reason.scope, reason.value
+                ),
+                Some(cx.source.span_of(&reason.path)),
+            );
+        }
+        let edit = match job.permissions {
+            Some(_) => replace(cx.source, &job_path.clone().key("permissions"), inferred),
+            None => cx.source.locate(&job_path).and_then(|job| {
+                let indent = cx.source.position(job.value.start).column - 1;
+                let value = block(&inferred.required, indent + 2);
+                edit::insert_entry(
+                    cx.source,
+                    &job_path,
+                    "permissions",
+                    value.trim_start_matches(' '),
+                )
+            }),
+        };
+        if let Some(edit) = edit {
+            // the database may be missing something a step does, so the
+            // workflow should be run after the fix is applied
+            diagnostic = diagnostic.with_fix(Fix {
+                message: "grant only the inferred permissions".to_string(),
+                applicability: Applicability::Unsafe,
+                edits: vec![edit],
+            });
+        }
+        diagnostic
+    }
+}
+
+/// Replace the declared `permissions` at `path` with the inferred ones,
+/// keeping a block mapping at the indentation it was written at
+fn replace(source: &Source, path: &YamlPath, inferred: &JobPermissions) -> Option<TextEdit> {
+    let node = source.locate(path)?;
+    let key = node.key?;
+    let key_line = source.position(key.start).line;
+    let value_line = source.position(node.value.start).line;
+    let (start, indent) = if value_line > key_line {
+        // keep any comment after `permissions:`
+        let indent = source.position(node.value.start).column - 1;
+        (source.line_span(key_line).end, indent)
+    } else {
+        let colon = key.end + source.text()[key.end..].find(':')? + 1;
+        (colon, node.indent + 2)
+    };
+    Some(TextEdit::new(
+        Span::new(start, node.value.end),
+        block(&inferred.required, indent),
+    ))
+}
+
+/// `permissions` as the text following `permissions:`, a block mapping at
+/// `indent` or ` {}` when nothing is needed
+fn block(permissions: &Permissions, indent: usize) -> String {
+    let entries: String = permissions
+        .scopes()
+        .into_iter()
+        .filter(|(_, value)| !value.is_none())
+        .map(|(scope, value)| format!("\n{:indent$}{scope}: {value}", ""))
+        .collect();
+    if entries.is_empty() {
+        " {}".to_string()
+    } else {
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExcessivePermissions;
+
+    const WORKFLOW: &str = r#"name: ci
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - run: cargo test
+  comment:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: write
+      pull-requests: write
+    steps:
+      - run: gh pr comment "$PR" --body done
+  custom:
+    runs-on: ubuntu-latest
+    permissions: write-all
+    steps:
+      - uses: octo-org/mystery-action@v1
+  minimal:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: read
+    steps:
+      - uses: actions/checkout@v4
+"#;
+
+    #[test]
+    fn over_privileged_jobs() {
+        let diagnostics = crate::lint::rules::check(ExcessivePermissions, "", WORKFLOW);
+        insta::assert_yaml_snapshot!(diagnostics);
+        // `custom` uses an action the database doesn't know
+        assert!(diagnostics.iter().all(|d| !d.message.contains("custom")));
+        let config = "[rules]\nexcessive-permissions = { unrestricted = true }";
+        let diagnostics = crate::lint::rules::check(ExcessivePermissions, config, WORKFLOW);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].message,
+            "job `test` doesn't restrict the permissions of its token"
+        );
+    }
+
+    #[test]
+    fn block_mapping_fixes() {
+        let mut registry = crate::lint::Registry::new();
+        registry.register(ExcessivePermissions);
+        let config = "[rules]\nexcessive-permissions = { unrestricted = true }";
+        let linter = crate::lint::Linter::new(registry, config.parse().unwrap());
+        let source = crate::span::Source::new("workflow.yml", WORKFLOW);
+        let fixed = crate::lint::Fixer::new()
+            .unsafe_fixes(crate::lint::UnsafeFixes::Allow)
+            .fix(&linter, &source)
+            .unwrap();
+        insta::assert_snapshot!(fixed.source.text());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    filter::FilterPattern,
+    lint::{Context, Diagnostic, Error, Rule, Severity},
+    span::YamlPath,
+};
+
+/// `branches`, `tags` and `paths` patterns that aren't valid in GitHub's
+/// glob dialect, most often regex syntax that GitHub matches literally
+///
+/// Also reports filters combined with their `-ignore` variant, `!` patterns
+/// in `-ignore` filters and filters that only have `!` patterns, none of
+/// which match anything.
+pub struct FilterPatterns;
+
+impl Rule for FilterPatterns {
+    fn id(&self) -> &'static str {
+        "filter-pattern"
+    }
+
+    fn description(&self) -> &'static str {
+        "branch, tag and path filters must be valid glob patterns"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let on = &cx.workflow.on;
+        let mut triggers = Vec::new();
+        if let Some(push) = &on.push {
+            triggers.push((
+                "push",
+                vec![
+                    ("branches", &push.branches, &push.branches_ignore),
+                    ("tags", &push.tags, &push.tags_ignore),
+                    ("paths", &push.paths, &push.paths_ignore),
+                ],
+            ));
+        }
+        for (trigger, pr) in [
+            ("pull_request", &on.pull_request),
+            ("pull_request_target", &on.pull_request_target),
+        ] {
+            if let Some(pr) = pr {
+                triggers.push((
+                    trigger,
+                    vec![
+                        ("branches", &pr.branches, &pr.branches_ignore),
+                        ("paths", &pr.paths, &pr.paths_ignore),
+                    ],
+                ));
+            }
+        }
+        if let Some(run) = &on.workflow_run {
+            triggers.push((
+                "workflow_run",
+                vec![("branches", &run.branches, &run.branches_ignore)],
+            ));
+        }
+
+        for (trigger, filters) in triggers {
+            let path = YamlPath::root().key("on").key(trigger);
+            for (filter, include, ignore) in filters {
+                let ignore_filter = format!("{filter}-ignore");
+                self.check_patterns(cx, &path.clone().key(filter), include, diagnostics);
+                self.check_patterns(cx, &path.clone().key(&ignore_filter), ignore, diagnostics);
+                if !include.is_empty() && !ignore.is_empty() {
+                    diagnostics.push(Diagnostic::new(
+                        self.id(),
+                        format!("`{filter}` and `{ignore_filter}` can't both be used for `{trigger}`, use `!` patterns in `{filter}` instead"),
+                        cx.source.key_span_of(&path.clone().key(&ignore_filter)),
+                    ));
+                }
+                if !include.is_empty() && include.iter().all(FilterPattern::is_negated) {
+                    diagnostics.push(Diagnostic::new(
+                        self.id(),
+                        format!("`{filter}` only has `!` patterns so it never matches"),
+                        cx.source.key_span_of(&path.clone().key(filter)),
+                    ));
+                }
+                for (idx, pattern) in ignore.iter().enumerate() {
+                    if pattern.is_negated() {
+                        diagnostics.push(Diagnostic::new(
+                            self.id(),
+                            format!("`!` patterns can't be used in `{ignore_filter}`"),
+                            cx.source
+                                .span_of(&path.clone().key(&ignore_filter).index(idx)),
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FilterPatterns {
+    fn check_patterns(
+        &self,
+        cx: &Context<'_>,
+        path: &YamlPath,
+        patterns: &[FilterPattern],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for (idx, pattern) in patterns.iter().enumerate() {
+            for problem in pattern.problems() {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("invalid pattern `{pattern}`, {problem}"),
+                    cx.source.span_of(&path.clone().index(idx)),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilterPatterns;
+
+    #[test]
+    fn invalid_patterns() {
+        let workflow = r#"name: release
+on:
+  push:
+    branches:
+      - main
+      - release/*
+    tags:
+      - '^v\d+\.\d+$'
+      - v[0-9]+.[0-9]+.[0-9]+
+    paths:
+      - "!docs/**"
+  pull_request:
+    branches:
+      - feature/(a|b)
+      - "[a-z"
+    branches-ignore:
+      - "!main"
+jobs: {}
+"#;
+        let diagnostics = crate::lint::rules::check(FilterPatterns, "", workflow);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{
+    lint::{Context, Diagnostic, Error, Rule, Severity},
+    span::YamlPath,
+    Input, InputType, WorkflowDispatch,
+};
+use std::collections::BTreeMap;
+
+/// Inputs of `workflow_call` and `workflow_dispatch` whose `default` isn't
+/// valid for their `type`, types the trigger doesn't support and
+/// `workflow_dispatch` triggers declaring more inputs than GitHub allows
+pub struct InputDefault;
+
+impl Rule for InputDefault {
+    fn id(&self) -> &'static str {
+        "input-default"
+    }
+
+    fn description(&self) -> &'static str {
+        "input defaults must be valid for the input's type"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let on = &cx.workflow.on;
+        if let Some(call) = &on.workflow_call {
+            self.check_inputs(cx, "workflow_call", &call.inputs, diagnostics);
+        }
+        if let Some(dispatch) = &on.workflow_dispatch {
+            self.check_inputs(cx, "workflow_dispatch", &dispatch.inputs, diagnostics);
+            if dispatch.inputs.len() > WorkflowDispatch::MAX_INPUTS {
+                let path = YamlPath::root()
+                    .key("on")
+                    .key("workflow_dispatch")
+                    .key("inputs");
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!(
+                        "`workflow_dispatch` declares {} inputs, at most {} are allowed",
+                        dispatch.inputs.len(),
+                        WorkflowDispatch::MAX_INPUTS
+                    ),
+                    cx.source.key_span_of(&path),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InputDefault {
+    fn check_inputs(
+        &self,
+        cx: &Context<'_>,
+        trigger: &str,
+        inputs: &BTreeMap<String, Input>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for (name, input) in inputs {
+            let path = YamlPath::root()
+                .key("on")
+                .key(trigger)
+                .key("inputs")
+                .key(name);
+            match &input.kind {
+                Some(InputType::Choice(options)) if options.is_empty() => {
+                    diagnostics.push(Diagnostic::new(
+                        self.id(),
+                        format!("choice input `{name}` has no `options`"),
+                        cx.source.span_of(&path.clone().key("type")),
+                    ));
+                }
+                Some(kind @ (InputType::Choice(_) | InputType::Environment))
+                    if trigger == "workflow_call" =>
+                {
+                    diagnostics.push(Diagnostic::new(
+                        self.id(),
+                        format!("`{trigger}` doesn't support {kind} inputs"),
+                        cx.source.span_of(&path.clone().key("type")),
+                    ));
+                    continue;
+                }
+                _ => {}
+            }
+            if let Err(e) = input.validate_default() {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("the default of `{name}` is invalid, {e}"),
+                    cx.source.span_of(&path.key("default")),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InputDefault;
+
+    #[test]
+    fn invalid_defaults() {
+        let workflow = r#"name: release
+on:
+  workflow_call:
+    inputs:
+      dry-run:
+        type: boolean
+        default: "no"
+      target:
+        type: environment
+  workflow_dispatch:
+    inputs:
+      level:
+        type: choice
+        options: [patch, minor, major]
+        default: micro
+      replicas:
+        type: number
+        default: 3
+      empty:
+        type: choice
+jobs: {}
+"#;
+        let diagnostics = crate::lint::rules::check(InputDefault, "", workflow);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+
+    #[test]
+    fn too_many_dispatch_inputs() {
+        let inputs: String = (0..26).map(|idx| format!("      i{idx}: {{}}\n")).collect();
+        let workflow =
+            format!("name: release\non:\n  workflow_dispatch:\n    inputs:\n{inputs}jobs: {{}}\n");
+        let diagnostics = crate::lint::rules::check(InputDefault, "", &workflow);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "`workflow_dispatch` declares 26 inputs, at most 25 are allowed"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    lint::{Context, Diagnostic, Error, Rule, Severity},
+    span::YamlPath,
+};
+
+/// Jobs that run steps need a `runs-on`, only jobs that call a reusable
+/// workflow can leave it off
+pub struct MissingRunsOn;
+
+impl Rule for MissingRunsOn {
+    fn id(&self) -> &'static str {
+        "missing-runs-on"
+    }
+
+    fn description(&self) -> &'static str {
+        "jobs must declare `runs-on` unless they call a reusable workflow"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        for (name, job) in &cx.workflow.jobs {
+            if job.runs_on.is_some() || job.uses.is_some() {
+                continue;
+            }
+            diagnostics.push(Diagnostic::new(
+                self.id(),
+                format!("job `{name}` has no `runs-on`"),
+                cx.source.key_span_of(&YamlPath::job(name)),
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+//! The rules that ship with grackle
+
+mod action_inputs;
+mod action_outputs;
+mod dangerous_trigger;
+mod deprecated_input;
+mod excessive_permissions;
+mod filter_pattern;
+mod input_default;
+mod missing_runs_on;
+mod reusable_workflow_call;
+mod script_injection;
+mod unpinned_uses;
+
+pub use action_inputs::ActionInputs;
+pub use action_outputs::ActionOutputs;
+pub use dangerous_trigger::DangerousTrigger;
+pub use deprecated_input::DeprecatedInput;
+pub use excessive_permissions::ExcessivePermissions;
+pub use filter_pattern::FilterPatterns;
+pub use input_default::InputDefault;
+pub use missing_runs_on::MissingRunsOn;
+pub use reusable_workflow_call::ReusableWorkflowCall;
+pub use script_injection::ScriptInjection;
+pub use unpinned_uses::UnpinnedUses;
+
+use super::{Context, Registry};
+use crate::{action::Action, span::YamlPath, uses::Uses, JobStep};
+use serde_yml::Value;
+use std::sync::Arc;
+
+pub(crate) fn register_builtin(registry: &mut Registry) {
+    registry.register(MissingRunsOn);
+    registry.register(ScriptInjection);
+    registry.register(UnpinnedUses::default());
+    registry.register(DangerousTrigger);
+    registry.register(ExcessivePermissions);
+    registry.register(ActionInputs);
+    registry.register(DeprecatedInput);
+    registry.register(ActionOutputs);
+    registry.register(ReusableWorkflowCall);
+    registry.register(InputDefault);
+    registry.register(FilterPatterns);
+}
+
+/// A step using an action whose metadata could be found
+pub(crate) struct ResolvedStep<'a> {
+    pub job: &'a str,
+    pub path: YamlPath,
+    pub step: &'a JobStep,
+    pub uses: &'a str,
+    pub action: Arc<Action>,
+}
+
+pub(crate) fn resolved_steps<'a>(cx: &Context<'a>) -> Vec<ResolvedStep<'a>> {
+    let mut ret = Vec::new();
+    for (job, def) in &cx.workflow.jobs {
+        for (idx, step) in def.steps.iter().enumerate() {
+            let Some(uses) = step.uses.as_deref() else {
+                continue;
+            };
+            let Ok(parsed) = uses.parse::<Uses>() else {
+                continue;
+            };
+            if let Some(action) = cx.metadata.action(&parsed, cx.source) {
+                ret.push(ResolvedStep {
+                    job,
+                    path: YamlPath::step(job, idx),
+                    step,
+                    uses,
+                    action,
+                });
+            }
+        }
+    }
+    ret
+}
+
+/// Call `f` with every string in `value` and its path
+pub(crate) fn walk_strings(value: &Value, path: &YamlPath, f: &mut impl FnMut(&str, &YamlPath)) {
+    match value {
+        Value::String(s) => f(s, path),
+        Value::Sequence(seq) => {
+            for (idx, value) in seq.iter().enumerate() {
+                walk_strings(value, &path.clone().index(idx), f);
+            }
+        }
+        Value::Mapping(map) => {
+            for (key, value) in map {
+                if let Some(key) = key.as_str() {
+                    walk_strings(value, &path.clone().key(key), f);
+                }
+            }
+        }
+        Value::Tagged(tagged) => walk_strings(&tagged.value, path, f),
+        _ => {}
+    }
+}
+
+/// Lint `workflow` with only `rule` enabled
+#[cfg(test)]
+pub(crate) fn check(
+    rule: impl super::Rule + 'static,
+    config: &str,
+    workflow: &str,
+) -> Vec<super::Diagnostic> {
+    let mut registry = Registry::new();
+    registry.register(rule);
+    let linter = super::Linter::new(registry, config.parse().unwrap());
+    linter
+        .lint(&crate::span::Source::new("workflow.yml", workflow))
+        .unwrap()
+}
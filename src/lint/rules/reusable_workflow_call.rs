@@ -0,0 +1,281 @@
+use super::{action_inputs::suggest, walk_strings};
+use crate::{
+    expr,
+    lint::{edit::find_in_value, Context, Diagnostic, Error, Fix, Rule, Severity, TextEdit},
+    span::YamlPath,
+    uses::Uses,
+    Input, JobSecrets, Workflow, WorkflowCall,
+};
+use serde_yml::Value;
+use std::sync::Arc;
+
+/// Jobs calling a reusable workflow must match the contract its
+/// `on.workflow_call` declares
+///
+/// Every required input has to be passed with a value of its `type`, only
+/// declared inputs and secrets may be passed, required secrets have to be
+/// passed unless they are inherited and downstream jobs may only reference
+/// declared outputs. The called workflow is read from the repository or the
+/// `[metadata]` cache, calls to workflows that can't be found aren't checked.
+pub struct ReusableWorkflowCall;
+
+impl Rule for ReusableWorkflowCall {
+    fn id(&self) -> &'static str {
+        "reusable-workflow-call"
+    }
+
+    fn description(&self) -> &'static str {
+        "calls to reusable workflows must match the inputs, secrets and outputs they declare"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let mut callees = Vec::new();
+        for (job, def) in &cx.workflow.jobs {
+            let Some(uses) = def.uses.as_ref().and_then(Value::as_str) else {
+                continue;
+            };
+            let Ok(parsed) = uses.parse::<Uses>() else {
+                continue;
+            };
+            let Some(callee) = cx.metadata.workflow(&parsed, cx.source) else {
+                continue;
+            };
+            let path = YamlPath::job(job);
+            let Some(call) = &callee.on.workflow_call else {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("`{uses}` can't be called, it has no `workflow_call` trigger"),
+                    cx.source.span_of(&path.clone().key("uses")),
+                ));
+                continue;
+            };
+            self.check_inputs(cx, &path, uses, call, def, diagnostics);
+            self.check_secrets(cx, &path, uses, call, def, diagnostics);
+            callees.push((job.as_str(), uses, callee.clone()));
+        }
+        self.check_outputs(cx, &callees, diagnostics);
+        Ok(())
+    }
+}
+
+impl ReusableWorkflowCall {
+    fn check_inputs(
+        &self,
+        cx: &Context<'_>,
+        path: &YamlPath,
+        uses: &str,
+        call: &WorkflowCall,
+        def: &crate::Job,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        for (key, value) in &def.with {
+            let with = path.clone().key("with").key(key);
+            let Some(input) = find(&call.inputs, key) else {
+                let span = cx.source.key_span_of(&with);
+                let mut diagnostic =
+                    Diagnostic::new(self.id(), format!("`{uses}` has no input `{key}`"), span);
+                if let Some((suggestion, applicability)) = suggest(call.inputs.keys(), key) {
+                    diagnostic = diagnostic.with_fix(Fix {
+                        message: format!("rename to `{suggestion}`"),
+                        applicability,
+                        edits: vec![TextEdit::new(span, suggestion)],
+                    });
+                }
+                diagnostics.push(diagnostic);
+                continue;
+            };
+            let Some(kind) = &input.kind else {
+                continue;
+            };
+            if !kind.accepts(value) {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("the `{key}` input of `{uses}` is a {kind}"),
+                    cx.source.span_of(&with),
+                ));
+            }
+        }
+        for (name, input) in &call.inputs {
+            if input.required
+                && input.default.is_none()
+                && !def.with.keys().any(|k| k.eq_ignore_ascii_case(name))
+            {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("`{uses}` requires the input `{name}`"),
+                    cx.source.span_of(&path.clone().key("uses")),
+                ));
+            }
+        }
+    }
+
+    fn check_secrets(
+        &self,
+        cx: &Context<'_>,
+        path: &YamlPath,
+        uses: &str,
+        call: &WorkflowCall,
+        def: &crate::Job,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        // every secret of the caller is passed along, whether the required
+        // ones exist isn't known until the workflow runs
+        let JobSecrets::Map(secrets) = &def.secrets else {
+            return;
+        };
+        for key in secrets.keys() {
+            if !call.secrets.keys().any(|k| k.eq_ignore_ascii_case(key)) {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("`{uses}` has no secret `{key}`"),
+                    cx.source.key_span_of(&path.clone().key("secrets").key(key)),
+                ));
+            }
+        }
+        for (name, secret) in &call.secrets {
+            if secret.required && !secrets.keys().any(|k| k.eq_ignore_ascii_case(name)) {
+                diagnostics.push(Diagnostic::new(
+                    self.id(),
+                    format!("`{uses}` requires the secret `{name}`"),
+                    cx.source.span_of(&path.clone().key("uses")),
+                ));
+            }
+        }
+    }
+
+    /// `needs.<job>.outputs.<name>` where `<job>` calls a workflow that
+    /// doesn't declare `<name>`
+    fn check_outputs(
+        &self,
+        cx: &Context<'_>,
+        callees: &[(&str, &str, Arc<Workflow>)],
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        if callees.is_empty() {
+            return;
+        }
+        for (job, def) in &cx.workflow.jobs {
+            let Ok(value) = serde_yml::to_value(def) else {
+                continue;
+            };
+            walk_strings(&value, &YamlPath::job(job), &mut |text, path| {
+                for embedded in expr::embedded(text) {
+                    for reference in expr::references(embedded.expr) {
+                        let segments: Vec<_> = reference.segments().collect();
+                        let [context, needed, outputs, name, ..] = segments[..] else {
+                            continue;
+                        };
+                        if !context.eq_ignore_ascii_case("needs")
+                            || !outputs.eq_ignore_ascii_case("outputs")
+                            || name == "*"
+                        {
+                            continue;
+                        }
+                        let Some((_, uses, callee)) =
+                            callees.iter().find(|(job, ..)| *job == needed)
+                        else {
+                            continue;
+                        };
+                        let declared = callee.on.workflow_call.as_ref().map(|call| &call.outputs);
+                        if declared.is_some_and(|o| o.keys().any(|k| k.eq_ignore_ascii_case(name)))
+                        {
+                            continue;
+                        }
+                        let span = find_in_value(cx.source, path, &format!("outputs.{name}"))
+                            .into_iter()
+                            .next()
+                            .unwrap_or_else(|| cx.source.span_of(path));
+                        diagnostics.push(Diagnostic::new(
+                            self.id(),
+                            format!("`{uses}` called by `{needed}` has no output `{name}`"),
+                            span,
+                        ));
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn find<'a>(inputs: &'a std::collections::BTreeMap<String, Input>, key: &str) -> Option<&'a Input> {
+    inputs
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, input)| input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReusableWorkflowCall;
+
+    #[test]
+    fn contract() {
+        let root = crate::testing::TempDir::new("reusable-workflow");
+        let workflows = root.join(".github/workflows");
+        std::fs::create_dir_all(&workflows).unwrap();
+        std::fs::write(
+            workflows.join("deploy.yml"),
+            r#"name: deploy
+on:
+  workflow_call:
+    inputs:
+      environment:
+        type: string
+        required: true
+      dry-run:
+        type: boolean
+        default: false
+      replicas:
+        type: number
+    secrets:
+      token:
+        required: true
+    outputs:
+      url:
+        value: ${{ jobs.deploy.outputs.url }}
+jobs:
+  deploy:
+    runs-on: ubuntu-latest
+    steps:
+      - run: ./deploy.sh
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            workflows.join("build.yml"),
+            "name: build\non: push\njobs: {}\n",
+        )
+        .unwrap();
+        let workflow = r#"name: release
+on: push
+jobs:
+  staging:
+    uses: ./.github/workflows/deploy.yml
+    with:
+      dry_run: "yes"
+      replicas: three
+    secrets:
+      tokn: ${{ secrets.TOKEN }}
+  production:
+    uses: ./.github/workflows/deploy.yml
+    with:
+      environment: production
+      replicas: 3
+    secrets: inherit
+  build:
+    uses: ./.github/workflows/build.yml
+  notify:
+    needs: [staging, production]
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo "${{ needs.staging.outputs.url }} ${{ needs.production.outputs.link }}"
+"#;
+        let config = format!("[metadata]\nroot = {:?}\n", root.display().to_string());
+        let diagnostics = crate::lint::rules::check(ReusableWorkflowCall, &config, workflow);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+}
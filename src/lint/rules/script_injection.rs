@@ -0,0 +1,552 @@
+use crate::{
+    expr::{self, Reference},
+    lint::{
+        edit::{find_in_value, insert_entry},
+        Applicability, Context, Diagnostic, Error, Fix, Rule, Severity, TextEdit,
+    },
+    span::YamlPath,
+    Job, JobStep, Workflow,
+};
+use serde_yml::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Context values that can be set by anyone able to open an issue, pull
+/// request or comment, `*` matches any single property or index
+const UNTRUSTED: &[&str] = &[
+    "github.head_ref",
+    "github.event.issue.title",
+    "github.event.issue.body",
+    "github.event.pull_request.title",
+    "github.event.pull_request.body",
+    "github.event.pull_request.head.ref",
+    "github.event.pull_request.head.label",
+    "github.event.pull_request.head.repo.default_branch",
+    "github.event.comment.body",
+    "github.event.review.body",
+    "github.event.review_comment.body",
+    "github.event.discussion.title",
+    "github.event.discussion.body",
+    "github.event.pages.*.page_name",
+    "github.event.commits.*.message",
+    "github.event.commits.*.author.email",
+    "github.event.commits.*.author.name",
+    "github.event.head_commit.message",
+    "github.event.head_commit.author.email",
+    "github.event.head_commit.author.name",
+    "github.event.workflow_run.head_branch",
+    "github.event.workflow_run.head_commit.message",
+    "github.event.workflow_run.head_commit.author.email",
+    "github.event.workflow_run.head_commit.author.name",
+    "github.event.workflow_run.pull_requests.*.head.ref",
+];
+
+/// Attacker controlled values expanded directly into `run` scripts or
+/// `actions/github-script` scripts, tracked through `env`, step outputs, job
+/// outputs and `needs`
+pub struct ScriptInjection;
+
+impl Rule for ScriptInjection {
+    fn id(&self) -> &'static str {
+        "script-injection"
+    }
+
+    fn description(&self) -> &'static str {
+        "untrusted input must not be expanded into scripts"
+    }
+
+    fn default_severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let mut analysis = Analysis::new(cx.workflow);
+        // job outputs can flow into any later job so keep going until they
+        // stop changing before reporting anything
+        for _ in 0..=cx.workflow.jobs.len() {
+            let before = analysis.job_outputs.len();
+            for (name, job) in &cx.workflow.jobs {
+                analysis.job(name, job, None);
+            }
+            if analysis.job_outputs.len() == before {
+                break;
+            }
+        }
+        let mut sinks = Vec::new();
+        for (name, job) in &cx.workflow.jobs {
+            analysis.job(name, job, Some(&mut sinks));
+        }
+        for sink in sinks {
+            diagnostics.push(self.report(cx, sink));
+        }
+        Ok(())
+    }
+}
+
+impl ScriptInjection {
+    fn report(&self, cx: &Context<'_>, sink: Sink) -> Diagnostic {
+        let occurrences = find_in_value(cx.source, &sink.path, &sink.text);
+        let span = occurrences
+            .first()
+            .copied()
+            .unwrap_or_else(|| cx.source.span_of(&sink.path));
+        let origin = &sink.taint.origin;
+        let message = if sink.taint.hops.is_empty() {
+            format!(
+                "`{origin}` is attacker controlled and is expanded directly into {}",
+                sink.kind
+            )
+        } else {
+            format!(
+                "`{}` carries the attacker controlled `{origin}` and is expanded directly into {}",
+                sink.expr, sink.kind
+            )
+        };
+        let mut diagnostic = Diagnostic::new(self.id(), message, span);
+        for hop in &sink.taint.hops {
+            diagnostic =
+                diagnostic.with_note(hop.message.clone(), Some(cx.source.span_of(&hop.path)));
+        }
+        if let Some(fix) = self.fix(cx, &sink, &occurrences) {
+            diagnostic = diagnostic.with_fix(fix);
+        }
+        diagnostic
+    }
+
+    /// Replace the expression with an environment variable read by the
+    /// script, adding the step `env` entry that provides it when it isn't
+    /// already an `env.*` reference
+    fn fix(&self, cx: &Context<'_>, sink: &Sink, occurrences: &[crate::span::Span]) -> Option<Fix> {
+        if occurrences.is_empty() {
+            return None;
+        }
+        let var = |name: &str| -> Option<String> {
+            Some(match (sink.kind, sink.shell.as_str()) {
+                (SinkKind::GithubScript, _) => format!("process.env.{name}"),
+                (_, "pwsh" | "powershell") => format!("$env:{name}"),
+                (_, "cmd") => format!("%{name}%"),
+                (_, "python") => return None,
+                _ => format!("${{{name}}}"),
+            })
+        };
+        let existing = expr::references(&sink.expr)
+            .into_iter()
+            .next()
+            .filter(|r| r.path == sink.expr && r.context() == "env")
+            .map(|r| r.path["env.".len()..].to_string());
+        let mut edits = Vec::new();
+        let name = if let Some(name) = existing {
+            name
+        } else {
+            let base = env_name(&sink.taint.origin);
+            let value = format!("${{{{ {} }}}}", sink.expr);
+            let mut name = base.clone();
+            let mut suffix = 1;
+            let mut needs_entry = true;
+            while let Some(current) = sink.step_env.get(&name) {
+                if *current == value {
+                    needs_entry = false;
+                    break;
+                }
+                suffix += 1;
+                name = format!("{base}_{suffix}");
+            }
+            if needs_entry {
+                edits.push(insert_entry(
+                    cx.source,
+                    &sink.step.clone().key("env"),
+                    &name,
+                    &value,
+                )?);
+            }
+            name
+        };
+        let replacement = var(&name)?;
+        let text = cx.source.text();
+        let mut quoted = true;
+        for span in occurrences {
+            let mut span = *span;
+            if sink.kind == SinkKind::GithubScript {
+                // a JavaScript string holding only the expression becomes the
+                // variable itself, anywhere else the expression was script
+                // source or part of a longer string
+                let quote = text[..span.start].chars().next_back();
+                match quote {
+                    Some(q @ ('"' | '\'' | '`')) if text[span.end..].starts_with(q) => {
+                        span = crate::span::Span::new(span.start - 1, span.end + 1);
+                    }
+                    _ => quoted = false,
+                }
+            } else {
+                quoted &= text[..span.start].ends_with('"') && text[span.end..].starts_with('"');
+            }
+            edits.push(TextEdit::new(span, replacement.clone()));
+        }
+        let read_as = match sink.kind {
+            SinkKind::Run => format!("${name}"),
+            SinkKind::GithubScript => replacement,
+        };
+        Some(Fix {
+            message: format!("pass `{}` to the script as `{read_as}`", sink.expr),
+            // an unquoted shell variable is subject to word splitting where
+            // the expanded expression wasn't, and an unquoted expression in
+            // JavaScript was code rather than a string
+            applicability: if quoted {
+                Applicability::Safe
+            } else {
+                Applicability::Unsafe
+            },
+            edits,
+        })
+    }
+}
+
+/// The env var name used for an untrusted value, e.g. `ISSUE_TITLE` for
+/// `github.event.issue.title`
+fn env_name(origin: &str) -> String {
+    let segments: Vec<_> = origin
+        .split('.')
+        .filter(|s| !matches!(*s, "github" | "event" | "outputs" | "*"))
+        .collect();
+    let start = segments.len().saturating_sub(2);
+    segments[start..]
+        .join("_")
+        .to_uppercase()
+        .replace(|c: char| !c.is_ascii_alphanumeric(), "_")
+}
+
+fn untrusted(reference: &Reference) -> bool {
+    UNTRUSTED.iter().any(|pattern| {
+        let mut segments = reference.segments();
+        let mut matched = true;
+        for expected in pattern.split('.') {
+            match segments.next() {
+                // referencing a parent object (e.g. `toJSON(github.event)`)
+                // includes the untrusted value
+                None => return matched,
+                Some(seg) => {
+                    matched &= expected == "*" || seg == "*" || seg.eq_ignore_ascii_case(expected)
+                }
+            }
+        }
+        matched
+    })
+}
+
+#[derive(Debug, Clone)]
+struct Taint {
+    origin: String,
+    hops: Vec<Hop>,
+}
+
+impl Taint {
+    fn through(&self, message: String, path: YamlPath) -> Self {
+        let mut ret = self.clone();
+        ret.hops.push(Hop { message, path });
+        ret
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Hop {
+    message: String,
+    path: YamlPath,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+enum SinkKind {
+    #[strum(serialize = "a `run` script")]
+    Run,
+    #[strum(serialize = "an `actions/github-script` script")]
+    GithubScript,
+}
+
+struct Sink {
+    kind: SinkKind,
+    /// The path to the value containing the expression
+    path: YamlPath,
+    /// The path to the step
+    step: YamlPath,
+    /// The full `${{ ... }}` text
+    text: String,
+    expr: String,
+    taint: Taint,
+    shell: String,
+    step_env: BTreeMap<String, String>,
+}
+
+/// The tainted values visible while checking a single job
+#[derive(Default)]
+struct Scope<'a> {
+    env: BTreeMap<String, Taint>,
+    steps: BTreeMap<String, Taint>,
+    needs: BTreeSet<&'a str>,
+}
+
+struct Analysis<'a> {
+    workflow: &'a Workflow,
+    workflow_env: BTreeMap<String, Taint>,
+    /// (job, output) to its taint
+    job_outputs: BTreeMap<(String, String), Taint>,
+}
+
+impl<'a> Analysis<'a> {
+    fn new(workflow: &'a Workflow) -> Self {
+        let mut ret = Self {
+            workflow,
+            workflow_env: BTreeMap::new(),
+            job_outputs: BTreeMap::new(),
+        };
+        let scope = Scope::default();
+        for (key, value) in &workflow.env {
+            if let Some((_, taint)) = ret.taint_in(value, &scope) {
+                let path = YamlPath::root().key("env").key(key);
+                ret.workflow_env.insert(
+                    key.clone(),
+                    taint.through(format!("flows into the workflow env var `{key}`"), path),
+                );
+            }
+        }
+        ret
+    }
+
+    fn taint_of(&self, expr: &str, scope: &Scope<'_>) -> Option<Taint> {
+        expr::references(expr).into_iter().find_map(|r| {
+            if untrusted(&r) {
+                return Some(Taint {
+                    origin: r.path,
+                    hops: Vec::new(),
+                });
+            }
+            let mut segments = r.segments();
+            match (segments.next()?, segments.next(), segments.next()) {
+                ("env", Some(name), _) => scope.env.get(name).cloned(),
+                ("steps", Some(id), Some("outputs")) => scope.steps.get(id).cloned(),
+                ("needs", Some(job), Some("outputs")) if scope.needs.contains(job) => {
+                    let output = segments.next()?;
+                    self.job_outputs
+                        .get(&(job.to_string(), output.to_string()))
+                        .cloned()
+                }
+                _ => None,
+            }
+        })
+    }
+
+    fn taint_in<'v>(
+        &self,
+        value: &'v str,
+        scope: &Scope<'_>,
+    ) -> Option<(expr::Embedded<'v>, Taint)> {
+        expr::embedded(value)
+            .into_iter()
+            .find_map(|e| Some((e, self.taint_of(e.expr, scope)?)))
+    }
+
+    fn job(&mut self, name: &str, job: &'a Job, mut sinks: Option<&mut Vec<Sink>>) {
+        let mut scope = Scope {
+            env: self.workflow_env.clone(),
+            steps: BTreeMap::new(),
+            needs: job.needs.iter().map(String::as_str).collect(),
+        };
+        let job_path = YamlPath::job(name);
+        for (key, value) in &job.env {
+            if let Some((_, taint)) = self.taint_in(value, &scope) {
+                let path = job_path.clone().key("env").key(key);
+                scope.env.insert(
+                    key.clone(),
+                    taint.through(format!("flows into the job env var `{key}`"), path),
+                );
+            }
+        }
+        let default_shell = job
+            .defaults
+            .run
+            .shell
+            .clone()
+            .or_else(|| self.workflow.defaults.run.shell.clone())
+            .unwrap_or_else(|| {
+                let windows = job
+                    .runs_on
+                    .as_ref()
+                    .map(|r| format!("{r:?}").to_lowercase().contains("windows"))
+                    .unwrap_or(false);
+                if windows { "pwsh" } else { "bash" }.to_string()
+            });
+        for (idx, step) in job.steps.iter().enumerate() {
+            let step_path = YamlPath::step(name, idx);
+            let mut step_scope = Scope {
+                env: scope.env.clone(),
+                steps: scope.steps.clone(),
+                needs: scope.needs.clone(),
+            };
+            for (key, value) in &step.env {
+                if let Some((_, taint)) = self.taint_in(value, &scope) {
+                    let path = step_path.clone().key("env").key(key);
+                    step_scope.env.insert(
+                        key.clone(),
+                        taint.through(format!("flows into the step env var `{key}`"), path),
+                    );
+                }
+            }
+            if let Some(sinks) = sinks.as_deref_mut() {
+                let shell = step.shell.clone().unwrap_or_else(|| default_shell.clone());
+                self.step_sinks(step, &step_path, &step_scope, shell, sinks);
+            }
+            if let Some(id) = &step.id {
+                if let Some(taint) = self.step_input_taint(step, &step_scope) {
+                    scope.steps.insert(
+                        id.clone(),
+                        taint.through(
+                            format!("is used by step `{id}`, so its outputs are untrusted"),
+                            step_path.clone(),
+                        ),
+                    );
+                }
+            }
+        }
+        for (key, value) in &job.outputs {
+            if let Some((_, taint)) = self.taint_in(value, &scope) {
+                let path = job_path.clone().key("outputs").key(key);
+                self.job_outputs.insert(
+                    (name.to_string(), key.clone()),
+                    taint.through(format!("flows into the job output `{key}`"), path),
+                );
+            }
+        }
+    }
+
+    /// The taint of anything a step consumes, either directly or through
+    /// the env vars its script reads
+    fn step_input_taint(&self, step: &JobStep, scope: &Scope<'_>) -> Option<Taint> {
+        let with = step
+            .with
+            .values()
+            .filter_map(Value::as_str)
+            .find_map(|v| self.taint_in(v, scope).map(|(_, t)| t));
+        with.or_else(|| {
+            let run = step.run.as_deref()?;
+            self.taint_in(run, scope).map(|(_, t)| t).or_else(|| {
+                scope
+                    .env
+                    .iter()
+                    .find(|(key, _)| {
+                        run.contains(&format!("${key}")) || run.contains(&format!("${{{key}}}"))
+                    })
+                    .map(|(_, t)| t.clone())
+            })
+        })
+    }
+
+    fn step_sinks(
+        &self,
+        step: &JobStep,
+        step_path: &YamlPath,
+        scope: &Scope<'_>,
+        shell: String,
+        sinks: &mut Vec<Sink>,
+    ) {
+        let script = step
+            .uses
+            .as_deref()
+            .filter(|uses| uses.to_lowercase().starts_with("actions/github-script@"))
+            .and_then(|_| step.with.get("script").and_then(Value::as_str));
+        let candidates = [
+            (
+                SinkKind::Run,
+                step.run.as_deref(),
+                step_path.clone().key("run"),
+            ),
+            (
+                SinkKind::GithubScript,
+                script,
+                step_path.clone().key("with").key("script"),
+            ),
+        ];
+        for (kind, value, path) in candidates {
+            let Some(value) = value else {
+                continue;
+            };
+            let mut seen = BTreeSet::new();
+            for embedded in expr::embedded(value) {
+                let text = embedded.text(value);
+                if !seen.insert(text) {
+                    continue;
+                }
+                let Some(taint) = self.taint_of(embedded.expr, scope) else {
+                    continue;
+                };
+                sinks.push(Sink {
+                    kind,
+                    path: path.clone(),
+                    step: step_path.clone(),
+                    text: text.to_string(),
+                    expr: embedded.expr.to_string(),
+                    taint,
+                    shell: shell.clone(),
+                    step_env: step.env.clone(),
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScriptInjection;
+
+    const WORKFLOW: &str = r#"name: triage
+on:
+  issues:
+jobs:
+  label:
+    runs-on: ubuntu-latest
+    env:
+      TITLE: ${{ github.event.issue.title }}
+    outputs:
+      title: ${{ steps.meta.outputs.title }}
+    steps:
+      - run: echo "${{ github.event.issue.body }}"
+      - id: meta
+        run: echo "title=$TITLE" >> "$GITHUB_OUTPUT"
+      - run: echo ${{ env.TITLE }}
+  comment:
+    runs-on: ubuntu-latest
+    needs: label
+    steps:
+      - uses: actions/github-script@v7
+        with:
+          script: |
+            console.log("${{ needs.label.outputs.title }}")
+      - run: echo "${{ github.event.issue.number }}"
+"#;
+
+    #[test]
+    fn tainted_sinks() {
+        let diagnostics = crate::lint::rules::check(ScriptInjection, "", WORKFLOW);
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+
+    #[test]
+    fn github_script_fix() {
+        let workflow = r#"name: triage
+on:
+  issues:
+jobs:
+  comment:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/github-script@v7
+        with:
+          script: |
+            const title = "${{ github.event.issue.title }}"
+            console.log(title)
+"#;
+        let mut registry = crate::lint::Registry::new();
+        registry.register(ScriptInjection);
+        let linter = crate::lint::Linter::new(registry, "".parse().unwrap());
+        let source = crate::span::Source::new("workflow.yml", workflow);
+        let fixed = crate::lint::Fixer::new().fix(&linter, &source).unwrap();
+        assert!(fixed.remaining.is_empty());
+        insta::assert_snapshot!(fixed.source.text());
+    }
+}
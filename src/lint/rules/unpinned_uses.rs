@@ -0,0 +1,195 @@
+use crate::{
+    lint::{Applicability, Context, Diagnostic, Error, Fix, Rule, TextEdit},
+    span::YamlPath,
+    uses::{Lockfile, RefKind, Uses},
+};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+/// Actions and reusable workflows referenced by a mutable tag or branch
+///
+/// Options:
+/// - `trusted-owners`: owners (e.g. `actions`) whose references may be
+///   unpinned
+/// - `lockfile`: the path to a [`Lockfile`], relative to the configuration
+///   file, used to fix references by rewriting them to the sha they are
+///   locked to
+#[derive(Default)]
+pub struct UnpinnedUses {
+    /// Lockfiles by path, loaded the first time they are used
+    lockfiles: Mutex<BTreeMap<PathBuf, Arc<Lockfile>>>,
+}
+
+impl Rule for UnpinnedUses {
+    fn id(&self) -> &'static str {
+        "unpinned-uses"
+    }
+
+    fn description(&self) -> &'static str {
+        "actions and reusable workflows should be pinned to a full commit sha"
+    }
+
+    fn check(&self, cx: &Context<'_>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+        let trusted: Vec<String> = cx.option("trusted-owners")?.unwrap_or_default();
+        let lockfile = match cx.option::<String>("lockfile")? {
+            Some(path) => self.lockfile(cx.config_path(path))?,
+            None => Arc::default(),
+        };
+        for (name, job) in &cx.workflow.jobs {
+            let job_uses = job
+                .uses
+                .as_ref()
+                .and_then(|u| u.as_str())
+                .map(|u| (u, YamlPath::job(name).key("uses")));
+            let step_uses = job.steps.iter().enumerate().filter_map(|(idx, step)| {
+                let uses = step.uses.as_deref()?;
+                Some((uses, YamlPath::step(name, idx).key("uses")))
+            });
+            for (uses, path) in job_uses.into_iter().chain(step_uses) {
+                if let Some(d) = self.check_uses(cx, uses, &path, &trusted, &lockfile) {
+                    diagnostics.push(d);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl UnpinnedUses {
+    fn lockfile(&self, path: PathBuf) -> Result<Arc<Lockfile>, Error> {
+        let mut lockfiles = self.lockfiles.lock().unwrap();
+        if let Some(lockfile) = lockfiles.get(&path) {
+            return Ok(lockfile.clone());
+        }
+        let lockfile = Arc::new(Lockfile::load(&path)?);
+        lockfiles.insert(path, lockfile.clone());
+        Ok(lockfile)
+    }
+
+    fn check_uses(
+        &self,
+        cx: &Context<'_>,
+        uses: &str,
+        path: &YamlPath,
+        trusted: &[String],
+        lockfile: &Lockfile,
+    ) -> Option<Diagnostic> {
+        let span = cx.source.span_of(path);
+        let parsed = match uses.parse::<Uses>() {
+            Ok(parsed) => parsed,
+            Err(e) => return Some(Diagnostic::new(self.id(), e.to_string(), span)),
+        };
+        let reference = match parsed {
+            Uses::Local(_) => return None,
+            Uses::Docker(_) if parsed.is_docker_digest() => return None,
+            Uses::Docker(image) => {
+                return Some(Diagnostic::new(
+                    self.id(),
+                    format!("`docker://{image}` is not pinned to an image digest"),
+                    span,
+                ))
+            }
+            Uses::Repository(reference) => reference,
+        };
+        if trusted
+            .iter()
+            .any(|owner| owner.eq_ignore_ascii_case(&reference.owner))
+        {
+            return None;
+        }
+        let kind = reference.kind();
+        if kind == RefKind::Sha {
+            return None;
+        }
+        let message = match kind {
+            RefKind::Branch => format!(
+                "`{reference}` follows the branch `{}`, any push to it changes what runs",
+                reference.git_ref
+            ),
+            _ => format!(
+                "`{reference}` is pinned to the mutable tag `{}`",
+                reference.git_ref
+            ),
+        };
+        let mut diagnostic = Diagnostic::new(self.id(), message, span);
+        if let Some(sha) = lockfile.resolve(&reference) {
+            // a trailing comment keeps the human readable version around,
+            // unless the line already has a comment of its own
+            let line = cx.source.position(span.start).line;
+            let commented = cx.source.comments().iter().any(|(l, _)| *l == line);
+            let mut replacement = reference.with_ref(sha).to_string();
+            if !commented {
+                replacement = format!("{replacement} # {}", reference.git_ref);
+            }
+            diagnostic = diagnostic.with_fix(Fix {
+                message: format!("pin to `{sha}`"),
+                applicability: Applicability::Safe,
+                edits: vec![TextEdit::new(span, replacement)],
+            });
+        } else {
+            diagnostic = diagnostic.with_note(
+                format!(
+                    "add `{}` to the lockfile to allow fixing this automatically",
+                    reference.key()
+                ),
+                None,
+            );
+        }
+        Some(diagnostic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnpinnedUses;
+    use crate::{
+        lint::{Config, Linter, Registry},
+        span::Source,
+    };
+    use std::fs;
+
+    const WORKFLOW: &str = r#"name: ci
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: "swatinem/rust-cache@v2"
+      - uses: dtolnay/rust-toolchain@master # nightly
+      - uses: docker://alpine:3
+      - uses: ./.github/actions/setup
+      - uses: mozilla-actions/sccache-action@2e7f9ec7921547d4b46598398ca573513895d0bd
+  call:
+    uses: octo-org/workflows/.github/workflows/ci.yml@main
+"#;
+
+    #[test]
+    fn unpinned_references() {
+        let dir = crate::testing::TempDir::new("unpinned-uses");
+        fs::write(
+            dir.join("actions.lock"),
+            r#"[actions]
+"swatinem/rust-cache@v2" = "23bce251a8cd2ffc3c1075eaa2367cf899916d84"
+"dtolnay/rust-toolchain@master" = "1482605bfc5719782e1267fd0c0cc350fe7646b8"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("grackle.toml"),
+            r#"[rules]
+unpinned-uses = { trusted-owners = ["actions"], lockfile = "actions.lock" }
+"#,
+        )
+        .unwrap();
+        let config = Config::load(dir.join("grackle.toml")).unwrap();
+        let mut registry = Registry::new();
+        registry.register(UnpinnedUses::default());
+        let linter = Linter::new(registry, config);
+        let diagnostics = linter.lint(&Source::new("workflow.yml", WORKFLOW)).unwrap();
+        insta::assert_yaml_snapshot!(diagnostics);
+    }
+}
@@ -0,0 +1,92 @@
+use crate::span::Source;
+use std::collections::BTreeMap;
+
+const MARKER: &str = "# grackle: ignore";
+
+/// The lines with `# grackle: ignore[rule-id, ...]` comments
+///
+/// A comment at the end of a line applies to that line, a comment on a line
+/// by itself applies to the next line that isn't blank or another comment.
+/// Leaving off the list of rule ids ignores every rule.
+#[derive(Debug, Default)]
+pub(crate) struct Suppressions {
+    /// line number to the suppressed rule ids, `None` for all rules
+    lines: BTreeMap<usize, Option<Vec<String>>>,
+}
+
+impl Suppressions {
+    pub fn parse(source: &Source) -> Self {
+        let mut ret = Self::default();
+        let mut pending: Option<Option<Vec<String>>> = None;
+        let comments: BTreeMap<_, _> = source.comments().into_iter().collect();
+        for line in 1..source.line_count() + 1 {
+            let trimmed = source.line_text(line).trim_start();
+            let comment = comments.get(&line);
+            let own_line = comment.map(|c| c.len() == trimmed.len()) == Some(true);
+            if let Some(rules) = comment.and_then(|c| parse_marker(c)) {
+                if own_line {
+                    merge(&mut pending, rules);
+                } else {
+                    ret.add(line, rules);
+                }
+            }
+            if trimmed.is_empty() || own_line {
+                continue;
+            }
+            if let Some(rules) = pending.take() {
+                ret.add(line, rules);
+            }
+        }
+        ret
+    }
+
+    fn add(&mut self, line: usize, rules: Option<Vec<String>>) {
+        match self.lines.get_mut(&line) {
+            Some(existing) => merge_into(existing, rules),
+            None => {
+                self.lines.insert(line, rules);
+            }
+        }
+    }
+
+    pub fn is_suppressed(&self, rule: &str, line: usize) -> bool {
+        match self.lines.get(&line) {
+            Some(None) => true,
+            Some(Some(ids)) => ids.iter().any(|id| id == rule),
+            None => false,
+        }
+    }
+}
+
+fn merge(pending: &mut Option<Option<Vec<String>>>, rules: Option<Vec<String>>) {
+    match pending {
+        Some(existing) => merge_into(existing, rules),
+        None => *pending = Some(rules),
+    }
+}
+
+fn merge_into(existing: &mut Option<Vec<String>>, rules: Option<Vec<String>>) {
+    match (existing.as_mut(), rules) {
+        (_, None) => *existing = None,
+        (Some(ids), Some(rules)) => ids.extend(rules),
+        (None, Some(_)) => {}
+    }
+}
+
+/// Parse the rule ids out of an ignore comment, `Some(None)` when the comment
+/// applies to all rules
+fn parse_marker(comment: &str) -> Option<Option<Vec<String>>> {
+    let rest = comment.strip_prefix(MARKER)?;
+    let Some(list) = rest.strip_prefix('[') else {
+        // `# grackle: ignored` or `# grackle: ignore-next-line` aren't markers
+        let all = rest.is_empty() || rest.starts_with(char::is_whitespace);
+        return all.then_some(None);
+    };
+    let list = &list[..list.find(']')?];
+    Some(Some(
+        list.split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect(),
+    ))
+}
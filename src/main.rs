@@ -0,0 +1,538 @@
+use clap::{Parser, Subcommand, ValueEnum};
+#[cfg(unix)]
+use grackle::runner::{Conclusion, Runner};
+use grackle::{
+    affected::{Affected, Diff},
+    codegen::{self, Codegen},
+    condition::{Conditions, Evaluator},
+    event::Event,
+    expr::Expr,
+    inventory::Inventory,
+    lint::{Config, Fixer, Linter, Registry, Severity, UnsafeFixes},
+    report,
+    span::Source,
+    Workflow,
+};
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check workflows with the builtin rules, exits with a failure if any
+    /// errors are found
+    Lint {
+        /// Workflow files or directories of them
+        #[arg(default_value = ".github/workflows")]
+        paths: Vec<PathBuf>,
+        /// One of human, github, json, junit, checkstyle or sarif
+        #[arg(long, default_value = "human")]
+        format: report::Format,
+        /// The configuration file, by default the nearest `grackle.toml`
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Color::Auto)]
+        color: Color,
+        /// Apply fixes to the files and report what is left
+        #[arg(long)]
+        fix: bool,
+        /// Print a diff of the fixes without writing them, exits with a
+        /// failure if there is anything to fix
+        #[arg(long, conflicts_with = "fix")]
+        fix_dry_run: bool,
+        /// Also apply unsafe fixes, from every rule or only the rules listed
+        #[arg(long, num_args = 0..=1, require_equals = true, value_delimiter = ',')]
+        unsafe_fixes: Option<Vec<String>>,
+    },
+    /// List the secrets, variables and environment variables each workflow,
+    /// job and step references
+    Inventory {
+        /// Workflow files or directories of them
+        #[arg(default_value = ".github/workflows")]
+        paths: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+    },
+    /// Generate a typed builder module for an action from its `action.yml`
+    Codegen {
+        /// The action's directory or metadata file
+        path: PathBuf,
+        /// What the generated step uses, e.g. `actions/upload-artifact@v4`
+        #[arg(long)]
+        uses: String,
+        /// The name of the generated struct
+        #[arg(long)]
+        name: Option<String>,
+        /// Write the module to a file rather than stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// List the workflows and jobs that pushing the changes between two
+    /// commits, or opening a pull request of them, would run
+    Affected {
+        /// The branch the changes would be merged into
+        #[arg(long, default_value = "main")]
+        base: String,
+        /// The commit with the changes
+        #[arg(long, default_value = "HEAD")]
+        head: String,
+        /// The git checkout to read the diff from
+        #[arg(long, default_value = ".")]
+        repo: PathBuf,
+        /// Workflow files or directories of them, by default the
+        /// `.github/workflows` directory of `--repo`
+        paths: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+    },
+    /// Evaluate the job and step conditions of workflows for an event,
+    /// listing which jobs run, skip or depend on data known at runtime
+    Conditions {
+        /// The event, e.g. `push` or `pull_request`
+        #[arg(long, default_value = "push")]
+        event: String,
+        /// The activity type, e.g. `opened`
+        #[arg(long)]
+        activity: Option<String>,
+        /// The ref pushed, e.g. `main` or `refs/tags/v1.0.0`
+        #[arg(long = "ref")]
+        git_ref: Option<String>,
+        /// The branch a pull request targets
+        #[arg(long)]
+        base: Option<String>,
+        /// Assume these jobs fail when they run
+        #[arg(long, value_delimiter = ',')]
+        fail: Vec<String>,
+        /// Workflow files or directories of them
+        #[arg(default_value = ".github/workflows")]
+        paths: Vec<PathBuf>,
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+    },
+    #[cfg(unix)]
+    /// Run the jobs of a workflow on this machine, each in a temporary
+    /// workspace, exits with a failure if a job fails
+    Run {
+        /// The workflow file
+        workflow: PathBuf,
+        /// Only run this job, as if the jobs it needs succeeded
+        #[arg(long)]
+        job: Option<String>,
+        /// How many jobs run at once, the number of CPUs by default
+        #[arg(long)]
+        parallel: Option<usize>,
+        /// The format of the report of a workflow run
+        #[arg(long, value_enum, default_value_t = Format::Markdown)]
+        format: Format,
+        /// The event, e.g. `push` or `pull_request`
+        #[arg(long, default_value = "push")]
+        event: String,
+        /// The ref pushed, e.g. `main` or `refs/tags/v1.0.0`
+        #[arg(long = "ref")]
+        git_ref: Option<String>,
+        /// `name=value` pairs of the `inputs` context
+        #[arg(long, value_parser = key_value)]
+        input: Vec<(String, String)>,
+        /// `name=value` pairs of the `vars` context
+        #[arg(long, value_parser = key_value)]
+        var: Vec<(String, String)>,
+        /// `name=value` pairs of the `secrets` context
+        #[arg(long, value_parser = key_value)]
+        secret: Vec<(String, String)>,
+        /// Run in this directory rather than a temporary one
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+        /// `action=script` pairs, run the script in place of the action,
+        /// e.g. `actions/setup-node@v4='echo skipped'`
+        #[arg(long, value_parser = key_value)]
+        stub: Vec<(String, String)>,
+        /// Skip the steps of actions without a stub rather than failing them
+        #[arg(long)]
+        skip_unstubbed: bool,
+        /// Keep artifacts and caches in this directory, caches are shared by
+        /// the runs using it
+        #[arg(long)]
+        store: Option<PathBuf>,
+    },
+}
+
+fn key_value(arg: &str) -> std::result::Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected `name=value`, found `{arg}`"))
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Color {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Markdown,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Lint {
+            paths,
+            format,
+            config,
+            color,
+            fix,
+            fix_dry_run,
+            unsafe_fixes,
+        } => {
+            let mode = match unsafe_fixes {
+                None => UnsafeFixes::Deny,
+                Some(rules) if rules.is_empty() => UnsafeFixes::Allow,
+                Some(rules) => UnsafeFixes::Rules(rules.into_iter().collect()),
+            };
+            let fixer = Fixer::new().unsafe_fixes(mode);
+            if fix_dry_run {
+                fix_diff(&paths, config, &fixer)
+            } else {
+                let fixer = fix.then_some(fixer);
+                lint(&paths, format, config, color, fixer.as_ref())
+            }
+        }
+        Command::Inventory { paths, format } => inventory(&paths, format),
+        Command::Codegen {
+            path,
+            uses,
+            name,
+            output,
+        } => codegen(&path, uses, name, output),
+        Command::Affected {
+            base,
+            head,
+            repo,
+            paths,
+            format,
+        } => affected(&repo, &base, &head, &paths, format),
+        Command::Conditions {
+            event,
+            activity,
+            git_ref,
+            base,
+            fail,
+            paths,
+            format,
+        } => {
+            let mut event = Event::builder().name(event).build();
+            event.activity = activity;
+            event.git_ref = git_ref.as_deref().map(Into::into);
+            event.base_ref = base;
+            conditions(event, &fail, &paths, format)
+        }
+        #[cfg(unix)]
+        Command::Run {
+            workflow,
+            job,
+            parallel,
+            format,
+            event,
+            git_ref,
+            input,
+            var,
+            secret,
+            workspace,
+            stub,
+            skip_unstubbed,
+            store,
+        } => {
+            let mut event = Event::builder().name(event).build();
+            event.git_ref = git_ref.as_deref().map(Into::into);
+            let mut runner = Runner::builder().event(event);
+            for (name, value) in input {
+                let value = match value.as_str() {
+                    "true" | "false" => Expr::Bool(value == "true"),
+                    _ => Expr::String(value),
+                };
+                runner = runner.input(name, value);
+            }
+            for (name, value) in var {
+                runner = runner.var(name, value);
+            }
+            for (name, value) in secret {
+                runner = runner.secret(name, value);
+            }
+            for (action, script) in stub {
+                runner = runner.stub_script(action, script);
+            }
+            let runner = runner.parallel(parallel).skip_unstubbed(skip_unstubbed);
+            let runner = match (workspace, store) {
+                (Some(workspace), Some(store)) => runner.workspace(workspace).store(store).build(),
+                (Some(workspace), None) => runner.workspace(workspace).build(),
+                (None, Some(store)) => runner.store(store).build(),
+                (None, None) => runner.build(),
+            };
+            run(&runner, &workflow, job, format)
+        }
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn linter(config: Option<PathBuf>) -> Result<Linter> {
+    let config = match config {
+        Some(path) => Config::load(path)?,
+        None => Config::discover(std::env::current_dir()?)?
+            .map(|(_, config)| config)
+            .unwrap_or_default(),
+    };
+    Ok(Linter::new(Registry::builtin(), config))
+}
+
+fn lint(
+    paths: &[PathBuf],
+    format: report::Format,
+    config: Option<PathBuf>,
+    color: Color,
+    fixer: Option<&Fixer>,
+) -> Result<ExitCode> {
+    let linter = linter(config)?;
+    let color = match color {
+        Color::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        Color::Always => true,
+        Color::Never => false,
+    };
+    let stdout = std::io::stdout().lock();
+    let mut reporter = format.reporter(stdout, linter.registry(), color);
+    let mut failed = false;
+    for path in workflow_files(paths)? {
+        let mut source = Source::load(&path)?;
+        let mut changed = false;
+        let result = match fixer {
+            Some(fixer) => fixer.fix(&linter, &source).map(|fixed| {
+                if !fixed.fixed.is_empty() {
+                    eprintln!("fixed {} issues in {}", fixed.fixed.len(), path.display());
+                }
+                changed = fixed.is_changed();
+                source = fixed.source;
+                fixed.remaining
+            }),
+            None => linter.lint(&source),
+        };
+        let diagnostics = match result {
+            Ok(diagnostics) => diagnostics,
+            Err(e) => {
+                eprintln!("error: {}: {e}", path.display());
+                failed = true;
+                continue;
+            }
+        };
+        if changed {
+            std::fs::write(&path, source.text())?;
+        }
+        failed |= diagnostics.iter().any(|d| d.severity == Severity::Error);
+        reporter.report(&source, &diagnostics)?;
+    }
+    reporter.finish()?;
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn fix_diff(paths: &[PathBuf], config: Option<PathBuf>, fixer: &Fixer) -> Result<ExitCode> {
+    let linter = linter(config)?;
+    let mut changed = false;
+    for path in workflow_files(paths)? {
+        let fixed = fixer.fix(&linter, &Source::load(&path)?)?;
+        for skipped in &fixed.skipped {
+            eprintln!(
+                "{}: not fixing {} ({})",
+                path.display(),
+                skipped.diagnostic.rule,
+                skipped.reason
+            );
+        }
+        changed |= fixed.is_changed();
+        print!("{}", fixed.diff());
+    }
+    Ok(if changed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn inventory(paths: &[PathBuf], format: Format) -> Result<ExitCode> {
+    let mut inventory = Inventory::new();
+    for path in workflow_files(paths)? {
+        let text = std::fs::read_to_string(&path)?;
+        let workflow: Workflow =
+            serde_yml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+        inventory.add(path.display().to_string(), &workflow);
+    }
+    match format {
+        Format::Json => println!("{}", inventory.to_json()),
+        Format::Markdown => print!("{}", inventory.to_markdown()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn codegen(
+    path: &Path,
+    uses: String,
+    name: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<ExitCode> {
+    let action = codegen::load(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut codegen = Codegen::new(&action, uses);
+    if let Some(name) = name {
+        codegen = codegen.name(name);
+    }
+    match output {
+        Some(output) => std::fs::write(output, codegen.generate())?,
+        None => print!("{}", codegen.generate()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn affected(
+    repo: &Path,
+    base: &str,
+    head: &str,
+    paths: &[PathBuf],
+    format: Format,
+) -> Result<ExitCode> {
+    let mut affected = Affected::new(Diff::from_git(repo, base, head)?);
+    let default = [repo.join(".github/workflows")];
+    let paths = if paths.is_empty() {
+        &default[..]
+    } else {
+        paths
+    };
+    for path in workflow_files(paths)? {
+        let text = std::fs::read_to_string(&path)?;
+        let workflow: Workflow =
+            serde_yml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+        let name = path.strip_prefix(repo).unwrap_or(&path);
+        affected.add(name.display().to_string(), &workflow);
+    }
+    match format {
+        Format::Json => println!("{}", affected.to_json()),
+        Format::Markdown => print!("{}", affected.to_markdown()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn conditions(
+    event: Event,
+    fail: &[String],
+    paths: &[PathBuf],
+    format: Format,
+) -> Result<ExitCode> {
+    let evaluator = fail
+        .iter()
+        .fold(Evaluator::new(&event), |evaluator, job| evaluator.fail(job));
+    let mut conditions = Conditions::with_evaluator(event, evaluator);
+    for path in workflow_files(paths)? {
+        let text = std::fs::read_to_string(&path)?;
+        let workflow: Workflow =
+            serde_yml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+        conditions.add(path.display().to_string(), &workflow);
+    }
+    match format {
+        Format::Json => println!("{}", conditions.to_json()),
+        Format::Markdown => print!("{}", conditions.to_markdown()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(unix)]
+fn run(runner: &Runner, path: &Path, job: Option<String>, format: Format) -> Result<ExitCode> {
+    let text = std::fs::read_to_string(path)?;
+    let workflow: Workflow =
+        serde_yml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+    let Some(job) = job else {
+        let run = runner.run_workflow(&workflow)?;
+        match format {
+            Format::Json => println!("{}", run.to_json()),
+            Format::Markdown => print!("{}", run.to_markdown()),
+        }
+        return Ok(if run.result == Conclusion::Success {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    };
+    let run = runner.run_job(&workflow, &job)?;
+    for step in &run.steps {
+        let mark = match step.conclusion {
+            Conclusion::Success if step.outcome == Conclusion::Failure => "!",
+            Conclusion::Success => "✓",
+            Conclusion::Skipped => "-",
+            Conclusion::Failure | Conclusion::Cancelled => "✗",
+        };
+        println!("{mark} {}", step.name);
+        print!("{}", step.stdout);
+        eprint!("{}", step.stderr);
+        if let Some(message) = &step.message {
+            println!("  {message}");
+        }
+        if step.timed_out {
+            println!("  timed out");
+        }
+    }
+    if let Some(error) = &run.error {
+        println!("{error}");
+    }
+    for (name, value) in &run.outputs {
+        println!("{name}={value}");
+    }
+    println!("{} {}", run.name, run.result);
+    Ok(if run.result == Conclusion::Failure {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+/// Expand directories into the `.yml` and `.yaml` files directly inside them
+fn workflow_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut ret = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            ret.push(path.clone());
+            continue;
+        }
+        let mut files: Vec<_> = std::fs::read_dir(path)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| is_yaml(p))
+            .collect();
+        files.sort();
+        ret.extend(files);
+    }
+    Ok(ret)
+}
+
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yml" | "yaml")
+    )
+}
@@ -0,0 +1,151 @@
+//! Find the metadata of the actions and reusable workflows a workflow uses
+//! without network access
+//!
+//! Local actions and workflows (`uses: ./path`) are read from the repository
+//! the workflow belongs to. Those in other repositories are read from a cache
+//! directory laid out as `<cache>/<owner>/<repo>/<ref>/<path>`, holding an
+//! `action.yml` for actions and the workflow file itself for reusable
+//! workflows. The `<ref>` directory may be left out to use the same files for
+//! every ref.
+
+use crate::{
+    action::Action,
+    span::Source,
+    uses::{RepositoryRef, Uses},
+    Workflow,
+};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// Where to look for metadata, the `[metadata]` table of a `grackle.toml`,
+/// with paths relative to the file
+///
+/// ```toml
+/// [metadata]
+/// root = "."
+/// cache = ".grackle/actions"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataConfig {
+    /// The root of the repository, by default the directory containing the
+    /// `.github` directory of the linted file
+    #[serde(default)]
+    pub root: Option<PathBuf>,
+    #[serde(default)]
+    pub cache: Option<PathBuf>,
+}
+
+/// Loads and caches action metadata
+#[derive(Debug, Default)]
+pub struct MetadataStore {
+    config: MetadataConfig,
+    actions: Mutex<HashMap<PathBuf, Option<Arc<Action>>>>,
+    workflows: Mutex<HashMap<PathBuf, Option<Arc<Workflow>>>>,
+}
+
+impl MetadataStore {
+    pub fn new(config: MetadataConfig) -> Self {
+        Self {
+            config,
+            actions: Mutex::default(),
+            workflows: Mutex::default(),
+        }
+    }
+
+    /// The metadata of the action `uses` refers to from the workflow in
+    /// `source`, `None` for docker images or if it can't be found
+    pub fn action(&self, uses: &Uses, source: &Source) -> Option<Arc<Action>> {
+        let candidates = match uses {
+            Uses::Local(path) => vec![self.root(source).join(path.trim_start_matches("./"))],
+            Uses::Repository(reference) => self.cached(reference),
+            Uses::Docker(_) => return None,
+        };
+        candidates.into_iter().find_map(|dir| self.load(dir))
+    }
+
+    /// The reusable workflow a job's `uses` refers to from the workflow in
+    /// `source`, `None` if it can't be found or parsed
+    pub fn workflow(&self, uses: &Uses, source: &Source) -> Option<Arc<Workflow>> {
+        let candidates = match uses {
+            Uses::Local(path) => vec![self.root(source).join(path.trim_start_matches("./"))],
+            Uses::Repository(reference) => self.cached(reference),
+            Uses::Docker(_) => return None,
+        };
+        let mut workflows = self.workflows.lock().unwrap_or_else(|e| e.into_inner());
+        candidates.into_iter().find_map(|path| {
+            workflows
+                .entry(path)
+                .or_insert_with_key(|path| {
+                    let text = std::fs::read_to_string(path).ok()?;
+                    serde_yml::from_str(&text).ok().map(Arc::new)
+                })
+                .clone()
+        })
+    }
+
+    /// The root of the repository `source` belongs to
+    pub fn root(&self, source: &Source) -> PathBuf {
+        if let Some(root) = &self.config.root {
+            return root.clone();
+        }
+        let path = Path::new(source.path());
+        path.ancestors()
+            .find(|dir| dir.file_name().is_some_and(|name| name == ".github"))
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// The directories in the cache that may hold `reference`, most specific
+    /// first
+    pub fn cached(&self, reference: &RepositoryRef) -> Vec<PathBuf> {
+        let Some(cache) = &self.config.cache else {
+            return Vec::new();
+        };
+        let repo = cache.join(&reference.owner).join(&reference.repo);
+        let path = reference.path.as_deref().unwrap_or_default();
+        vec![repo.join(&reference.git_ref).join(path), repo.join(path)]
+    }
+
+    fn load(&self, dir: PathBuf) -> Option<Arc<Action>> {
+        let mut actions = self.actions.lock().unwrap_or_else(|e| e.into_inner());
+        actions
+            .entry(dir)
+            .or_insert_with_key(|dir| Action::load_dir(dir).ok().map(Arc::new))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_actions() {
+        let root = crate::testing::TempDir::new("local-action");
+        let dir = root.join(".github/actions/setup");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("action.yaml"),
+            "name: setup\nruns:\n  using: composite\n  steps: []\n",
+        )
+        .unwrap();
+        let store = MetadataStore::default();
+        let source = Source::new(
+            root.join(".github/workflows/ci.yml").display().to_string(),
+            "",
+        );
+        assert_eq!(store.root(&source), root.to_path_buf());
+        let uses = "./.github/actions/setup".parse().unwrap();
+        let action = store.action(&uses, &source).unwrap();
+        assert_eq!(action.runs.using(), "composite");
+        assert!(store
+            .action(&"./missing".parse().unwrap(), &source)
+            .is_none());
+    }
+}
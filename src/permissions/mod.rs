@@ -0,0 +1,344 @@
+//! Infer the least `GITHUB_TOKEN` permissions each job needs.
+//!
+//! Inference is driven by a [`Database`] of the scopes needed by actions and
+//! by commands (e.g. `gh release create`) found in `run` scripts. grackle
+//! bundles a database of common actions which can be extended from a toml
+//! file in the same format.
+
+use crate::{
+    expr,
+    span::YamlPath,
+    uses::{RepositoryRef, Uses},
+    Job, PermissionValue, Permissions, Workflow,
+};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path, sync::OnceLock};
+
+const BUNDLED: &str = include_str!("database.toml");
+
+/// The scopes needed by actions and commands
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Database {
+    /// keyed by `owner/repo` or `owner/repo/path`
+    #[serde(default)]
+    pub actions: BTreeMap<String, Permissions>,
+    /// keyed by the leading words of a command, e.g. `gh pr comment`
+    #[serde(default)]
+    pub commands: BTreeMap<String, Permissions>,
+}
+
+impl Database {
+    /// The database that ships with grackle
+    pub fn bundled() -> Self {
+        static PARSED: OnceLock<Database> = OnceLock::new();
+        PARSED
+            .get_or_init(|| toml::from_str(BUNDLED).expect("bundled permissions database is valid"))
+            .clone()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Add every entry of `other`, replacing any existing entries
+    pub fn extend(&mut self, other: Database) {
+        self.actions.extend(
+            other
+                .actions
+                .into_iter()
+                .map(|(k, v)| (k.to_lowercase(), v)),
+        );
+        self.commands.extend(other.commands);
+    }
+
+    fn action(&self, reference: &RepositoryRef) -> Option<&Permissions> {
+        let repository = reference.repository().to_lowercase();
+        let full = reference
+            .path
+            .as_ref()
+            .map(|path| format!("{repository}/{}", path.to_lowercase()));
+        full.and_then(|full| self.find_action(&full))
+            .or_else(|| self.find_action(&repository))
+    }
+
+    fn find_action(&self, name: &str) -> Option<&Permissions> {
+        self.actions
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, perms)| perms)
+    }
+
+    /// Infer the permissions needed by every job in `workflow`
+    pub fn infer(&self, workflow: &Workflow) -> Inference {
+        let jobs = workflow
+            .jobs
+            .iter()
+            .map(|(name, job)| {
+                let mut inferred = self.infer_job(name, job);
+                inferred.declared = job.permissions.or(workflow.permissions);
+                (name.clone(), inferred)
+            })
+            .collect();
+        Inference { jobs }
+    }
+
+    fn infer_job(&self, name: &str, job: &Job) -> JobPermissions {
+        let mut ret = JobPermissions::default();
+        if let Some(uses) = job.uses.as_ref().and_then(|u| u.as_str()) {
+            ret.unknown.push(Unknown {
+                path: YamlPath::job(name).key("uses"),
+                reason: format!("calls the reusable workflow `{uses}`"),
+            });
+            return ret;
+        }
+        for (idx, step) in job.steps.iter().enumerate() {
+            let path = YamlPath::step(name, idx);
+            if let Some(uses) = &step.uses {
+                match uses.parse::<Uses>() {
+                    Ok(Uses::Repository(reference)) => match self.action(&reference) {
+                        Some(perms) => ret.require(*perms, &path, reference.repository()),
+                        None => ret.unknown.push(Unknown {
+                            path: path.clone(),
+                            reason: format!(
+                                "`{}` isn't in the permissions database",
+                                reference.repository()
+                            ),
+                        }),
+                    },
+                    Ok(Uses::Local(local)) => ret.unknown.push(Unknown {
+                        path: path.clone(),
+                        reason: format!("the local action `{local}` can't be inferred"),
+                    }),
+                    Ok(Uses::Docker(_)) | Err(_) => {}
+                }
+                if step
+                    .with
+                    .get("script")
+                    .is_some_and(|_| uses.to_lowercase().starts_with("actions/github-script@"))
+                {
+                    ret.unknown.push(Unknown {
+                        path: path.clone().key("with").key("script"),
+                        reason: "the API calls made by `actions/github-script` can't be inferred"
+                            .to_string(),
+                    });
+                }
+            }
+            let Some(run) = &step.run else {
+                continue;
+            };
+            for command in commands(run) {
+                let known = self.commands.iter().find(|(key, _)| {
+                    let words: Vec<_> = key.split_whitespace().collect();
+                    command.len() >= words.len() && command[..words.len()] == words[..]
+                });
+                match known {
+                    Some((key, perms)) => ret.require(*perms, &path, key.clone()),
+                    None if command.first() == Some(&"gh") && command.get(1) == Some(&"api") => {
+                        ret.unknown.push(Unknown {
+                            path: path.clone().key("run"),
+                            reason: "the scopes needed by `gh api` can't be inferred".to_string(),
+                        })
+                    }
+                    None => {}
+                }
+            }
+            let uses_token = step
+                .env
+                .values()
+                .chain(std::iter::once(run))
+                .any(|value| references_token(value));
+            if uses_token && run.contains("api.github.com") {
+                ret.unknown.push(Unknown {
+                    path: path.clone().key("run"),
+                    reason: "the script calls the GitHub API directly".to_string(),
+                });
+            }
+        }
+        ret
+    }
+}
+
+/// Split a script into commands, each as a list of words
+fn commands(script: &str) -> Vec<Vec<&str>> {
+    script
+        .split(['\n', ';', '|', '&', '(', ')', '`'])
+        .map(|command| {
+            command
+                .split_whitespace()
+                .skip_while(|word| word.contains('=') || *word == "sudo" || *word == "then")
+                .collect::<Vec<_>>()
+        })
+        .filter(|words| !words.is_empty())
+        .collect()
+}
+
+fn references_token(value: &str) -> bool {
+    expr::embedded(value).iter().any(|e| {
+        expr::references(e.expr)
+            .iter()
+            .any(|r| r.starts_with("github.token") || r.starts_with("secrets.GITHUB_TOKEN"))
+    })
+}
+
+/// Infer the permissions needed by every job in `workflow` using the bundled
+/// [`Database`]
+pub fn infer(workflow: &Workflow) -> Inference {
+    Database::bundled().infer(workflow)
+}
+
+/// The inferred permissions of each job in a workflow
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Inference {
+    pub jobs: IndexMap<String, JobPermissions>,
+}
+
+impl Inference {
+    /// The jobs whose declared permissions grant more than they need
+    pub fn over_privileged(&self) -> impl Iterator<Item = (&String, &JobPermissions)> {
+        self.jobs.iter().filter(|(_, job)| job.is_over_privileged())
+    }
+
+    /// Set the permissions of every job that could be fully inferred to
+    /// exactly what it needs, returns the names of the jobs that changed
+    pub fn apply(&self, workflow: &mut Workflow) -> Vec<String> {
+        let mut ret = Vec::new();
+        for (name, inferred) in &self.jobs {
+            let Some(job) = workflow.jobs.get_mut(name) else {
+                continue;
+            };
+            if !inferred.unknown.is_empty() || job.uses.is_some() {
+                continue;
+            }
+            let effective = job.permissions.or(workflow.permissions);
+            if effective != Some(inferred.required) {
+                job.permissions = Some(inferred.required);
+                ret.push(name.clone());
+            }
+        }
+        ret
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JobPermissions {
+    /// The least permissions the job needs
+    pub required: Permissions,
+    /// The permissions the job currently has, from the job or workflow,
+    /// `None` when they aren't restricted
+    pub declared: Option<Permissions>,
+    /// Why each scope is required
+    pub reasons: Vec<Reason>,
+    /// Steps whose needs couldn't be inferred
+    pub unknown: Vec<Unknown>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reason {
+    pub scope: &'static str,
+    pub value: PermissionValue,
+    #[serde(serialize_with = "serialize_path")]
+    pub path: YamlPath,
+    /// The action or command that needs the scope
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Unknown {
+    #[serde(serialize_with = "serialize_path")]
+    pub path: YamlPath,
+    pub reason: String,
+}
+
+fn serialize_path<S: serde::Serializer>(path: &YamlPath, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(path)
+}
+
+impl JobPermissions {
+    fn require(&mut self, perms: Permissions, path: &YamlPath, source: String) {
+        for (scope, value) in perms.scopes() {
+            if value.is_none() {
+                continue;
+            }
+            self.reasons.push(Reason {
+                scope,
+                value,
+                path: path.clone(),
+                source: source.clone(),
+            });
+        }
+        self.required = self.required.union(&perms);
+    }
+
+    /// The scopes granted beyond what is required, as (scope, granted,
+    /// required). Unrestricted permissions are reported as `write` for every
+    /// scope since that is the default for many repositories
+    pub fn excess(&self) -> Vec<(&'static str, PermissionValue, PermissionValue)> {
+        let declared = self
+            .declared
+            .unwrap_or_else(|| Permissions::all(PermissionValue::Write));
+        declared
+            .scopes()
+            .into_iter()
+            .zip(self.required.scopes())
+            .filter(|((_, granted), (_, required))| !required.covers(*granted))
+            .map(|((scope, granted), (_, required))| (scope, granted, required))
+            .collect()
+    }
+
+    pub fn is_over_privileged(&self) -> bool {
+        !self.excess().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"name: release
+on: push
+permissions: write-all
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - uses: actions/upload-artifact@v4
+        with:
+          name: dist
+          path: dist
+  release:
+    runs-on: ubuntu-latest
+    permissions:
+      contents: write
+    steps:
+      - uses: actions/download-artifact@v4
+      - run: gh release create "$TAG" dist/*
+      - uses: aws-actions/configure-aws-credentials@v4
+  custom:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: octo-org/mystery-action@v1
+"#;
+
+    #[test]
+    fn infer_permissions() {
+        let mut workflow: Workflow = serde_yml::from_str(WORKFLOW).unwrap();
+        let inference = infer(&workflow);
+        insta::assert_yaml_snapshot!(inference);
+        let over: Vec<_> = inference.over_privileged().map(|(name, _)| name).collect();
+        // `release` needs `id-token: write` which it is missing, not over
+        assert_eq!(over, ["build", "custom"]);
+        assert_eq!(inference.apply(&mut workflow), ["build", "release"]);
+        assert_eq!(
+            workflow.jobs["release"].permissions,
+            Some(
+                Permissions::builder()
+                    .contents(PermissionValue::Write)
+                    .id(PermissionValue::Write)
+                    .build()
+            )
+        );
+    }
+}
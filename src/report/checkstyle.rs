@@ -0,0 +1,59 @@
+use super::{xml_escape, Reporter};
+use crate::{
+    lint::{Diagnostic, Severity},
+    span::Source,
+};
+use std::io::{self, Write};
+
+/// A Checkstyle XML report, the `source` of each error is `grackle.<rule>`
+pub struct Checkstyle<W> {
+    out: W,
+    files: Vec<(String, Vec<String>)>,
+}
+
+impl<W: Write> Checkstyle<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Reporter for Checkstyle<W> {
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        let errors = diagnostics
+            .iter()
+            .map(|d| {
+                let start = source.position(d.span.start);
+                let severity = match d.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "info",
+                };
+                format!(
+                    r#"<error line="{}" column="{}" severity="{severity}" message="{}" source="grackle.{}"/>"#,
+                    start.line,
+                    start.column,
+                    xml_escape(&d.message),
+                    xml_escape(&d.rule)
+                )
+            })
+            .collect();
+        self.files.push((source.path().to_string(), errors));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(self.out, r#"<checkstyle version="4.3">"#)?;
+        for (path, errors) in &self.files {
+            writeln!(self.out, r#"  <file name="{}">"#, xml_escape(path))?;
+            for error in errors {
+                writeln!(self.out, "    {error}")?;
+            }
+            writeln!(self.out, "  </file>")?;
+        }
+        writeln!(self.out, "</checkstyle>")
+    }
+}
@@ -0,0 +1,61 @@
+use super::Reporter;
+use crate::{
+    lint::{Diagnostic, Severity},
+    span::Source,
+};
+use std::io::{self, Write};
+
+/// [Workflow commands] that GitHub turns into annotations on the lines of a
+/// pull request, e.g. `::error file=ci.yml,line=4,col=3::message`
+///
+/// [Workflow commands]: https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions
+pub struct GitHub<W> {
+    out: W,
+}
+
+impl<W: Write> GitHub<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+impl<W: Write> Reporter for GitHub<W> {
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        for diagnostic in diagnostics {
+            let command = match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::Info => "notice",
+            };
+            let start = source.position(diagnostic.span.start);
+            let end = source.position(diagnostic.span.end);
+            let mut message = diagnostic.message.clone();
+            for note in &diagnostic.notes {
+                message.push_str("\nnote: ");
+                message.push_str(&note.message);
+            }
+            writeln!(
+                self.out,
+                "::{command} file={},line={},col={},endLine={},endColumn={},title={}::{}",
+                escape_property(source.path()),
+                start.line,
+                start.column,
+                end.line,
+                end.column,
+                escape_property(&diagnostic.rule),
+                escape_data(&message),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
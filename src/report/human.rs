@@ -0,0 +1,198 @@
+use super::Reporter;
+use crate::{
+    lint::{Diagnostic, Severity},
+    span::{Source, Span},
+};
+use std::io::{self, Write};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+const GREEN: &str = "\x1b[1;32m";
+
+/// rustc style snippets of the source with the span of each diagnostic
+/// underlined
+///
+/// ```text
+/// error[missing-runs-on]: job `build` has no `runs-on`
+///  --> .github/workflows/ci.yml:4:3
+///   |
+/// 4 |   build:
+///   |   ^^^^^
+/// ```
+pub struct Human<W> {
+    out: W,
+    color: bool,
+    errors: usize,
+    warnings: usize,
+}
+
+impl<W: Write> Human<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            color: false,
+            errors: 0,
+            warnings: 0,
+        }
+    }
+
+    /// Use ANSI colors
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn diagnostic(&mut self, source: &Source, diagnostic: &Diagnostic) -> io::Result<()> {
+        let (label, style) = match diagnostic.severity {
+            Severity::Error => ("error", RED),
+            Severity::Warning => ("warning", YELLOW),
+            Severity::Info => ("info", CYAN),
+        };
+        let width = source.position(source.text().len()).line.to_string().len();
+        writeln!(
+            self.out,
+            "{}{}",
+            self.paint(style, &format!("{label}[{}]", diagnostic.rule)),
+            self.paint(BOLD, &format!(": {}", diagnostic.message)),
+        )?;
+        self.snippet(source, diagnostic.span, '^', style, width)?;
+        for note in &diagnostic.notes {
+            match note.span {
+                Some(span) => {
+                    writeln!(self.out, "{}: {}", self.paint(GREEN, "note"), note.message)?;
+                    self.snippet(source, span, '-', BLUE, width)?;
+                }
+                None => writeln!(
+                    self.out,
+                    "{:width$} {} {}: {}",
+                    "",
+                    self.paint(BLUE, "="),
+                    self.paint(BOLD, "note"),
+                    note.message
+                )?,
+            }
+        }
+        if let Some(fix) = &diagnostic.fix {
+            writeln!(
+                self.out,
+                "{:width$} {} {}: {} ({})",
+                "",
+                self.paint(BLUE, "="),
+                self.paint(BOLD, "help"),
+                fix.message,
+                fix.applicability
+            )?;
+        }
+        writeln!(self.out)
+    }
+
+    /// The lines of `span` with it underlined by `marker`, spans over more
+    /// than one line are marked to the end of each line
+    fn snippet(
+        &mut self,
+        source: &Source,
+        span: Span,
+        marker: char,
+        style: &str,
+        width: usize,
+    ) -> io::Result<()> {
+        let start = source.position(span.start);
+        let end = source.position(span.end);
+        let gutter = self.paint(BLUE, "|");
+        writeln!(
+            self.out,
+            "{:width$}{} {}:{}:{}",
+            "",
+            self.paint(BLUE, "-->"),
+            source.path(),
+            start.line,
+            start.column
+        )?;
+        writeln!(self.out, "{:width$} {gutter}", "")?;
+        let last = if end.line > start.line && end.column == 1 {
+            end.line - 1
+        } else {
+            end.line
+        };
+        for line in start.line..=last {
+            let text = source.line_text(line);
+            let from = if line == start.line {
+                start.column
+            } else {
+                // skip the indentation of continuation lines
+                text.chars().take_while(|c| c.is_whitespace()).count() + 1
+            };
+            let to = if line == end.line {
+                end.column
+            } else {
+                text.chars().count() + 1
+            };
+            let len = to.saturating_sub(from).max(1);
+            writeln!(
+                self.out,
+                "{} {gutter} {text}",
+                self.paint(BLUE, &format!("{line:>width$}"))
+            )?;
+            writeln!(
+                self.out,
+                "{:width$} {gutter} {:pad$}{}",
+                "",
+                "",
+                self.paint(style, &marker.to_string().repeat(len)),
+                pad = from - 1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Reporter for Human<W> {
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        for diagnostic in diagnostics {
+            match diagnostic.severity {
+                Severity::Error => self.errors += 1,
+                Severity::Warning => self.warnings += 1,
+                Severity::Info => {}
+            }
+            self.diagnostic(source, diagnostic)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if self.errors == 0 && self.warnings == 0 {
+            return Ok(());
+        }
+        let plural = |n: usize, word: &str| {
+            if n == 1 {
+                format!("{n} {word}")
+            } else {
+                format!("{n} {word}s")
+            }
+        };
+        writeln!(
+            self.out,
+            "{}",
+            self.paint(
+                BOLD,
+                &format!(
+                    "found {} and {}",
+                    plural(self.errors, "error"),
+                    plural(self.warnings, "warning")
+                )
+            )
+        )
+    }
+}
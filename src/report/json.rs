@@ -0,0 +1,44 @@
+use super::Reporter;
+use crate::{
+    lint::Diagnostic,
+    span::{Position, Source},
+};
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Newline delimited JSON, one object per diagnostic with the file it was
+/// found in and the positions of its span
+pub struct Json<W> {
+    out: W,
+}
+
+impl<W: Write> Json<W> {
+    pub fn new(out: W) -> Self {
+        Self { out }
+    }
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    path: &'a str,
+    start: Position,
+    end: Position,
+    #[serde(flatten)]
+    diagnostic: &'a Diagnostic,
+}
+
+impl<W: Write> Reporter for Json<W> {
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        for diagnostic in diagnostics {
+            let line = Line {
+                path: source.path(),
+                start: source.position(diagnostic.span.start),
+                end: source.position(diagnostic.span.end),
+                diagnostic,
+            };
+            serde_json::to_writer(&mut self.out, &line)?;
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+}
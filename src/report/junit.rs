@@ -0,0 +1,107 @@
+use super::{xml_escape, Reporter};
+use crate::{lint::Diagnostic, span::Source};
+use std::io::{self, Write};
+
+/// A JUnit XML report with a test suite per file and a failing test case per
+/// diagnostic, files without diagnostics get a single passing test case
+pub struct JUnit<W> {
+    out: W,
+    suites: Vec<Suite>,
+}
+
+struct Suite {
+    path: String,
+    cases: Vec<Case>,
+}
+
+struct Case {
+    name: String,
+    failure: Option<(String, String, String)>,
+}
+
+impl<W: Write> JUnit<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            suites: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Reporter for JUnit<W> {
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        let mut cases: Vec<_> = diagnostics
+            .iter()
+            .map(|d| {
+                let start = source.position(d.span.start);
+                let location = format!("{}:{}:{}", source.path(), start.line, start.column);
+                let mut details = format!("{}: {location}", d.severity);
+                for note in &d.notes {
+                    details.push_str("\nnote: ");
+                    details.push_str(&note.message);
+                }
+                Case {
+                    name: format!("{} {location}", d.rule),
+                    failure: Some((d.rule.clone(), d.message.clone(), details)),
+                }
+            })
+            .collect();
+        if cases.is_empty() {
+            cases.push(Case {
+                name: source.path().to_string(),
+                failure: None,
+            });
+        }
+        self.suites.push(Suite {
+            path: source.path().to_string(),
+            cases,
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let count = |suite: &Suite| suite.cases.len();
+        let failures = |suite: &Suite| suite.cases.iter().filter(|c| c.failure.is_some()).count();
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            self.out,
+            r#"<testsuites name="grackle" tests="{}" failures="{}">"#,
+            self.suites.iter().map(count).sum::<usize>(),
+            self.suites.iter().map(failures).sum::<usize>(),
+        )?;
+        for suite in &self.suites {
+            let path = xml_escape(&suite.path);
+            writeln!(
+                self.out,
+                r#"  <testsuite name="{path}" tests="{}" failures="{}">"#,
+                count(suite),
+                failures(suite)
+            )?;
+            for case in &suite.cases {
+                let name = xml_escape(&case.name);
+                match &case.failure {
+                    None => writeln!(
+                        self.out,
+                        r#"    <testcase name="{name}" classname="{path}"/>"#
+                    )?,
+                    Some((kind, message, details)) => {
+                        writeln!(
+                            self.out,
+                            r#"    <testcase name="{name}" classname="{path}">"#
+                        )?;
+                        writeln!(
+                            self.out,
+                            r#"      <failure type="{}" message="{}">{}</failure>"#,
+                            xml_escape(kind),
+                            xml_escape(message),
+                            xml_escape(details).replace("&#10;", "\n")
+                        )?;
+                        writeln!(self.out, "    </testcase>")?;
+                    }
+                }
+            }
+            writeln!(self.out, "  </testsuite>")?;
+        }
+        writeln!(self.out, "</testsuites>")
+    }
+}
@@ -0,0 +1,177 @@
+//! Render [`Diagnostic`]s for people and for other tools
+//!
+//! Each output format is a [`Reporter`] that is handed the diagnostics of one
+//! file at a time. Formats that describe every file in a single document
+//! (JUnit, Checkstyle and SARIF) buffer what they are given and write it all
+//! in [`Reporter::finish`].
+
+mod checkstyle;
+mod github;
+mod human;
+mod json;
+mod junit;
+
+pub use checkstyle::Checkstyle;
+pub use github::GitHub;
+pub use human::Human;
+pub use json::Json;
+pub use junit::JUnit;
+
+use crate::{
+    lint::{Diagnostic, Registry},
+    sarif,
+    span::Source,
+};
+use std::io::{self, Write};
+
+pub trait Reporter {
+    /// Report the `diagnostics` found in `source`, called for every file
+    /// checked including those without any diagnostics
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()>;
+
+    /// Called once after every file has been reported
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The available output formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum Format {
+    Human,
+    GitHub,
+    Json,
+    JUnit,
+    Checkstyle,
+    Sarif,
+}
+
+impl Format {
+    /// A reporter for this format writing to `out`, `color` only applies to
+    /// [`Format::Human`]
+    pub fn reporter<'a>(
+        self,
+        out: impl Write + 'a,
+        registry: &Registry,
+        color: bool,
+    ) -> Box<dyn Reporter + 'a> {
+        match self {
+            Format::Human => Box::new(Human::new(out).color(color)),
+            Format::GitHub => Box::new(GitHub::new(out)),
+            Format::Json => Box::new(Json::new(out)),
+            Format::JUnit => Box::new(JUnit::new(out)),
+            Format::Checkstyle => Box::new(Checkstyle::new(out)),
+            Format::Sarif => Box::new(Sarif::new(out, registry)),
+        }
+    }
+}
+
+/// Writes a [`sarif::Log`] of every reported file
+pub struct Sarif<W> {
+    out: W,
+    log: sarif::Log,
+}
+
+impl<W: Write> Sarif<W> {
+    pub fn new(out: W, registry: &Registry) -> Self {
+        Self {
+            out,
+            log: sarif::Log::new(registry),
+        }
+    }
+}
+
+impl<W: Write> Reporter for Sarif<W> {
+    fn report(&mut self, source: &Source, diagnostics: &[Diagnostic]) -> io::Result<()> {
+        self.log.add(source, diagnostics);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        writeln!(self.out, "{}", self.log.to_json())
+    }
+}
+
+/// Escape text for use in XML content and attribute values
+pub(crate) fn xml_escape(text: &str) -> String {
+    let mut ret = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            '\n' => ret.push_str("&#10;"),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{Config, Linter};
+
+    const WORKFLOW: &str = r#"name: ci
+on: push
+jobs:
+  build:
+    permissions: write-all
+    steps:
+      - uses: actions/checkout@v4
+      - run: echo "${{ github.event.head_commit.message }}"
+  test:
+    runs-on: ubuntu-latest
+    permissions: {}
+    steps:
+      - run: cargo test
+"#;
+
+    fn render(format: Format) -> String {
+        let linter = Linter::new(Registry::builtin(), Config::default());
+        let sources = [
+            Source::new(".github/workflows/ci.yml", WORKFLOW),
+            Source::new(
+                ".github/workflows/empty.yml",
+                "name: empty\non: push\njobs: {}\n",
+            ),
+        ];
+        let mut out = Vec::new();
+        let mut reporter = format.reporter(&mut out, linter.registry(), false);
+        for source in &sources {
+            let diagnostics = linter.lint(source).unwrap();
+            reporter.report(source, &diagnostics).unwrap();
+        }
+        reporter.finish().unwrap();
+        drop(reporter);
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn human() {
+        insta::assert_snapshot!(render(Format::Human));
+    }
+
+    #[test]
+    fn github() {
+        insta::assert_snapshot!(render(Format::GitHub));
+    }
+
+    #[test]
+    fn json() {
+        insta::assert_snapshot!(render(Format::Json));
+    }
+
+    #[test]
+    fn junit() {
+        insta::assert_snapshot!(render(Format::JUnit));
+    }
+
+    #[test]
+    fn checkstyle() {
+        insta::assert_snapshot!(render(Format::Checkstyle));
+    }
+}
@@ -0,0 +1,407 @@
+//! Typed handles to reusable workflows for callers generated in the same
+//! program
+//!
+//! ```
+//! use grackle::{Input, InputType, Job, Output, WorkflowCall};
+//!
+//! let deploy = WorkflowCall::builder()
+//!     .input("environment", Input::builder().required(true).kind(InputType::String).build())
+//!     .output("url", Output::builder().value("${{ jobs.deploy.outputs.url }}").build())
+//!     .build()
+//!     .handle("./.github/workflows/deploy.yml");
+//! let job = deploy.job().input("environment", "production").build()?;
+//! assert!(deploy.validate(&job).is_empty());
+//! assert_eq!(
+//!     deploy.output("production", "url").unwrap().to_string(),
+//!     "${{ needs.production.outputs.url }}",
+//! );
+//! # Ok::<(), Vec<grackle::reusable::CallError>>(())
+//! ```
+
+use crate::{InputType, Job, JobSecrets, Permissions, Strategy, Workflow, WorkflowCall};
+use serde_yml::Value;
+use std::{collections::BTreeMap, fmt};
+
+/// A reusable workflow and the contract its `on.workflow_call` declares
+#[derive(Debug, Clone)]
+pub struct ReusableWorkflow {
+    uses: String,
+    call: WorkflowCall,
+}
+
+impl ReusableWorkflow {
+    /// `uses` is how callers refer to the workflow, e.g.
+    /// `./.github/workflows/deploy.yml`
+    pub fn new(uses: impl ToString, call: WorkflowCall) -> Self {
+        Self {
+            uses: uses.to_string(),
+            call,
+        }
+    }
+
+    /// The handle of `workflow`, `None` if it can't be called
+    pub fn from_workflow(uses: impl ToString, workflow: &Workflow) -> Option<Self> {
+        let call = workflow.on.workflow_call.as_ref()?;
+        Some(Self::new(uses, call.clone()))
+    }
+
+    pub fn uses(&self) -> &str {
+        &self.uses
+    }
+
+    pub fn contract(&self) -> &WorkflowCall {
+        &self.call
+    }
+
+    /// Start a job that calls this workflow, checked against its contract
+    /// when built
+    pub fn job(&self) -> CallBuilder {
+        CallBuilder {
+            workflow: self.clone(),
+            job: Job {
+                uses: Some(self.uses.clone().into()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// The output `name` of the workflow called by the job `job`, for the
+    /// jobs that need it
+    pub fn output(&self, job: impl ToString, name: &str) -> Result<JobOutput, CallError> {
+        if find(&self.call.outputs, name).is_none() {
+            return Err(CallError::UnknownOutput(self.uses.clone(), name.into()));
+        }
+        Ok(JobOutput::new(job, name))
+    }
+
+    /// If the workflow declares the input `name` and `value` can be passed
+    /// to it, names are case insensitive
+    pub fn check_input(&self, name: &str, value: &Value) -> Result<(), CallError> {
+        let Some(input) = find(&self.call.inputs, name) else {
+            return Err(CallError::UnknownInput(self.uses.clone(), name.into()));
+        };
+        match &input.kind {
+            Some(kind) if !kind.accepts(value) => Err(CallError::InputType(
+                self.uses.clone(),
+                name.into(),
+                kind.clone(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// If the workflow declares the secret `name`, names are case insensitive
+    pub fn check_secret(&self, name: &str) -> Result<(), CallError> {
+        if find(&self.call.secrets, name).is_some() {
+            Ok(())
+        } else {
+            Err(CallError::UnknownSecret(self.uses.clone(), name.into()))
+        }
+    }
+
+    /// Every way `job` breaks the contract, including required inputs and
+    /// secrets it doesn't pass
+    pub fn validate(&self, job: &Job) -> Vec<CallError> {
+        let mut errors = Vec::new();
+        for (name, value) in &job.with {
+            if let Err(e) = self.check_input(name, value) {
+                errors.push(e);
+            }
+        }
+        for (name, input) in &self.call.inputs {
+            if input.required && input.default.is_none() && find(&job.with, name).is_none() {
+                errors.push(CallError::MissingInput(self.uses.clone(), name.clone()));
+            }
+        }
+        if let JobSecrets::Map(secrets) = &job.secrets {
+            for name in secrets.keys() {
+                if let Err(e) = self.check_secret(name) {
+                    errors.push(e);
+                }
+            }
+            for (name, secret) in &self.call.secrets {
+                if secret.required && find(secrets, name).is_none() {
+                    errors.push(CallError::MissingSecret(self.uses.clone(), name.clone()));
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// The entry of `map` named `name`, ignoring case like GitHub does
+fn find<'a, V>(map: &'a BTreeMap<String, V>, name: &str) -> Option<&'a V> {
+    map.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value)
+}
+
+/// Builds a [`Job`] calling a [`ReusableWorkflow`], see
+/// [`ReusableWorkflow::job`]
+#[derive(Debug, Clone)]
+pub struct CallBuilder {
+    workflow: ReusableWorkflow,
+    job: Job,
+}
+
+impl CallBuilder {
+    /// Pass an input to the workflow
+    pub fn input(mut self, key: impl ToString, value: impl Into<Value>) -> Self {
+        self.job.with.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Pass a secret to the workflow
+    pub fn secret(mut self, key: impl ToString, value: impl ToString) -> Self {
+        self.job.secrets.insert(key, value);
+        self
+    }
+
+    pub fn inherit_secrets(mut self) -> Self {
+        self.job.secrets = JobSecrets::Inherit;
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<Value>) -> Self {
+        self.job.name = Some(name.into());
+        self
+    }
+
+    pub fn needs(mut self, job: impl ToString) -> Self {
+        self.job.needs.push(job.to_string());
+        self
+    }
+
+    pub fn if_cond(mut self, cond: impl ToString) -> Self {
+        self.job.if_cond = Some(cond.to_string());
+        self
+    }
+
+    pub fn permissions(mut self, permissions: Permissions) -> Self {
+        self.job.permissions = Some(permissions);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.job.strategy = strategy;
+        self
+    }
+
+    /// The job, or every way it breaks the workflow's contract
+    pub fn build(self) -> Result<Job, Vec<CallError>> {
+        let errors = self.workflow.validate(&self.job);
+        if errors.is_empty() {
+            Ok(self.job)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl Workflow {
+    /// Check every job calling one of `workflows` against its contract
+    pub fn validate_calls(&self, workflows: &[ReusableWorkflow]) -> Vec<(String, CallError)> {
+        self.jobs
+            .iter()
+            .filter_map(|(name, job)| {
+                let uses = job.uses.as_ref()?.as_str()?;
+                Some((name, job, workflows.iter().find(|w| w.uses == uses)?))
+            })
+            .flat_map(|(name, job, workflow)| {
+                workflow
+                    .validate(job)
+                    .into_iter()
+                    .map(move |e| (name.clone(), e))
+            })
+            .collect()
+    }
+}
+
+/// A way a call breaks the contract of a reusable workflow, each holding
+/// the workflow's `uses` and the name of the input, secret or output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    UnknownInput(String, String),
+    /// The input's declared type is the last field
+    InputType(String, String, InputType),
+    MissingInput(String, String),
+    UnknownSecret(String, String),
+    MissingSecret(String, String),
+    UnknownOutput(String, String),
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::UnknownInput(uses, name) => write!(f, "`{uses}` has no input `{name}`"),
+            CallError::InputType(uses, name, kind) => {
+                write!(f, "the `{name}` input of `{uses}` is a {kind}")
+            }
+            CallError::MissingInput(uses, name) => {
+                write!(f, "`{uses}` requires the input `{name}`")
+            }
+            CallError::UnknownSecret(uses, name) => write!(f, "`{uses}` has no secret `{name}`"),
+            CallError::MissingSecret(uses, name) => {
+                write!(f, "`{uses}` requires the secret `{name}`")
+            }
+            CallError::UnknownOutput(uses, name) => write!(f, "`{uses}` has no output `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+/// A reference to an output of a job, from a job that needs it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobOutput {
+    pub job: String,
+    pub name: String,
+}
+
+impl JobOutput {
+    pub fn new(job: impl ToString, name: impl ToString) -> Self {
+        Self {
+            job: job.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    /// The reference without the `${{ }}` delimiters, to be used as part of a
+    /// larger expression
+    pub fn expr(&self) -> String {
+        format!("needs.{}.outputs.{}", self.job, self.name)
+    }
+}
+
+impl fmt::Display for JobOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "${{{{ {} }}}}", self.expr())
+    }
+}
+
+impl From<JobOutput> for String {
+    fn from(output: JobOutput) -> Self {
+        output.to_string()
+    }
+}
+
+impl From<JobOutput> for Value {
+    fn from(output: JobOutput) -> Self {
+        Value::String(output.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Input, Output, Secret};
+
+    fn deploy() -> ReusableWorkflow {
+        WorkflowCall::builder()
+            .input(
+                "environment",
+                Input::builder()
+                    .required(true)
+                    .kind(InputType::String)
+                    .build(),
+            )
+            .input("dry-run", Input::builder().kind(InputType::Boolean).build())
+            .secret("token", Secret::builder().required(true).build())
+            .output(
+                "url",
+                Output::builder()
+                    .value("${{ jobs.deploy.outputs.url }}")
+                    .build(),
+            )
+            .build()
+            .handle("./.github/workflows/deploy.yml")
+    }
+
+    #[test]
+    fn calls() {
+        let deploy = deploy();
+        let staging = deploy
+            .job()
+            .input("dry-run", true)
+            .secret("token", "${{ secrets.STAGING_TOKEN }}")
+            .build();
+        assert_eq!(
+            staging.unwrap_err(),
+            [CallError::MissingInput(
+                deploy.uses().into(),
+                "environment".into()
+            )]
+        );
+        let workflow = Workflow::builder()
+            .name("release")
+            .add_job(
+                "staging",
+                Job::builder()
+                    .uses(deploy.uses())
+                    .with("dry-run", true)
+                    .secret("token", "${{ secrets.STAGING_TOKEN }}")
+                    .build(),
+            )
+            .add_job(
+                "production",
+                deploy
+                    .job()
+                    .input("environment", "production")
+                    .inherit_secrets()
+                    .build()
+                    .unwrap(),
+            )
+            .add_job(
+                "notify",
+                Job::builder()
+                    .needs("production")
+                    .runs_on("ubuntu-latest")
+                    .env_var("URL", deploy.output("production", "url").unwrap())
+                    .build(),
+            )
+            .build();
+        assert_eq!(
+            workflow.validate_calls(std::slice::from_ref(&deploy)),
+            [(
+                "staging".to_string(),
+                CallError::MissingInput(deploy.uses().into(), "environment".into())
+            )]
+        );
+        insta::assert_yaml_snapshot!(workflow);
+    }
+
+    #[test]
+    fn contract_errors() {
+        let deploy = deploy();
+        // names are case insensitive
+        assert!(deploy
+            .job()
+            .input("Environment", "production")
+            .secret("TOKEN", "${{ secrets.TOKEN }}")
+            .build()
+            .is_ok());
+        assert_eq!(
+            deploy.output("deploy", "link"),
+            Err(CallError::UnknownOutput(
+                deploy.uses().into(),
+                "link".into()
+            ))
+        );
+        // the order inputs and secrets are passed in doesn't matter
+        let errors = deploy
+            .job()
+            .secret("tokn", "${{ secrets.TOKEN }}")
+            .input("dry-run", "yes")
+            .input("environment", "production")
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            [
+                "the `dry-run` input of `./.github/workflows/deploy.yml` is a boolean",
+                "`./.github/workflows/deploy.yml` has no secret `tokn`",
+                "`./.github/workflows/deploy.yml` requires the secret `token`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Run the `uses` steps of a job: local composite actions and stubs
+//! registered for remote actions
+
+use super::{store, Conclusion, FileCommands, JobState, Runner, Script, StepDefaults, StepRun};
+use crate::{
+    action::{Action, Runs},
+    condition,
+    expr::Expr,
+    uses::Uses,
+    JobStep,
+};
+use serde_yml::Value;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::Path,
+    sync::{atomic::Ordering, Arc},
+};
+
+/// How deep composite actions may use other composite actions
+pub const MAX_DEPTH: usize = 10;
+
+/// What a [`Stub`] closure gets from the step that uses it
+#[derive(Debug)]
+pub struct StubContext<'a> {
+    /// The step's `with`, evaluated
+    pub inputs: &'a BTreeMap<String, String>,
+    pub workspace: &'a Path,
+    /// The environment variables of the step
+    pub env: &'a BTreeMap<String, String>,
+}
+
+/// A closure standing in for an action, returning its outputs
+pub type StubFn =
+    dyn Fn(&StubContext<'_>) -> Result<BTreeMap<String, String>, String> + Send + Sync;
+
+/// What runs in place of an action that can't run locally
+#[derive(Clone)]
+pub enum Stub {
+    Fn(Arc<StubFn>),
+    /// A script run like a `run` step, with the inputs in the `inputs`
+    /// context and `$INPUT_<NAME>`
+    Script(String),
+}
+
+impl fmt::Debug for Stub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stub::Fn(_) => f.write_str("Stub::Fn"),
+            Stub::Script(script) => f.debug_tuple("Stub::Script").field(script).finish(),
+        }
+    }
+}
+
+impl Runner {
+    /// Run the action `uses` of `step`, a stub registered for it or a local
+    /// composite action
+    pub(super) fn action(
+        &self,
+        uses: &str,
+        step: &JobStep,
+        defaults: &StepDefaults<'_>,
+        state: &JobState,
+        run: &mut StepRun,
+    ) -> Result<(FileCommands, Conclusion), String> {
+        let parsed: Uses = uses.parse().map_err(|e| format!("{e}"))?;
+        if let Some(stub) = self.stub_for(uses, &parsed) {
+            return self.run_stub(stub, step, defaults, state, run);
+        }
+        match parsed {
+            Uses::Local(path) => self.composite(&path, step, defaults, state, run),
+            Uses::Docker(_) => self.unstubbed(
+                format!("docker actions don't run locally, stub `{uses}`"),
+                run,
+            ),
+            Uses::Repository(action) => {
+                let inputs = inputs(step, None, &state.contexts)?;
+                if let Some(ran) = store::builtin(&action, &inputs, defaults, run) {
+                    return ran.map(|commands| (commands, Conclusion::Success));
+                }
+                self.unstubbed(format!("no stub for `{uses}`"), run)
+            }
+        }
+    }
+
+    /// Fail the step of an action that can't run, or skip it when the runner
+    /// skips unstubbed actions
+    fn unstubbed(
+        &self,
+        message: String,
+        run: &mut StepRun,
+    ) -> Result<(FileCommands, Conclusion), String> {
+        if !self.skip_unstubbed {
+            return Err(message);
+        }
+        run.message = Some(message);
+        Ok((FileCommands::default(), Conclusion::Skipped))
+    }
+
+    /// The stub for `uses` itself, or for any ref of the action
+    fn stub_for(&self, uses: &str, parsed: &Uses) -> Option<&Stub> {
+        self.stubs.get(uses.trim()).or_else(|| match parsed {
+            Uses::Repository(r) => match &r.path {
+                Some(path) => self.stubs.get(&format!("{}/{path}", r.repository())),
+                None => self.stubs.get(&r.repository()),
+            },
+            _ => None,
+        })
+    }
+
+    fn run_stub(
+        &self,
+        stub: &Stub,
+        step: &JobStep,
+        defaults: &StepDefaults<'_>,
+        state: &JobState,
+        run: &mut StepRun,
+    ) -> Result<(FileCommands, Conclusion), String> {
+        let inputs = inputs(step, None, &state.contexts)?;
+        match stub {
+            Stub::Fn(stub) => {
+                let context = StubContext {
+                    inputs: &inputs,
+                    workspace: defaults.workspace,
+                    env: &state.env,
+                };
+                let outputs = stub(&context)?;
+                let commands = FileCommands {
+                    outputs,
+                    ..Default::default()
+                };
+                Ok((commands, Conclusion::Success))
+            }
+            Stub::Script(script) => {
+                let mut contexts = state.contexts.clone();
+                let mut env = state.env.clone();
+                for (name, value) in &inputs {
+                    contexts.insert(
+                        format!("inputs.{}", name.to_ascii_lowercase()),
+                        Expr::String(value.clone()),
+                    );
+                    let name = name.replace(' ', "_").to_ascii_uppercase();
+                    env.insert(format!("INPUT_{name}"), value.clone());
+                }
+                // stubs run in the workspace whatever the `defaults` say
+                let defaults = StepDefaults {
+                    working_directory: None,
+                    ..*defaults
+                };
+                let script = Script {
+                    script,
+                    step,
+                    defaults: &defaults,
+                    contexts: &contexts,
+                    env: &env,
+                    path: &state.path,
+                    deadline: state.deadline,
+                    cancelled: &state.cancelled,
+                };
+                self.script(&script, run)
+            }
+        }
+    }
+
+    /// Run the steps of the composite action at `path` in the workspace,
+    /// with their own `inputs` and `steps` contexts
+    fn composite(
+        &self,
+        path: &str,
+        step: &JobStep,
+        defaults: &StepDefaults<'_>,
+        state: &JobState,
+        run: &mut StepRun,
+    ) -> Result<(FileCommands, Conclusion), String> {
+        if defaults.depth >= MAX_DEPTH {
+            return Err(format!("composite actions nest more than {MAX_DEPTH} deep"));
+        }
+        let dir = defaults.workspace.join(path);
+        let action = Action::load_dir(&dir).map_err(|e| format!("`{path}`: {e}"))?;
+        let Runs::Composite(runs) = &action.runs else {
+            return self.unstubbed(
+                format!("only composite actions run locally, stub `{path}`"),
+                run,
+            );
+        };
+        if let Some(idx) = runs
+            .steps
+            .iter()
+            .position(|s| s.run.is_some() && s.shell.is_none())
+        {
+            return Err(format!(
+                "`{path}`: step {} runs a script without a `shell`",
+                idx + 1
+            ));
+        }
+
+        let mut contexts: BTreeMap<_, _> = state
+            .contexts
+            .iter()
+            .filter(|(path, _)| !path.starts_with("steps.") && !path.starts_with("inputs."))
+            .map(|(path, value)| (path.clone(), value.clone()))
+            .collect();
+        for (name, value) in inputs(step, Some(&action), &state.contexts)? {
+            contexts.insert(
+                format!("inputs.{}", name.to_ascii_lowercase()),
+                Expr::String(value),
+            );
+        }
+        let dir = dir.display().to_string();
+        contexts.insert("github.action_path".into(), Expr::String(dir.clone()));
+        let mut env = state.env.clone();
+        env.insert("GITHUB_ACTION_PATH".into(), dir);
+        let mut inner = JobState {
+            contexts,
+            env: env.clone(),
+            path: state.path.clone(),
+            summary: String::new(),
+            failed: false,
+            deadline: state.deadline,
+            cancelled: state.cancelled.clone(),
+            saves: Vec::new(),
+        };
+        for (idx, composite_step) in runs.steps.iter().enumerate() {
+            let defaults = StepDefaults {
+                shell: None,
+                working_directory: None,
+                idx,
+                depth: defaults.depth + 1,
+                ..*defaults
+            };
+            let ran = self
+                .step(composite_step, &defaults, &mut inner)
+                .map_err(|e| e.to_string())?;
+            run.stdout.push_str(&ran.stdout);
+            run.stderr.push_str(&ran.stderr);
+            if ran.conclusion == Conclusion::Failure && run.message.is_none() {
+                run.message = Some(format!("`{}` failed", ran.name));
+            }
+            run.steps.push(ran);
+        }
+
+        let mut outputs = BTreeMap::new();
+        for (name, output) in &action.outputs {
+            if let Some(value) = &output.value {
+                let value = condition::interpolate(value, &inner.contexts)
+                    .map_err(|e| format!("output `{name}`: {e}"))?;
+                outputs.insert(name.clone(), value);
+            }
+        }
+        let added = inner.path.len() - state.path.len();
+        let commands = FileCommands {
+            outputs,
+            // what the steps wrote to `$GITHUB_ENV`
+            env: inner
+                .env
+                .into_iter()
+                .filter(|(name, value)| env.get(name) != Some(value))
+                .collect(),
+            path: inner.path[..added].to_vec(),
+            summary: inner.summary,
+            saves: inner.saves,
+        };
+        let conclusion = if state.cancelled.load(Ordering::Relaxed) {
+            Conclusion::Cancelled
+        } else if inner.failed {
+            Conclusion::Failure
+        } else {
+            Conclusion::Success
+        };
+        Ok((commands, conclusion))
+    }
+}
+
+/// The evaluated `with` of `step`, and the defaults of the inputs of
+/// `action` it doesn't set
+fn inputs(
+    step: &JobStep,
+    action: Option<&Action>,
+    contexts: &BTreeMap<String, Expr>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut inputs = BTreeMap::new();
+    for (name, value) in &step.with {
+        let value = string(value, contexts).map_err(|e| format!("input `{name}`: {e}"))?;
+        inputs.insert(name.clone(), value);
+    }
+    for (name, input) in action.iter().flat_map(|a| &a.inputs) {
+        if inputs.keys().any(|set| set.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+        let value = match &input.default {
+            Some(value) => string(value, contexts).map_err(|e| format!("input `{name}`: {e}"))?,
+            None => String::new(),
+        };
+        inputs.insert(name.clone(), value);
+    }
+    Ok(inputs)
+}
+
+/// A `with` value or input default as the string actions get
+fn string(value: &Value, contexts: &BTreeMap<String, Expr>) -> Result<String, String> {
+    Ok(match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => condition::interpolate(s, contexts)?,
+        other => serde_yml::to_string(other).map_err(|e| e.to_string())?,
+    })
+}
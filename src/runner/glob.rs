@@ -0,0 +1,196 @@
+//! Globs with the semantics of `@actions/glob`, which the artifact actions
+//! match their `path` and `pattern` inputs with
+//!
+//! `*` matches any characters but `/`, `**` as a whole path segment matches
+//! any number of directories, `?` matches one character but `/` and `[...]`
+//! one character of a class, negated with `[!...]` or `[^...]`. A `\`
+//! escapes the next character. Unlike [`FilterPattern`](crate::filter::FilterPattern)s
+//! `?` and `+` don't repeat the previous character.
+
+/// A compiled glob, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Glob(Vec<Segment>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `**`
+    Directories,
+    Pattern(Vec<Token>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    /// `?`
+    Any,
+    /// `*`
+    Star,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| match segment {
+                "**" => Segment::Directories,
+                segment => Segment::Pattern(tokens(segment)),
+            })
+            .collect();
+        Self(segments)
+    }
+
+    /// Whether any character of `s` is special in a glob
+    pub fn is_glob(s: &str) -> bool {
+        s.contains(['*', '?', '['])
+    }
+
+    /// Whether the `/` separated `path`, or one of its parent directories,
+    /// matches
+    pub fn matches(&self, path: &str) -> bool {
+        let components: Vec<_> = path.split('/').filter(|c| !c.is_empty()).collect();
+        segments(&self.0, &components)
+    }
+}
+
+fn tokens(segment: &str) -> Vec<Token> {
+    let mut ret = Vec::new();
+    let mut chars = segment.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '\\' => Token::Char(chars.next().unwrap_or('\\')),
+            '?' => Token::Any,
+            '*' => {
+                while chars.next_if_eq(&'*').is_some() {}
+                Token::Star
+            }
+            '[' => match class(&chars.clone().collect::<String>()) {
+                Some((token, len)) => {
+                    for _ in 0..len {
+                        chars.next();
+                    }
+                    token
+                }
+                None => Token::Char('['),
+            },
+            c => Token::Char(c),
+        };
+        ret.push(token);
+    }
+    ret
+}
+
+/// The class at the start of `rest`, after its `[`, and how many characters
+/// it spans with the `]`, `None` if it isn't closed
+fn class(rest: &str) -> Option<(Token, usize)> {
+    let mut chars = rest.char_indices().peekable();
+    let negated = chars.next_if(|(_, c)| matches!(c, '!' | '^')).is_some();
+    let mut ranges = Vec::new();
+    let mut first = true;
+    while let Some((idx, c)) = chars.next() {
+        if c == ']' && !first {
+            let len = rest[..idx].chars().count() + 1;
+            return Some((Token::Class { negated, ranges }, len));
+        }
+        first = false;
+        let start = match c {
+            '\\' => chars.next()?.1,
+            c => c,
+        };
+        let end = match chars.peek() {
+            Some(&(dash, '-')) if !rest[dash + 1..].starts_with(']') => {
+                chars.next();
+                match chars.next()? {
+                    (_, '\\') => chars.next()?.1,
+                    (_, end) => end,
+                }
+            }
+            _ => start,
+        };
+        ranges.push((start, end));
+    }
+    None
+}
+
+fn segments(glob: &[Segment], components: &[&str]) -> bool {
+    match glob.split_first() {
+        // the parent directory of `components` matched
+        None => true,
+        Some((Segment::Directories, rest)) => {
+            segments(rest, components)
+                || (!components.is_empty() && segments(glob, &components[1..]))
+        }
+        Some((Segment::Pattern(tokens), rest)) => match components.split_first() {
+            Some((component, components)) => {
+                let chars: Vec<_> = component.chars().collect();
+                matches(tokens, &chars) && segments(rest, components)
+            }
+            None => false,
+        },
+    }
+}
+
+fn matches(tokens: &[Token], chars: &[char]) -> bool {
+    match tokens.split_first() {
+        None => chars.is_empty(),
+        Some((Token::Star, rest)) => (0..=chars.len()).any(|idx| matches(rest, &chars[idx..])),
+        Some((token, rest)) => match chars.split_first() {
+            Some((c, chars)) => {
+                let matched = match token {
+                    Token::Char(expected) => c == expected,
+                    Token::Any => true,
+                    Token::Class { negated, ranges } => {
+                        ranges.iter().any(|(start, end)| (start..=end).contains(&c)) != *negated
+                    }
+                    Token::Star => unreachable!("handled above"),
+                };
+                matched && matches(rest, chars)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_glob() {
+        let cases = [
+            ("dist/app-?.tar", "dist/app-1.tar", true),
+            ("dist/app-?.tar", "dist/app-.tar", false),
+            ("dist/app-?.tar", "dist/app-12.tar", false),
+            ("dist/a+.tar", "dist/a+.tar", true),
+            ("dist/a+.tar", "dist/aa.tar", false),
+            ("dist/*.tar", "dist/app.tar", true),
+            ("dist/*.tar", "dist/linux/app.tar", false),
+            ("dist/**/*.tar", "dist/app.tar", true),
+            ("dist/**/*.tar", "dist/linux/x64/app.tar", true),
+            ("**/*.pdb", "dist/linux/app.pdb", true),
+            ("dist/[0-9].log", "dist/7.log", true),
+            ("dist/[!0-9].log", "dist/7.log", false),
+            ("dist/[^0-9].log", "dist/a.log", true),
+            ("dist/[]].log", "dist/].log", true),
+            ("dist/[a-].log", "dist/-.log", true),
+            ("dist/[a", "dist/[a", true),
+            (r"dist/\*.log", "dist/*.log", true),
+            (r"dist/\*.log", "dist/a.log", false),
+            // the files below a matching directory
+            ("dist/linux", "dist/linux/app", true),
+            ("dist/l*", "dist/linux/x64/app", true),
+            ("dist/linux", "dist/linux-x64/app", false),
+        ];
+        for (glob, path, expected) in cases {
+            assert_eq!(
+                Glob::new(glob).matches(path),
+                expected,
+                "`{glob}` matching `{path}`"
+            );
+        }
+    }
+}
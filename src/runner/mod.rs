@@ -0,0 +1,1217 @@
+//! Run workflows and the `run` steps of their jobs on the local machine
+//!
+//! Steps run in a workspace directory, a new temporary one by default, with
+//! the shell and working directory resolved from the step, the job's and the
+//! workflow's `defaults`. Outputs, environment variables, `PATH` entries and
+//! summaries written to the `$GITHUB_*` files flow into the following steps.
+//!
+//! Steps that use local composite actions run their steps, other actions
+//! only run when a [`Stub`] is registered for them. The artifact and cache
+//! actions are emulated with a local store unless they're stubbed. The steps
+//! of any other action fail, or are skipped with `skip_unstubbed`.
+//!
+//! ```
+//! use grackle::{event::Event, runner::Runner, Workflow};
+//!
+//! let workflow: Workflow = serde_yml::from_str(r#"
+//! name: ci
+//! on: push
+//! jobs:
+//!   test:
+//!     runs-on: ubuntu-latest
+//!     steps:
+//!       - id: version
+//!         run: echo "version=1.0.0" >> "$GITHUB_OUTPUT"
+//!       - run: echo ${{ steps.version.outputs.version }}
+//! "#).unwrap();
+//! let runner = Runner::builder().event(Event::builder().name("push").build()).build();
+//! let run = runner.run_job(&workflow, "test").unwrap();
+//! assert_eq!(run.steps[1].stdout, "1.0.0\n");
+//! ```
+
+use crate::{
+    condition::{self, Evaluator},
+    event::Event,
+    expr::Expr,
+    Job, JobStep, Workflow,
+};
+use serde::Serialize;
+use serde_yml::Value;
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    io::{self, Read},
+    os::unix::process::CommandExt,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use typed_builder::TypedBuilder;
+
+mod action;
+mod glob;
+mod schedule;
+mod store;
+
+pub use action::{Stub, StubContext, StubFn, MAX_DEPTH};
+pub use schedule::WorkflowRun;
+pub use store::{Artifact, DEFAULT_RETENTION_DAYS};
+use store::{CacheSave, Store};
+
+/// How long a job may run when it doesn't set `timeout-minutes`
+pub const DEFAULT_TIMEOUT_MINUTES: f64 = 360.0;
+
+/// Runs jobs for an event
+#[derive(Debug, Clone, TypedBuilder)]
+#[builder(mutators(
+    /// A value of the `inputs` context
+    pub fn input(&mut self, name: impl ToString, value: Expr) {
+        self.contexts.insert(format!("inputs.{}", name.to_string().to_ascii_lowercase()), value);
+    }
+    /// A value of the `vars` context
+    pub fn var(&mut self, name: impl ToString, value: impl ToString) {
+        let name = format!("vars.{}", name.to_string().to_ascii_lowercase());
+        self.contexts.insert(name, Expr::String(value.to_string()));
+    }
+    /// A value of the `secrets` context
+    pub fn secret(&mut self, name: impl ToString, value: impl ToString) {
+        let name = format!("secrets.{}", name.to_string().to_ascii_lowercase());
+        self.contexts.insert(name, Expr::String(value.to_string()));
+    }
+    /// An environment variable of the runner, like the ones the runner
+    /// machine sets rather than the workflow
+    pub fn env(&mut self, name: impl ToString, value: impl ToString) {
+        self.env.insert(name.to_string(), value.to_string());
+    }
+    /// Stand in for the action `owner/repo@ref`, every ref of `owner/repo`
+    /// or the local action `./path` with a closure returning its outputs
+    pub fn stub(
+        &mut self,
+        action: impl ToString,
+        stub: impl Fn(&StubContext<'_>) -> Result<BTreeMap<String, String>, String>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.stubs.insert(action.to_string(), Stub::Fn(Arc::new(stub)));
+    }
+    /// Stand in for an action with a script run like a `run` step
+    pub fn stub_script(&mut self, action: impl ToString, script: impl ToString) {
+        self.stubs.insert(action.to_string(), Stub::Script(script.to_string()));
+    }
+))]
+pub struct Runner {
+    event: Event,
+    /// The directory steps run in, a new temporary directory removed after
+    /// the job by default
+    #[builder(default, setter(into, strip_option))]
+    workspace: Option<PathBuf>,
+    #[builder(via_mutators(init = BTreeMap::new()))]
+    contexts: BTreeMap<String, Expr>,
+    #[builder(via_mutators(init = BTreeMap::new()))]
+    env: BTreeMap<String, String>,
+    /// How many job instances [`Runner::run_workflow`] runs at once, the
+    /// number of CPUs by default
+    #[builder(default, setter(into))]
+    parallel: Option<usize>,
+    /// The directory artifacts and caches are kept in, a new temporary
+    /// directory for every run, removed after it, by default
+    #[builder(default, setter(into, strip_option))]
+    store: Option<PathBuf>,
+    #[builder(via_mutators(init = BTreeMap::new()))]
+    stubs: BTreeMap<String, Stub>,
+    /// Skip the steps of actions without a stub or an emulation rather than
+    /// failing them
+    #[builder(default)]
+    skip_unstubbed: bool,
+}
+
+/// The result of a job or step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Conclusion {
+    Success,
+    Failure,
+    Skipped,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobRun {
+    /// The job's id
+    pub job: String,
+    /// The id and matrix values, e.g. `test (linux, 18)`
+    pub name: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub matrix: BTreeMap<String, Value>,
+    /// The evaluated `concurrency.group`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<String>,
+    pub result: Conclusion,
+    /// The evaluated `continue-on-error`, a failure of the instance doesn't
+    /// fail the job or cancel the other instances
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub continue_on_error: bool,
+    #[serde(serialize_with = "seconds")]
+    pub duration: Duration,
+    pub steps: Vec<StepRun>,
+    /// The job's `outputs`, evaluated after its steps
+    pub outputs: BTreeMap<String, String>,
+    /// What the steps wrote to `$GITHUB_STEP_SUMMARY`
+    pub summary: String,
+    /// Why the job failed when it isn't a step's fault
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepRun {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The result before `continue-on-error` is applied
+    pub outcome: Conclusion,
+    pub conclusion: Conclusion,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub outputs: BTreeMap<String, String>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Why the step failed or was skipped when it isn't the script's fault
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// The steps of a composite action
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<StepRun>,
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    UnknownJob(String),
+    /// A job that needs a job that doesn't exist
+    UnknownNeed(String, String),
+    /// A job that transitively needs itself
+    Cycle(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::UnknownJob(name) => write!(f, "unknown job `{name}`"),
+            RunError::UnknownNeed(job, need) => {
+                write!(f, "job `{job}` needs `{need}`, which doesn't exist")
+            }
+            RunError::Cycle(job) => write!(f, "job `{job}` depends on itself through `needs`"),
+            RunError::Io(e) => write!(f, "io error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+impl From<io::Error> for RunError {
+    fn from(e: io::Error) -> Self {
+        RunError::Io(e)
+    }
+}
+
+/// The state threaded through the steps of a job
+struct JobState {
+    contexts: BTreeMap<String, Expr>,
+    /// The workflow's and job's `env` and what was written to `$GITHUB_ENV`
+    env: BTreeMap<String, String>,
+    /// What was written to `$GITHUB_PATH`, the latest first
+    path: Vec<String>,
+    summary: String,
+    failed: bool,
+    deadline: Instant,
+    cancelled: Arc<AtomicBool>,
+    /// The caches to save once the job succeeded
+    saves: Vec<CacheSave>,
+}
+
+impl Runner {
+    /// Run the job `name` of `workflow` once, as if it doesn't need other
+    /// jobs and its matrix is empty
+    pub fn run_job(&self, workflow: &Workflow, name: &str) -> Result<JobRun, RunError> {
+        let job = workflow
+            .jobs
+            .get(name)
+            .ok_or_else(|| RunError::UnknownJob(name.to_string()))?;
+        let contexts = self.contexts();
+        let mut run = JobRun::new(name, name);
+        let status = |function: &str| Some(function != "cancelled");
+        match condition::check(job.if_cond.as_deref(), &contexts, &status) {
+            Ok(true) => {}
+            Ok(false) => {
+                run.result = Conclusion::Skipped;
+                return Ok(run);
+            }
+            Err(e) => {
+                run.result = Conclusion::Failure;
+                run.error = Some(e);
+                return Ok(run);
+            }
+        }
+        let store = self.store()?;
+        self.job(workflow, job, contexts, run, &store, Arc::default())
+            .map_err(Into::into)
+    }
+
+    /// The `github`, `inputs`, `vars` and `secrets` contexts
+    fn contexts(&self) -> BTreeMap<String, Expr> {
+        let mut contexts = Evaluator::new(&self.event).into_contexts();
+        contexts.extend(self.contexts.clone());
+        contexts
+    }
+
+    /// Run the steps of `job` with `contexts` extended by the job's own,
+    /// stopping at the next step `cancelled` is set
+    fn job(
+        &self,
+        workflow: &Workflow,
+        job: &Job,
+        mut contexts: BTreeMap<String, Expr>,
+        mut run: JobRun,
+        store: &Store,
+        cancelled: Arc<AtomicBool>,
+    ) -> io::Result<JobRun> {
+        let start = Instant::now();
+        let name = run.job.clone();
+        let (root, workspace, temp, home) = self.directories()?;
+        let string = |s: &Path| Expr::String(s.display().to_string());
+        contexts.insert("github.workspace".into(), string(&workspace));
+        contexts.insert("github.job".into(), Expr::String(name.to_string()));
+        contexts.insert("runner.os".into(), Expr::String("Linux".into()));
+        contexts.insert("runner.temp".into(), string(&temp));
+        let mut state = JobState {
+            contexts,
+            env: BTreeMap::new(),
+            path: Vec::new(),
+            summary: String::new(),
+            failed: false,
+            deadline: Instant::now() + minutes(DEFAULT_TIMEOUT_MINUTES),
+            cancelled,
+            saves: Vec::new(),
+        };
+        let env = workflow.env.iter().chain(&job.env);
+        if let Err(e) = self.extend_env(&mut state, env) {
+            let _ = fs::remove_dir_all(&root);
+            run.result = Conclusion::Failure;
+            run.error = Some(e);
+            return Ok(run);
+        }
+        match job
+            .timeout_minutes
+            .as_ref()
+            .map(|t| number(t, &state.contexts))
+        {
+            Some(Ok(timeout)) => state.deadline = Instant::now() + minutes(timeout),
+            Some(Err(e)) => {
+                let _ = fs::remove_dir_all(&root);
+                run.result = Conclusion::Failure;
+                run.error = Some(e);
+                return Ok(run);
+            }
+            None => {}
+        }
+
+        let shell = [&job.defaults.run.shell, &workflow.defaults.run.shell];
+        let directory = [
+            &job.defaults.run.working_directory,
+            &workflow.defaults.run.working_directory,
+        ];
+        for (idx, step) in job.steps.iter().enumerate() {
+            let defaults = StepDefaults {
+                shell: shell.iter().find_map(|s| s.as_deref()),
+                working_directory: directory.iter().find_map(|d| d.as_deref()),
+                workspace: &workspace,
+                temp: &temp,
+                home: &home,
+                store,
+                job: &name,
+                idx,
+                depth: 0,
+            };
+            let step = self.step(step, &defaults, &mut state)?;
+            if step.timed_out && Instant::now() >= state.deadline {
+                run.error = Some("the job timed out".into());
+            }
+            run.steps.push(step);
+        }
+        if !state.failed && !state.cancelled.load(Ordering::Relaxed) {
+            for save in &state.saves {
+                if let Err(e) = store.save(save) {
+                    state.failed = true;
+                    run.error = Some(format!("saving a cache: {e}"));
+                }
+            }
+        }
+        // the temp directory and the default workspace
+        let _ = fs::remove_dir_all(&root);
+
+        for (output, value) in &job.outputs {
+            match condition::interpolate(value, &state.contexts) {
+                Ok(value) => {
+                    run.outputs.insert(output.clone(), value);
+                }
+                Err(e) => {
+                    state.failed = true;
+                    run.error = Some(format!("output `{output}`: {e}"));
+                }
+            }
+        }
+        run.summary = state.summary;
+        if state.cancelled.load(Ordering::Relaxed) {
+            run.result = Conclusion::Cancelled;
+        } else if state.failed {
+            run.result = Conclusion::Failure;
+        }
+        run.duration = start.elapsed();
+        Ok(run)
+    }
+
+    /// The store of a run, in a new temporary directory unless one is set
+    fn store(&self) -> io::Result<Store> {
+        static STORES: AtomicUsize = AtomicUsize::new(0);
+        match &self.store {
+            Some(root) => Store::new(root),
+            None => Store::temporary(&std::env::temp_dir().join(format!(
+                "grackle-store-{}-{}",
+                std::process::id(),
+                STORES.fetch_add(1, Ordering::Relaxed)
+            ))),
+        }
+    }
+
+    /// A new directory for a job, the workspace, the runner temp directory
+    /// and the home directory steps get as `$HOME`, all inside the first but
+    /// the workspace when one is set
+    fn directories(&self) -> io::Result<(PathBuf, PathBuf, PathBuf, PathBuf)> {
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "grackle-run-{}-{}",
+            std::process::id(),
+            RUNS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let workspace = match &self.workspace {
+            Some(workspace) => workspace.clone(),
+            None => root.join("workspace"),
+        };
+        let temp = root.join("temp");
+        let home = root.join("home");
+        fs::create_dir_all(&workspace)?;
+        fs::create_dir_all(&temp)?;
+        fs::create_dir_all(&home)?;
+        Ok((root, fs::canonicalize(workspace)?, temp, home))
+    }
+
+    /// Evaluate `env` and add it to the state and the `env` context
+    fn extend_env<'a>(
+        &self,
+        state: &mut JobState,
+        env: impl Iterator<Item = (&'a String, &'a String)>,
+    ) -> Result<(), String> {
+        for (name, value) in env {
+            let value = condition::interpolate(value, &state.contexts)?;
+            state.contexts.insert(
+                format!("env.{}", name.to_ascii_lowercase()),
+                Expr::String(value.clone()),
+            );
+            state.env.insert(name.clone(), value);
+        }
+        Ok(())
+    }
+
+    fn step(
+        &self,
+        step: &JobStep,
+        defaults: &StepDefaults<'_>,
+        state: &mut JobState,
+    ) -> io::Result<StepRun> {
+        let mut run = StepRun {
+            name: step_name(step),
+            id: step.id.clone(),
+            outcome: Conclusion::Skipped,
+            conclusion: Conclusion::Skipped,
+            exit_code: None,
+            timed_out: false,
+            outputs: BTreeMap::new(),
+            stdout: String::new(),
+            stderr: String::new(),
+            message: None,
+            steps: Vec::new(),
+        };
+        let failed = state.failed;
+        let cancelled = state.cancelled.load(Ordering::Relaxed);
+        let status = move |function: &str| match function {
+            "success" => Some(!failed && !cancelled),
+            "failure" => Some(failed),
+            "always" => Some(true),
+            _ => Some(cancelled),
+        };
+        // the step's `env` is only visible to the step
+        let mut step_state = JobState {
+            contexts: state.contexts.clone(),
+            env: state.env.clone(),
+            path: state.path.clone(),
+            summary: String::new(),
+            failed,
+            deadline: state.deadline,
+            cancelled: state.cancelled.clone(),
+            saves: Vec::new(),
+        };
+        let prepared = self
+            .extend_env(&mut step_state, step.env.iter())
+            .and_then(|()| {
+                condition::check(step.if_cond.as_deref(), &step_state.contexts, &status)
+            });
+        match prepared {
+            Ok(true) if Instant::now() >= state.deadline => {
+                run.message = Some("the job timed out".into());
+                state.failed = true;
+                return Ok(run);
+            }
+            Ok(true) => {}
+            Ok(false) => {
+                record(&run, state);
+                return Ok(run);
+            }
+            Err(e) => {
+                run.message = Some(e);
+                run.outcome = Conclusion::Failure;
+                run.conclusion = Conclusion::Failure;
+                state.failed = true;
+                record(&run, state);
+                return Ok(run);
+            }
+        }
+        let ran = match (&step.run, &step.uses) {
+            (Some(script), _) => {
+                let script = Script {
+                    script,
+                    step,
+                    defaults,
+                    contexts: &step_state.contexts,
+                    env: &step_state.env,
+                    path: &state.path,
+                    deadline: state.deadline,
+                    cancelled: &state.cancelled,
+                };
+                self.script(&script, &mut run)
+            }
+            (None, Some(uses)) => self.action(uses, step, defaults, &step_state, &mut run),
+            (None, None) => Err("the step has neither `run` nor `uses`".into()),
+        };
+        run.outcome = match ran {
+            Ok((commands, conclusion)) => {
+                run.outputs = commands.outputs;
+                for (name, value) in commands.env {
+                    state.contexts.insert(
+                        format!("env.{}", name.to_ascii_lowercase()),
+                        Expr::String(value.clone()),
+                    );
+                    state.env.insert(name, value);
+                }
+                let mut path = commands.path;
+                path.append(&mut state.path);
+                state.path = path;
+                state.summary.push_str(&commands.summary);
+                state.saves.extend(commands.saves);
+                conclusion
+            }
+            Err(e) => {
+                run.message = Some(e);
+                Conclusion::Failure
+            }
+        };
+        let continue_on_error = match &step.continue_on_error {
+            Some(value) => truthy(value, &step_state.contexts),
+            None => Ok(false),
+        };
+        run.conclusion = match (run.outcome, continue_on_error) {
+            (Conclusion::Failure, Ok(true)) => Conclusion::Success,
+            (outcome, Ok(_)) => outcome,
+            (_, Err(e)) => {
+                run.message = Some(e);
+                Conclusion::Failure
+            }
+        };
+        if run.conclusion == Conclusion::Failure {
+            state.failed = true;
+        }
+        record(&run, state);
+        Ok(run)
+    }
+
+    /// Run the script of a step and read what it wrote to the files
+    fn script(
+        &self,
+        script: &Script<'_>,
+        run: &mut StepRun,
+    ) -> Result<(FileCommands, Conclusion), String> {
+        let Script {
+            step,
+            defaults,
+            contexts,
+            ..
+        } = *script;
+        let text = condition::interpolate(script.script, contexts)?;
+        let (program, args, extension) =
+            shell(step.shell.as_deref().or(defaults.shell).unwrap_or_default())?;
+        let directory = match step
+            .working_directory
+            .as_deref()
+            .or(defaults.working_directory)
+        {
+            Some(dir) => defaults
+                .workspace
+                .join(condition::interpolate(dir, contexts)?),
+            None => defaults.workspace.to_path_buf(),
+        };
+        if !directory.is_dir() {
+            return Err(format!(
+                "the working directory `{}` doesn't exist",
+                directory.display()
+            ));
+        }
+        let deadline = match &step.timeout_minutes {
+            Some(timeout) => {
+                let timeout = Instant::now() + minutes(number(timeout, contexts)?);
+                timeout.min(script.deadline)
+            }
+            None => script.deadline,
+        };
+
+        let io = |e: io::Error| e.to_string();
+        let files = defaults
+            .temp
+            .join(format!("_file_commands_{}", defaults.idx));
+        fs::create_dir_all(&files).map_err(io)?;
+        let script_path = files.join(format!("script.{extension}"));
+        fs::write(&script_path, &text).map_err(io)?;
+        let file = |name: &str| -> Result<PathBuf, String> {
+            let path = files.join(name);
+            fs::write(&path, "").map_err(io)?;
+            Ok(path)
+        };
+        let output = file("output")?;
+        let env = file("env")?;
+        let path = file("path")?;
+        let summary = file("summary")?;
+
+        let mut command = Command::new(program);
+        command
+            .args(
+                args.iter()
+                    .map(|arg| arg.replace("{0}", &script_path.to_string_lossy())),
+            )
+            .current_dir(&directory)
+            .envs(&self.env)
+            .envs(self.default_env(contexts, defaults, step))
+            .envs(script.env)
+            .env("GITHUB_OUTPUT", &output)
+            .env("GITHUB_ENV", &env)
+            .env("GITHUB_PATH", &path)
+            .env("GITHUB_STEP_SUMMARY", &summary)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0);
+        if !script.path.is_empty() {
+            let mut paths = script.path.to_vec();
+            paths.extend(std::env::var("PATH").ok());
+            command.env("PATH", paths.join(":"));
+        }
+        let child = command
+            .spawn()
+            .map_err(|e| format!("can't run `{program}`: {e}"))?;
+        let (status, timed_out) = wait(child, deadline, script.cancelled, run).map_err(io)?;
+        run.exit_code = status;
+        run.timed_out = timed_out;
+
+        let conclusion = if status == Some(0) && !timed_out {
+            Conclusion::Success
+        } else if status.is_none() && script.cancelled.load(Ordering::Relaxed) {
+            Conclusion::Cancelled
+        } else {
+            Conclusion::Failure
+        };
+        let read = |path: &Path| fs::read_to_string(path).map_err(io);
+        let commands = FileCommands {
+            outputs: parse_file_command(&read(&output)?)?.into_iter().collect(),
+            env: parse_file_command(&read(&env)?)?,
+            path: read(&path)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .rev()
+                .map(String::from)
+                .collect(),
+            summary: read(&summary)?,
+            saves: Vec::new(),
+        };
+        Ok((commands, conclusion))
+    }
+
+    /// The variables GitHub sets for every step
+    fn default_env(
+        &self,
+        contexts: &BTreeMap<String, Expr>,
+        defaults: &StepDefaults<'_>,
+        step: &JobStep,
+    ) -> BTreeMap<String, String> {
+        let mut env = BTreeMap::from([
+            ("CI".to_string(), "true".to_string()),
+            ("GITHUB_ACTIONS".into(), "true".into()),
+            ("GITHUB_EVENT_NAME".into(), self.event.name.clone()),
+            ("GITHUB_JOB".into(), defaults.job.to_string()),
+            (
+                "GITHUB_ACTION".into(),
+                step.id.clone().unwrap_or(format!("__run_{}", defaults.idx)),
+            ),
+            (
+                "GITHUB_WORKSPACE".into(),
+                defaults.workspace.display().to_string(),
+            ),
+            ("HOME".into(), defaults.home.display().to_string()),
+            ("RUNNER_OS".into(), "Linux".into()),
+            ("RUNNER_TEMP".into(), defaults.temp.display().to_string()),
+        ]);
+        for (name, path) in [
+            ("GITHUB_REF", "github.ref"),
+            ("GITHUB_REF_NAME", "github.ref_name"),
+            ("GITHUB_REF_TYPE", "github.ref_type"),
+            ("GITHUB_BASE_REF", "github.base_ref"),
+            ("GITHUB_HEAD_REF", "github.head_ref"),
+        ] {
+            if let Some(Expr::String(value)) = contexts.get(path) {
+                env.insert(name.into(), value.clone());
+            }
+        }
+        env
+    }
+}
+
+impl JobRun {
+    /// The result counted towards the job and the workflow, a failure with
+    /// `continue-on-error` counts as a success
+    pub fn conclusion(&self) -> Conclusion {
+        match self.result {
+            Conclusion::Failure if self.continue_on_error => Conclusion::Success,
+            result => result,
+        }
+    }
+
+    fn new(job: &str, name: impl Into<String>) -> Self {
+        Self {
+            job: job.to_string(),
+            name: name.into(),
+            matrix: BTreeMap::new(),
+            concurrency: None,
+            result: Conclusion::Success,
+            continue_on_error: false,
+            duration: Duration::ZERO,
+            steps: Vec::new(),
+            outputs: BTreeMap::new(),
+            summary: String::new(),
+            error: None,
+        }
+    }
+}
+
+fn seconds<S: serde::Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+/// A `run` step ready to run
+struct Script<'a> {
+    script: &'a str,
+    step: &'a JobStep,
+    defaults: &'a StepDefaults<'a>,
+    contexts: &'a BTreeMap<String, Expr>,
+    env: &'a BTreeMap<String, String>,
+    path: &'a [String],
+    deadline: Instant,
+    cancelled: &'a AtomicBool,
+}
+
+/// What a step wrote to the `$GITHUB_*` files
+#[derive(Default)]
+struct FileCommands {
+    outputs: BTreeMap<String, String>,
+    env: Vec<(String, String)>,
+    /// The latest first
+    path: Vec<String>,
+    summary: String,
+    /// The caches `actions/cache` saves at the end of the job
+    saves: Vec<CacheSave>,
+}
+
+/// Add the results of a step to the `steps` context
+fn record(run: &StepRun, state: &mut JobState) {
+    let Some(id) = &run.id else {
+        return;
+    };
+    let id = id.to_ascii_lowercase();
+    let contexts = &mut state.contexts;
+    contexts.insert(
+        format!("steps.{id}.outcome"),
+        Expr::String(run.outcome.to_string()),
+    );
+    contexts.insert(
+        format!("steps.{id}.conclusion"),
+        Expr::String(run.conclusion.to_string()),
+    );
+    for (name, value) in &run.outputs {
+        contexts.insert(
+            format!("steps.{id}.outputs.{}", name.to_ascii_lowercase()),
+            Expr::String(value.clone()),
+        );
+    }
+}
+
+/// What a step falls back to when it doesn't set them
+struct StepDefaults<'a> {
+    shell: Option<&'a str>,
+    working_directory: Option<&'a str>,
+    workspace: &'a Path,
+    temp: &'a Path,
+    /// The job's `$HOME`, which `~` in action inputs resolves against
+    home: &'a Path,
+    store: &'a Store,
+    job: &'a str,
+    idx: usize,
+    /// How many composite actions the step is nested in
+    depth: usize,
+}
+
+fn step_name(step: &JobStep) -> String {
+    if let Some(name) = &step.name {
+        return name.clone();
+    }
+    match (&step.run, &step.uses) {
+        (Some(run), _) => format!("Run {}", run.lines().next().unwrap_or_default()),
+        (None, Some(uses)) => format!("Run {uses}"),
+        (None, None) => String::new(),
+    }
+}
+
+/// The program, arguments and script extension of a `shell`, `{0}` in the
+/// arguments is replaced with the script's path
+fn shell(shell: &str) -> Result<(&str, Vec<&str>, &'static str), String> {
+    let args = |args: &'static str| args.split(' ').collect();
+    Ok(match shell {
+        "" if find_program("bash") => ("bash", args("-e {0}"), "sh"),
+        "" | "sh" => ("sh", args("-e {0}"), "sh"),
+        "bash" => ("bash", args("--noprofile --norc -eo pipefail {0}"), "sh"),
+        "python" => ("python", args("{0}"), "py"),
+        "pwsh" | "powershell" => (shell, vec!["-command", ". '{0}'"], "ps1"),
+        "cmd" => return Err("`cmd` can't run on Linux".into()),
+        custom if custom.contains("{0}") => {
+            let mut parts = custom.split_whitespace();
+            let program = parts.next().unwrap_or_default();
+            (program, parts.collect(), "sh")
+        }
+        custom => {
+            return Err(format!(
+                "unknown shell `{custom}`, custom shells need `{{0}}`"
+            ))
+        }
+    })
+}
+
+fn find_program(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+}
+
+fn minutes(minutes: f64) -> Duration {
+    Duration::try_from_secs_f64(minutes * 60.0).unwrap_or(Duration::MAX)
+}
+
+/// Add `value` to `contexts` at `path`, and the values of a mapping at
+/// their paths below it
+fn insert_value(contexts: &mut BTreeMap<String, Expr>, path: &str, value: &Value) {
+    let expr = match value {
+        Value::Null => Expr::Null,
+        Value::Bool(b) => Expr::Bool(*b),
+        Value::Number(n) => Expr::Number(n.as_f64().unwrap_or(f64::NAN)),
+        Value::String(s) => Expr::String(s.clone()),
+        Value::Mapping(map) => {
+            for (key, value) in map {
+                if let Some(key) = key.as_str() {
+                    insert_value(contexts, &format!("{path}.{key}"), value);
+                }
+            }
+            return;
+        }
+        Value::Tagged(tagged) => return insert_value(contexts, path, &tagged.value),
+        Value::Sequence(_) => return,
+    };
+    contexts.insert(path.to_ascii_lowercase(), expr);
+}
+
+/// A number that may be an expression, like `timeout-minutes`
+fn number(value: &Value, contexts: &BTreeMap<String, Expr>) -> Result<f64, String> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => condition::interpolate(s, contexts)?.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("expected a number, found {value:?}"))
+}
+
+/// A boolean that may be an expression, like `continue-on-error`
+fn truthy(value: &Value, contexts: &BTreeMap<String, Expr>) -> Result<bool, String> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        Value::String(s) => match condition::interpolate(s, contexts)?.trim() {
+            "true" => Ok(true),
+            "false" | "" => Ok(false),
+            other => Err(format!("expected a boolean, found `{other}`")),
+        },
+        _ => Err(format!("expected a boolean, found {value:?}")),
+    }
+}
+
+/// Wait for `child` to exit, killing it and everything it started at the
+/// deadline or when the job is cancelled. Returns the exit code and whether
+/// it timed out.
+fn wait(
+    mut child: Child,
+    deadline: Instant,
+    cancelled: &AtomicBool,
+    run: &mut StepRun,
+) -> io::Result<(Option<i32>, bool)> {
+    let read = |pipe: Option<Box<dyn Read + Send>>| {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            String::from_utf8_lossy(&buf).into_owned()
+        })
+    };
+    let stdout = read(child.stdout.take().map(|p| Box::new(p) as _));
+    let stderr = read(child.stderr.take().map(|p| Box::new(p) as _));
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        timed_out = Instant::now() >= deadline;
+        if timed_out || cancelled.load(Ordering::Relaxed) {
+            let _ = Command::new("kill")
+                .args(["-KILL", "--", &format!("-{}", child.id())])
+                .status();
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    run.stdout = stdout.join().unwrap_or_default();
+    run.stderr = stderr.join().unwrap_or_default();
+    Ok((status.code(), timed_out))
+}
+
+/// Parse `name=value` lines and `name<<delimiter` blocks
+fn parse_file_command(text: &str) -> Result<Vec<(String, String)>, String> {
+    let mut ret = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let heredoc = line.find("<<").filter(|&idx| !line[..idx].contains('='));
+        if let Some(idx) = heredoc {
+            let (name, delimiter) = (&line[..idx], &line[idx + 2..]);
+            let mut value = Vec::new();
+            loop {
+                match lines.next() {
+                    Some(line) if line == delimiter => break,
+                    Some(line) => value.push(line),
+                    None => return Err(format!("`{name}` is missing its delimiter `{delimiter}`")),
+                }
+            }
+            ret.push((name.to_string(), value.join("\n")));
+        } else if let Some((name, value)) = line.split_once('=') {
+            ret.push((name.to_string(), value.to_string()));
+        } else {
+            return Err(format!("invalid line `{line}`, expected `name=value`"));
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runner() -> Runner {
+        Runner::builder()
+            .event(Event::builder().name("push").git_ref("main").build())
+            .secret("TOKEN", "hunter2")
+            .build()
+    }
+
+    #[test]
+    fn run_steps() {
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: ci
+on: push
+env:
+  LEVEL: workflow
+  GREETING: hello
+defaults:
+  run:
+    shell: sh
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    env:
+      LEVEL: job
+    defaults:
+      run:
+        working-directory: sub
+    outputs:
+      version: ${{ steps.version.outputs.version }}
+      workspace: ${{ github.workspace }}
+    steps:
+      - working-directory: .
+        run: mkdir -p sub bin && printf '#!/bin/sh\necho tool\n' > bin/tool && chmod +x bin/tool
+      - id: version
+        run: |
+          pwd | sed 's|.*/||'
+          echo "version=1.2.3" >> "$GITHUB_OUTPUT"
+          echo "FROM_STEP=$LEVEL" >> "$GITHUB_ENV"
+          echo "$GITHUB_WORKSPACE/bin" >> "$GITHUB_PATH"
+          printf 'NOTES<<EOF\nline 1\nline 2\nEOF\n' >> "$GITHUB_ENV"
+          echo "## Version" >> "$GITHUB_STEP_SUMMARY"
+      - env:
+          LEVEL: step
+        run: echo "$GREETING $LEVEL $FROM_STEP ${{ env.FROM_STEP }} $NOTES" && tool
+      - if: github.ref_name == 'main' && steps.version.outputs.version != ''
+        run: echo "$LEVEL ${{ secrets.TOKEN }} $GITHUB_REF $CI"
+      - if: github.event_name == 'pull_request'
+        run: echo skipped
+      - uses: actions/checkout@v4
+"###,
+        )
+        .unwrap();
+        let runner = Runner {
+            skip_unstubbed: true,
+            ..runner()
+        };
+        let run = runner.run_job(&workflow, "test").unwrap();
+        let steps: Vec<_> = run
+            .steps
+            .iter()
+            .map(|s| format!("{} {}: {}", s.conclusion, s.name, s.stdout.trim_end()))
+            .collect();
+        insta::assert_debug_snapshot!(steps);
+        assert_eq!(run.result, Conclusion::Success);
+        assert_eq!(run.outputs["version"], "1.2.3");
+        assert_eq!(run.summary, "## Version\n");
+        assert!(!Path::new(&run.outputs["workspace"]).exists());
+    }
+
+    #[test]
+    fn failures() {
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: ci
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - id: flaky
+        continue-on-error: true
+        run: exit 3
+      - timeout-minutes: 0.01
+        run: sleep 5
+      - run: echo skipped
+      - if: failure() && steps.flaky.outcome == 'failure'
+        run: echo ${{ steps.flaky.conclusion }}
+      - if: always()
+        shell: fish
+        run: echo unknown shell
+"###,
+        )
+        .unwrap();
+        let start = Instant::now();
+        let run = runner().run_job(&workflow, "test").unwrap();
+        assert!(start.elapsed() < Duration::from_secs(4));
+        let steps: Vec<_> = run
+            .steps
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} ({}) exit={:?} timed_out={}: {}",
+                    s.conclusion,
+                    s.outcome,
+                    s.exit_code,
+                    s.timed_out,
+                    s.message.as_deref().unwrap_or(s.stdout.trim_end()),
+                )
+            })
+            .collect();
+        insta::assert_debug_snapshot!(steps);
+        assert_eq!(run.result, Conclusion::Failure);
+    }
+
+    #[test]
+    fn actions() {
+        let workspace = crate::testing::TempDir::new("actions");
+        let action = workspace.join(".github/actions/greet");
+        fs::create_dir_all(&action).unwrap();
+        fs::write(
+            action.join("action.yml"),
+            r###"name: greet
+inputs:
+  who:
+    default: ${{ github.ref_name }}
+  greeting:
+    default: hello
+outputs:
+  message:
+    value: ${{ steps.say.outputs.message }}
+runs:
+  using: composite
+  steps:
+    - id: say
+      shell: sh
+      run: |
+        echo "message=${{ inputs.greeting }} ${{ inputs.who }}" >> "$GITHUB_OUTPUT"
+        echo "GREETED=${{ inputs.who }}" >> "$GITHUB_ENV"
+        test -f "$GITHUB_ACTION_PATH/action.yml" && echo found
+    - uses: actions/upload-artifact@v4
+      with:
+        name: greeting
+"###,
+        )
+        .unwrap();
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: ci
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v4
+      - id: greet
+        uses: ./.github/actions/greet
+        with:
+          who: ${{ steps.missing.outputs.x || 'world' }}
+      - run: echo "${{ steps.greet.outputs.message }} $GREETED" && cat checked-out
+      - id: version
+        uses: acme/version@main
+        with:
+          bump: minor
+      - run: echo ${{ steps.version.outputs.version }}
+      - uses: acme/unknown@v1
+      - uses: docker://alpine:3
+"###,
+        )
+        .unwrap();
+        let uploads = Arc::new(AtomicUsize::new(0));
+        let counter = uploads.clone();
+        let run = Runner::builder()
+            .event(Event::builder().name("push").git_ref("main").build())
+            .workspace(workspace.to_path_buf())
+            .stub("actions/checkout@v4", |context: &StubContext<'_>| {
+                fs::write(context.workspace.join("checked-out"), "yes\n")
+                    .map_err(|e| e.to_string())?;
+                Ok(BTreeMap::new())
+            })
+            .stub(
+                "actions/upload-artifact",
+                move |context: &StubContext<'_>| {
+                    assert_eq!(context.inputs["name"], "greeting");
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    Ok(BTreeMap::new())
+                },
+            )
+            .stub_script(
+                "acme/version@main",
+                r#"echo "version=2.0.0-$INPUT_BUMP-${{ inputs.bump }}" >> "$GITHUB_OUTPUT""#,
+            )
+            .skip_unstubbed(true)
+            .build()
+            .run_job(&workflow, "test")
+            .unwrap();
+        let steps: Vec<_> = run
+            .steps
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} {}: {}",
+                    s.conclusion,
+                    s.name,
+                    s.message.as_deref().unwrap_or(s.stdout.trim_end())
+                )
+            })
+            .collect();
+        insta::assert_debug_snapshot!(steps);
+        assert_eq!(run.steps[1].steps.len(), 2);
+        assert_eq!(uploads.load(Ordering::Relaxed), 1);
+        assert_eq!(run.result, Conclusion::Success);
+    }
+
+    #[test]
+    fn unstubbed_actions() {
+        let workflow: Workflow = serde_yml::from_str(
+            r#"name: ci
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: acme/unknown@v1
+      - run: echo after
+"#,
+        )
+        .unwrap();
+        let runner = |skip| {
+            Runner::builder()
+                .event(Event::builder().name("push").build())
+                .skip_unstubbed(skip)
+                .build()
+        };
+        let run = runner(false).run_job(&workflow, "test").unwrap();
+        assert_eq!(run.result, Conclusion::Failure);
+        assert_eq!(run.steps[0].conclusion, Conclusion::Failure);
+        assert_eq!(
+            run.steps[0].message.as_deref(),
+            Some("no stub for `acme/unknown@v1`")
+        );
+        assert_eq!(run.steps[1].conclusion, Conclusion::Skipped);
+
+        let run = runner(true).run_workflow(&workflow).unwrap();
+        assert_eq!(run.result, Conclusion::Success);
+        assert!(run
+            .to_markdown()
+            .contains("`test`: `Run acme/unknown@v1` skipped, no stub for `acme/unknown@v1`"));
+    }
+
+    #[test]
+    fn file_commands() {
+        assert_eq!(
+            parse_file_command("a=1\n\nb<<END\nx=y\n\nEND\nc=d=e\n"),
+            Ok(vec![
+                ("a".into(), "1".into()),
+                ("b".into(), "x=y\n".into()),
+                ("c".into(), "d=e".into()),
+            ])
+        );
+        assert!(parse_file_command("a<<END\nvalue\n").is_err());
+        assert!(parse_file_command("name\n").is_err());
+    }
+}
@@ -0,0 +1,817 @@
+//! Run every job of a workflow in the order of their `needs`
+
+use super::{insert_value, number, truthy, Artifact, Conclusion, JobRun, RunError, Runner};
+use crate::{condition, expr, expr::Expr, Job, Matrix, Workflow};
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_yml::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The report of a workflow run
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowRun {
+    pub name: String,
+    pub result: Conclusion,
+    #[serde(serialize_with = "super::seconds")]
+    pub duration: Duration,
+    /// The evaluated workflow `concurrency.group`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<String>,
+    /// Every job instance, in the order they finished
+    pub jobs: Vec<JobRun>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<Artifact>,
+}
+
+impl WorkflowRun {
+    /// The instances of the job `id`
+    pub fn job<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a JobRun> {
+        self.jobs.iter().filter(move |run| run.job == id)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("workflow runs are valid json")
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut ret = format!(
+            "# {}\n\n{} in {:.1}s\n",
+            self.name,
+            self.result,
+            self.duration.as_secs_f64()
+        );
+        ret.push_str("\n| Job | Result | Duration | Steps |\n");
+        ret.push_str("| --- | --- | --- | --- |\n");
+        for run in &self.jobs {
+            let mut steps = BTreeMap::new();
+            for step in &run.steps {
+                *steps.entry(step.conclusion.to_string()).or_insert(0) += 1;
+            }
+            let steps: Vec<_> = steps
+                .iter()
+                .map(|(conclusion, count)| format!("{count} {conclusion}"))
+                .collect();
+            let _ = writeln!(
+                ret,
+                "| `{}` | {} | {:.1}s | {} |",
+                run.name,
+                run.result,
+                run.duration.as_secs_f64(),
+                steps.join(", "),
+            );
+        }
+        if !self.artifacts.is_empty() {
+            ret.push_str("\n| Artifact | Job | Files | Size | Retention |\n");
+            ret.push_str("| --- | --- | --- | --- | --- |\n");
+            for artifact in &self.artifacts {
+                let _ = writeln!(
+                    ret,
+                    "| `{}` | `{}` | {} | {} B | {} days |",
+                    artifact.name,
+                    artifact.job,
+                    artifact.files,
+                    artifact.size,
+                    artifact.retention_days,
+                );
+            }
+        }
+        for run in &self.jobs {
+            if let Some(error) = &run.error {
+                let _ = write!(ret, "\n`{}`: {error}\n", run.name);
+            }
+            let failed = run
+                .steps
+                .iter()
+                .filter(|s| s.outcome == Conclusion::Failure);
+            for step in failed {
+                let why = match (&step.message, step.exit_code, step.timed_out) {
+                    (Some(message), ..) => message.clone(),
+                    (None, _, true) => "timed out".into(),
+                    (None, Some(code), _) => format!("exited with {code}"),
+                    (None, None, _) => "was killed".into(),
+                };
+                let _ = write!(ret, "\n`{}`: `{}` {why}\n", run.name, step.name);
+                if !step.stderr.is_empty() {
+                    let _ = write!(ret, "\n```\n{}\n```\n", step.stderr.trim_end());
+                }
+            }
+            let skipped = run
+                .steps
+                .iter()
+                .filter(|s| s.conclusion == Conclusion::Skipped);
+            for step in skipped {
+                if let Some(message) = &step.message {
+                    let _ = write!(
+                        ret,
+                        "\n`{}`: `{}` skipped, {message}\n",
+                        run.name, step.name
+                    );
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// A job instance waiting to start
+struct Instance {
+    run: JobRun,
+    contexts: BTreeMap<String, Expr>,
+    cancel_in_progress: bool,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// A job whose instances are running
+struct Running<'a> {
+    job: &'a Job,
+    pending: VecDeque<Instance>,
+    /// The cancellation flags of the running instances
+    running: Vec<Arc<AtomicBool>>,
+    max_parallel: usize,
+    fail_fast: bool,
+}
+
+/// The instance holding a concurrency group and the one waiting for it
+#[derive(Default)]
+struct Group {
+    running: Option<Arc<AtomicBool>>,
+    pending: Option<Arc<AtomicBool>>,
+}
+
+impl Runner {
+    /// Run every job of `workflow`, each after the jobs it needs. Matrix
+    /// instances run in parallel up to `max-parallel`, and with `fail-fast`
+    /// the first failure cancels the others. A job waits for the instance
+    /// running in its concurrency group, cancelling the instance already
+    /// waiting, or the running one with `cancel-in-progress`.
+    pub fn run_workflow(&self, workflow: &Workflow) -> Result<WorkflowRun, RunError> {
+        check_needs(workflow)?;
+        let start = Instant::now();
+        let contexts = self.contexts();
+        let mut report = WorkflowRun {
+            name: workflow.name.clone(),
+            result: Conclusion::Success,
+            duration: Duration::ZERO,
+            concurrency: workflow
+                .concurrency
+                .group
+                .as_ref()
+                .and_then(|group| scalar(group, &contexts).ok()),
+            jobs: Vec::new(),
+            artifacts: Vec::new(),
+        };
+        let store = self.store()?;
+        let mut done: BTreeMap<&str, (Conclusion, BTreeMap<String, String>)> = BTreeMap::new();
+        // in the order of the workflow so instances start in that order
+        let mut running: IndexMap<&str, Running<'_>> = IndexMap::new();
+        let mut groups: BTreeMap<String, Group> = BTreeMap::new();
+        let mut active = 0;
+        let parallel = self
+            .parallel
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from))
+            .max(1);
+        let (tx, rx) = mpsc::channel();
+
+        let scheduled = thread::scope(|scope| -> Result<(), RunError> {
+            loop {
+                // start the jobs whose needs are done, until none are left
+                // since skipping a job may unblock others
+                loop {
+                    let ready: Vec<_> = workflow
+                        .jobs
+                        .iter()
+                        .filter(|(id, job)| {
+                            !done.contains_key(id.as_str())
+                                && !running.contains_key(id.as_str())
+                                && job.needs.iter().all(|n| done.contains_key(n.as_str()))
+                        })
+                        .collect();
+                    if ready.is_empty() {
+                        break;
+                    }
+                    for (id, job) in ready {
+                        match self.expand(workflow, id, job, &contexts, &done) {
+                            Ok(job_running) => {
+                                running.insert(id, job_running);
+                            }
+                            Err(run) => {
+                                done.insert(id, (run.result, BTreeMap::new()));
+                                report.jobs.push(*run);
+                            }
+                        }
+                    }
+                }
+
+                for (id, job) in running.iter_mut() {
+                    let mut idx = 0;
+                    while idx < job.pending.len()
+                        && active < parallel
+                        && job.running.len() < job.max_parallel
+                    {
+                        let instance = &job.pending[idx];
+                        if let Some(name) = &instance.run.concurrency {
+                            let group = groups.entry(name.clone()).or_default();
+                            if let Some(holder) = &group.running {
+                                if instance.cancel_in_progress {
+                                    holder.store(true, Ordering::Relaxed);
+                                }
+                                if let Some(pending) = &group.pending {
+                                    if !Arc::ptr_eq(pending, &instance.cancelled) {
+                                        pending.store(true, Ordering::Relaxed);
+                                    }
+                                }
+                                group.pending = Some(instance.cancelled.clone());
+                                idx += 1;
+                                continue;
+                            }
+                            group.running = Some(instance.cancelled.clone());
+                            group.pending = None;
+                        }
+                        let instance = job.pending.remove(idx).expect("idx is in bounds");
+                        job.running.push(instance.cancelled.clone());
+                        active += 1;
+                        let tx = tx.clone();
+                        let job_def = job.job;
+                        let id: &str = id;
+                        let store = &store;
+                        scope.spawn(move || {
+                            let flag = instance.cancelled.clone();
+                            let run = self.job(
+                                workflow,
+                                job_def,
+                                instance.contexts,
+                                instance.run,
+                                store,
+                                instance.cancelled,
+                            );
+                            let _ = tx.send((id, flag, run));
+                        });
+                    }
+                }
+
+                // instances cancelled while they waited for their group
+                for job in running.values_mut() {
+                    let (cancelled, pending) = std::mem::take(&mut job.pending)
+                        .into_iter()
+                        .partition(|i| i.cancelled.load(Ordering::Relaxed));
+                    job.pending = pending;
+                    for instance in cancelled {
+                        let mut run = instance.run;
+                        run.result = Conclusion::Cancelled;
+                        report.jobs.push(run);
+                    }
+                }
+                self.finish_jobs(&mut running, &mut done, &mut report);
+
+                if active == 0 {
+                    if done.len() == workflow.jobs.len() {
+                        break;
+                    }
+                    continue;
+                }
+                let (id, flag, run) = rx.recv().expect("a job instance is running");
+                active -= 1;
+                let run = run?;
+                for group in groups.values_mut() {
+                    if group
+                        .running
+                        .as_ref()
+                        .is_some_and(|r| Arc::ptr_eq(r, &flag))
+                    {
+                        group.running = None;
+                    }
+                }
+                let job = running.get_mut(id).expect("the job is running");
+                job.running.retain(|r| !Arc::ptr_eq(r, &flag));
+                if run.conclusion() == Conclusion::Failure && job.fail_fast {
+                    for instance in &job.pending {
+                        instance.cancelled.store(true, Ordering::Relaxed);
+                    }
+                    for other in &job.running {
+                        other.store(true, Ordering::Relaxed);
+                    }
+                }
+                report.jobs.push(run);
+            }
+            Ok(())
+        });
+        report.artifacts = store.artifacts();
+        if store.is_temporary() {
+            // the artifacts of a temporary store only last as long as the run
+            drop(store);
+            for artifact in &mut report.artifacts {
+                artifact.path = None;
+            }
+        }
+        scheduled?;
+
+        let results: Vec<_> = done.values().map(|(result, _)| *result).collect();
+        report.result = aggregate(&results);
+        if report.result == Conclusion::Skipped {
+            report.result = Conclusion::Success;
+        }
+        report.duration = start.elapsed();
+        Ok(report)
+    }
+
+    /// Evaluate the `if` of a ready job and expand its matrix, the `Err` is
+    /// the run of a job that skips or can't start
+    fn expand<'a>(
+        &self,
+        workflow: &Workflow,
+        id: &str,
+        job: &'a Job,
+        contexts: &BTreeMap<String, Expr>,
+        done: &BTreeMap<&str, (Conclusion, BTreeMap<String, String>)>,
+    ) -> Result<Running<'a>, Box<JobRun>> {
+        let mut run = JobRun::new(id, id);
+        let mut contexts = contexts.clone();
+        for need in &job.needs {
+            let (result, outputs) = &done[need.as_str()];
+            let need = need.to_ascii_lowercase();
+            contexts.insert(
+                format!("needs.{need}.result"),
+                Expr::String(result.to_string()),
+            );
+            for (name, value) in outputs {
+                contexts.insert(
+                    format!("needs.{need}.outputs.{}", name.to_ascii_lowercase()),
+                    Expr::String(value.clone()),
+                );
+            }
+        }
+        let ancestors: Vec<_> = ancestors(workflow, id)
+            .iter()
+            .map(|ancestor| done[ancestor.as_str()].0)
+            .collect();
+        let status = |function: &str| match function {
+            "success" => Some(ancestors.iter().all(|r| *r == Conclusion::Success)),
+            "failure" => Some(ancestors.contains(&Conclusion::Failure)),
+            "always" => Some(true),
+            _ => Some(false),
+        };
+        let fail = |run: &mut JobRun, error: String| {
+            run.result = Conclusion::Failure;
+            run.error = Some(error);
+        };
+        match condition::check(job.if_cond.as_deref(), &contexts, &status) {
+            Ok(true) => {}
+            Ok(false) => {
+                run.result = Conclusion::Skipped;
+                return Err(Box::new(run));
+            }
+            Err(e) => {
+                fail(&mut run, e);
+                return Err(Box::new(run));
+            }
+        }
+        if job.uses.is_some() {
+            run.result = Conclusion::Skipped;
+            run.error = Some("reusable workflows don't run locally".into());
+            return Err(Box::new(run));
+        }
+
+        let strategy = &job.strategy;
+        let combinations = match combinations(&strategy.matrix, &contexts) {
+            Ok(combinations) if combinations.is_empty() && !strategy.matrix.is_empty() => {
+                fail(&mut run, "the matrix has no combinations".into());
+                return Err(Box::new(run));
+            }
+            Ok(combinations) if combinations.is_empty() => vec![BTreeMap::new()],
+            Ok(combinations) => combinations,
+            Err(e) => {
+                fail(&mut run, e);
+                return Err(Box::new(run));
+            }
+        };
+        let fail_fast = match &strategy.fail_fast {
+            Some(value) => truthy(value, &contexts),
+            None => Ok(true),
+        };
+        let max_parallel = match &strategy.max_parallel {
+            Some(value) => number(value, &contexts).map(|n| n.max(1.0) as usize),
+            None => Ok(usize::MAX),
+        };
+        let (fail_fast, max_parallel) = match (fail_fast, max_parallel) {
+            (Ok(fail_fast), Ok(max_parallel)) => (fail_fast, max_parallel),
+            (Err(e), _) | (_, Err(e)) => {
+                fail(&mut run, e);
+                return Err(Box::new(run));
+            }
+        };
+
+        let total = combinations.len();
+        let mut pending = VecDeque::new();
+        for (idx, matrix) in combinations.into_iter().enumerate() {
+            let mut contexts = contexts.clone();
+            for (key, value) in &matrix {
+                insert_value(&mut contexts, &format!("matrix.{key}"), value);
+            }
+            contexts.insert("strategy.job-index".into(), Expr::Number(idx as f64));
+            contexts.insert("strategy.job-total".into(), Expr::Number(total as f64));
+            contexts.insert("strategy.fail-fast".into(), Expr::Bool(fail_fast));
+            if max_parallel != usize::MAX {
+                contexts.insert(
+                    "strategy.max-parallel".into(),
+                    Expr::Number(max_parallel as f64),
+                );
+            }
+            let mut run = JobRun::new(id, instance_name(id, job, &matrix, &contexts));
+            run.matrix = matrix;
+            let concurrency = &job.concurrency;
+            let group = concurrency.group.as_ref().map(|g| scalar(g, &contexts));
+            let cancel = concurrency
+                .cancel_in_progress
+                .as_ref()
+                .map(|c| truthy(c, &contexts));
+            let continue_on_error = job.continue_on_error.as_ref().map(|c| truthy(c, &contexts));
+            match (
+                group.transpose(),
+                cancel.transpose(),
+                continue_on_error.transpose(),
+            ) {
+                (Ok(group), Ok(cancel), Ok(continue_on_error)) => {
+                    run.concurrency = group;
+                    run.continue_on_error = continue_on_error.unwrap_or_default();
+                    pending.push_back(Instance {
+                        run,
+                        contexts,
+                        cancel_in_progress: cancel.unwrap_or_default(),
+                        cancelled: Arc::default(),
+                    });
+                }
+                (Err(e), ..) | (_, Err(e), _) | (.., Err(e)) => {
+                    fail(&mut run, e);
+                    return Err(Box::new(run));
+                }
+            }
+        }
+        Ok(Running {
+            job,
+            pending,
+            running: Vec::new(),
+            max_parallel,
+            fail_fast,
+        })
+    }
+
+    /// Move jobs without pending or running instances to `done`, merging the
+    /// outputs of their instances in the order they finished
+    fn finish_jobs<'a>(
+        &self,
+        running: &mut IndexMap<&'a str, Running<'_>>,
+        done: &mut BTreeMap<&'a str, (Conclusion, BTreeMap<String, String>)>,
+        report: &mut WorkflowRun,
+    ) {
+        running.retain(|id, job| {
+            if !job.pending.is_empty() || !job.running.is_empty() {
+                return true;
+            }
+            let runs: Vec<_> = report.jobs.iter().filter(|run| run.job == *id).collect();
+            let results: Vec<_> = runs.iter().map(|run| run.conclusion()).collect();
+            let mut outputs = BTreeMap::new();
+            for run in runs {
+                for (name, value) in &run.outputs {
+                    if !value.is_empty() {
+                        outputs.insert(name.clone(), value.clone());
+                    }
+                }
+            }
+            done.insert(id, (aggregate(&results), outputs));
+            false
+        });
+    }
+}
+
+/// Fail on unknown `needs` and cycles before anything runs
+fn check_needs(workflow: &Workflow) -> Result<(), RunError> {
+    for (id, job) in &workflow.jobs {
+        if let Some(need) = job.needs.iter().find(|n| !workflow.jobs.contains_key(*n)) {
+            return Err(RunError::UnknownNeed(id.clone(), need.clone()));
+        }
+        if ancestors(workflow, id).contains(id) {
+            return Err(RunError::Cycle(id.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Every job `id` transitively needs
+fn ancestors(workflow: &Workflow, id: &str) -> BTreeSet<String> {
+    let mut ret = BTreeSet::new();
+    let mut queue: Vec<_> = workflow.jobs[id].needs.clone();
+    while let Some(need) = queue.pop() {
+        if ret.insert(need.clone()) {
+            if let Some(job) = workflow.jobs.get(&need) {
+                queue.extend(job.needs.iter().cloned());
+            }
+        }
+    }
+    ret
+}
+
+/// The result of a job from the results of its instances
+fn aggregate(results: &[Conclusion]) -> Conclusion {
+    if results.contains(&Conclusion::Failure) {
+        Conclusion::Failure
+    } else if results.contains(&Conclusion::Cancelled) {
+        Conclusion::Cancelled
+    } else if !results.is_empty() && results.iter().all(|r| *r == Conclusion::Skipped) {
+        Conclusion::Skipped
+    } else {
+        Conclusion::Success
+    }
+}
+
+/// Expand a matrix, evaluating `${{ fromJSON(...) }}` matrices
+fn combinations(
+    matrix: &Matrix,
+    contexts: &BTreeMap<String, Expr>,
+) -> Result<Vec<BTreeMap<String, Value>>, String> {
+    if let Some(combinations) = matrix.combinations() {
+        return Ok(combinations);
+    }
+    let Matrix::String(text) = matrix else {
+        unreachable!("only expressions have no combinations");
+    };
+    let json = match expr::embedded(text).as_slice() {
+        [only] if only.text(text) == text.trim() => match expr::parse(only.expr) {
+            Ok(Expr::Call(name, args))
+                if name.eq_ignore_ascii_case("fromJSON") && args.len() == 1 =>
+            {
+                condition::interpolate(&format!("${{{{ {} }}}}", args[0]), contexts)?
+            }
+            _ => return Err(format!("can't expand the matrix `{text}` locally")),
+        },
+        _ => return Err(format!("can't expand the matrix `{text}` locally")),
+    };
+    let matrix: Matrix =
+        serde_json::from_str(&json).map_err(|e| format!("invalid matrix `{json}`: {e}"))?;
+    matrix
+        .combinations()
+        .ok_or_else(|| format!("invalid matrix `{json}`"))
+}
+
+/// The job's `name`, or its id followed by the matrix values
+fn instance_name(
+    id: &str,
+    job: &Job,
+    matrix: &BTreeMap<String, Value>,
+    contexts: &BTreeMap<String, Expr>,
+) -> String {
+    if let Some(name) = job.name.as_ref().and_then(|n| scalar(n, contexts).ok()) {
+        return name;
+    }
+    if matrix.is_empty() {
+        return id.to_string();
+    }
+    let values: Vec<_> = matrix
+        .values()
+        .map(|value| match value {
+            Value::String(s) => s.clone(),
+            other => serde_json::to_string(other).unwrap_or_default(),
+        })
+        .collect();
+    format!("{id} ({})", values.join(", "))
+}
+
+/// A scalar that may contain expressions, e.g. a concurrency group
+fn scalar(value: &Value, contexts: &BTreeMap<String, Expr>) -> Result<String, String> {
+    match value {
+        Value::String(s) => condition::interpolate(s, contexts),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Number(n) => Ok(n.to_string()),
+        other => Err(format!("expected a string, found {other:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+
+    fn run(yaml: &str) -> WorkflowRun {
+        let workflow: Workflow = serde_yml::from_str(yaml).unwrap();
+        Runner::builder()
+            .event(Event::builder().name("push").build())
+            .parallel(8)
+            .build()
+            .run_workflow(&workflow)
+            .unwrap()
+    }
+
+    /// The instances of each job in the order of the workflow
+    fn results(run: &WorkflowRun) -> Vec<String> {
+        let mut jobs: Vec<_> = run.jobs.iter().collect();
+        jobs.sort_by(|a, b| a.name.cmp(&b.name));
+        jobs.iter()
+            .map(|job| {
+                let stdout: Vec<_> = job.steps.iter().map(|s| s.stdout.trim_end()).collect();
+                format!("{} {}: {}", job.name, job.result, stdout.join(" "))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn needs_and_matrix() {
+        let run = run(r#"name: ci
+on: push
+jobs:
+  version:
+    runs-on: ubuntu-latest
+    outputs:
+      version: ${{ steps.v.outputs.version }}
+      targets: ${{ steps.v.outputs.targets }}
+    steps:
+      - id: v
+        run: |
+          echo "version=1.2.3" >> "$GITHUB_OUTPUT"
+          echo 'targets={"target": ["x86", "arm"]}' >> "$GITHUB_OUTPUT"
+  build:
+    needs: version
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        os: [linux, macos]
+        release: [true]
+    outputs:
+      built: ${{ steps.b.outputs.built }}
+    steps:
+      - id: b
+        run: |
+          echo "${{ matrix.os }} ${{ needs.version.outputs.version }} ${{ strategy.job-index }}/${{ strategy.job-total }}"
+          echo "built=${{ matrix.os }}" >> "$GITHUB_OUTPUT"
+  cross:
+    needs: version
+    strategy:
+      matrix: ${{ fromJSON(needs.version.outputs.targets) }}
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ matrix.target }}
+  publish:
+    needs: [build, cross]
+    if: needs.build.result == 'success'
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ needs.build.outputs.built != '' }}
+  never:
+    needs: publish
+    if: github.event_name == 'pull_request'
+    runs-on: ubuntu-latest
+  after-never:
+    needs: never
+    runs-on: ubuntu-latest
+  cleanup:
+    needs: never
+    if: always()
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ needs.never.result }}
+"#);
+        insta::assert_debug_snapshot!(results(&run));
+        assert_eq!(run.result, Conclusion::Success);
+        let publish = run.jobs.iter().position(|j| j.job == "publish").unwrap();
+        assert!(run.jobs[..publish].iter().all(|j| j.job != "never"));
+        assert!(run.to_markdown().contains("| `publish` | success |"));
+    }
+
+    #[test]
+    fn fail_fast() {
+        let start = Instant::now();
+        let run = run(r#"name: ci
+on: push
+jobs:
+  serial:
+    runs-on: ubuntu-latest
+    strategy:
+      max-parallel: 1
+      matrix:
+        n: [1, 2, 3]
+    steps:
+      - run: echo ${{ matrix.n }} && test ${{ matrix.n }} != 2
+  parallel:
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        n: [1, 2]
+    steps:
+      - run: test ${{ matrix.n }} = 1 && sleep 5
+  no-fail-fast:
+    runs-on: ubuntu-latest
+    strategy:
+      fail-fast: false
+      max-parallel: 1
+      matrix:
+        n: [1, 2]
+    steps:
+      - run: echo ${{ matrix.n }} && test ${{ matrix.n }} = 2
+  report:
+    needs: [serial, parallel]
+    if: failure()
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ needs.serial.result }} ${{ needs.parallel.result }}
+"#);
+        assert!(start.elapsed() < Duration::from_secs(4));
+        insta::assert_debug_snapshot!(results(&run));
+        assert_eq!(run.result, Conclusion::Failure);
+    }
+
+    #[test]
+    fn job_continue_on_error() {
+        let run = run(r#"name: ci
+on: push
+jobs:
+  test:
+    runs-on: ubuntu-latest
+    continue-on-error: ${{ matrix.experimental }}
+    strategy:
+      max-parallel: 1
+      matrix:
+        n: [1, 2]
+        experimental: [false]
+        include:
+          - n: 0
+            experimental: true
+    steps:
+      - run: echo ${{ matrix.n }} && test ${{ matrix.n }} != 0
+  report:
+    needs: test
+    runs-on: ubuntu-latest
+    steps:
+      - run: echo ${{ needs.test.result }}
+"#);
+        let results = results(&run);
+        assert_eq!(results[0], "report success: success");
+        assert!(results[1..].iter().any(|r| r.ends_with("failure: 0")));
+        assert_eq!(
+            run.job("test")
+                .filter(|r| r.result == Conclusion::Success)
+                .count(),
+            2
+        );
+        assert_eq!(run.result, Conclusion::Success);
+    }
+
+    #[test]
+    fn concurrency() {
+        let run = run(r#"name: deploy
+on: push
+concurrency:
+  group: ${{ github.event_name }}
+jobs:
+  first:
+    runs-on: ubuntu-latest
+    concurrency:
+      group: deploy
+    steps:
+      - run: sleep 0.5 && echo first
+  second:
+    runs-on: ubuntu-latest
+    concurrency:
+      group: deploy
+    steps:
+      - run: echo second
+  third:
+    runs-on: ubuntu-latest
+    concurrency:
+      group: deploy
+    steps:
+      - run: echo third
+  build:
+    runs-on: ubuntu-latest
+    concurrency:
+      group: build-${{ matrix.n }}
+      cancel-in-progress: true
+    strategy:
+      matrix:
+        n: [1, 1]
+    steps:
+      - run: sleep 0.5 && echo ${{ strategy.job-index }}
+"#);
+        insta::assert_debug_snapshot!(results(&run));
+        assert_eq!(run.concurrency.as_deref(), Some("push"));
+    }
+
+    #[test]
+    fn invalid_needs() {
+        let workflow: Workflow = serde_yml::from_str(
+            "name: ci\njobs:\n  a: {needs: b}\n  b: {needs: a}\n  c: {needs: d}\n",
+        )
+        .unwrap();
+        let runner = Runner::builder()
+            .event(Event::builder().name("push").build())
+            .build();
+        let err = runner.run_workflow(&workflow).unwrap_err();
+        assert_eq!(err.to_string(), "job `a` depends on itself through `needs`");
+    }
+}
@@ -0,0 +1,778 @@
+//! Built in stand-ins for `actions/upload-artifact`,
+//! `actions/download-artifact` and `actions/cache`, backed by a directory
+//!
+//! Artifacts are kept per run and caches are shared by every run with the
+//! same store.
+
+use super::{glob::Glob, FileCommands, StepDefaults, StepRun};
+use crate::uses::RepositoryRef;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Component, Path, PathBuf},
+    sync::Mutex,
+};
+
+/// How long artifacts are kept when `retention-days` isn't set, and the
+/// longest it may be
+pub const DEFAULT_RETENTION_DAYS: u32 = 90;
+
+/// An uploaded artifact
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    pub id: usize,
+    pub name: String,
+    /// The id of the job that uploaded it
+    pub job: String,
+    pub files: usize,
+    /// The total size of the files in bytes
+    pub size: u64,
+    pub retention_days: u32,
+    /// Where the files are kept, `None` once the run's temporary store is
+    /// removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// The artifacts of a run and the caches it shares with other runs
+#[derive(Debug)]
+pub(super) struct Store {
+    root: PathBuf,
+    artifacts_dir: PathBuf,
+    cache_dir: PathBuf,
+    artifacts: Mutex<Vec<Artifact>>,
+    /// Whether the store is removed when dropped
+    temporary: bool,
+}
+
+impl Drop for Store {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+}
+
+/// A cache `actions/cache` saves after the job succeeds
+#[derive(Debug, Clone)]
+pub(super) struct CacheSave {
+    key: String,
+    /// The paths as written, which caches are matched by
+    paths: Vec<String>,
+    resolved: Vec<PathBuf>,
+}
+
+impl Store {
+    /// A store in `root`, with a new directory for the run's artifacts
+    pub(super) fn new(root: &Path) -> io::Result<Self> {
+        let cache_dir = root.join("cache");
+        fs::create_dir_all(&cache_dir)?;
+        let runs = root.join("artifacts");
+        fs::create_dir_all(&runs)?;
+        let artifacts_dir = numbered(&runs)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            artifacts_dir,
+            cache_dir,
+            artifacts: Mutex::default(),
+            temporary: false,
+        })
+    }
+
+    /// A store in `root` removed, with its caches and artifacts, when it's
+    /// dropped
+    pub(super) fn temporary(root: &Path) -> io::Result<Self> {
+        let mut store = Self::new(root)?;
+        store.temporary = true;
+        Ok(store)
+    }
+
+    pub(super) fn is_temporary(&self) -> bool {
+        self.temporary
+    }
+
+    /// The artifacts uploaded so far, in the order they were
+    pub(super) fn artifacts(&self) -> Vec<Artifact> {
+        self.artifacts.lock().expect("not poisoned").clone()
+    }
+
+    /// The directory holding the files of `artifact`
+    fn artifact_dir(&self, artifact: &Artifact) -> PathBuf {
+        self.artifacts_dir.join(&artifact.name)
+    }
+
+    fn upload(
+        &self,
+        name: &str,
+        root: &Path,
+        files: &[PathBuf],
+        retention_days: u32,
+        overwrite: bool,
+        job: &str,
+    ) -> Result<Artifact, String> {
+        let mut artifacts = self.artifacts.lock().expect("not poisoned");
+        if let Some(idx) = artifacts.iter().position(|a| a.name == name) {
+            if !overwrite {
+                return Err(format!("an artifact named `{name}` already exists"));
+            }
+            let old = artifacts.remove(idx);
+            fs::remove_dir_all(self.artifact_dir(&old)).map_err(|e| e.to_string())?;
+        }
+        let path = self.artifacts_dir.join(name);
+        let mut size = 0;
+        for file in files {
+            let to = path.join(file);
+            if let Some(parent) = to.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            size +=
+                fs::copy(root.join(file), &to).map_err(|e| format!("{}: {e}", file.display()))?;
+        }
+        let id = artifacts.iter().map(|a| a.id).max().unwrap_or_default() + 1;
+        let artifact = Artifact {
+            id,
+            name: name.to_string(),
+            job: job.to_string(),
+            files: files.len(),
+            size,
+            retention_days,
+            path: Some(path),
+        };
+        artifacts.push(artifact.clone());
+        Ok(artifact)
+    }
+
+    /// The most recent cache saved for `paths` under the first of `keys` it
+    /// matches exactly or else by prefix, and the key it was saved under
+    fn lookup(&self, paths: &[String], keys: &[&str]) -> io::Result<Option<(PathBuf, String)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let dir = entry?.path();
+            let Some(n) = dir
+                .file_name()
+                .and_then(|n| n.to_str()?.parse::<usize>().ok())
+            else {
+                continue;
+            };
+            // the entry is complete once its key is written
+            let Ok(meta) = fs::read_to_string(dir.join("key")) else {
+                continue;
+            };
+            let (key, saved_paths) = meta.split_once('\n').unwrap_or((&meta, ""));
+            if saved_paths.lines().eq(paths) {
+                entries.push((n, key.to_string(), dir));
+            }
+        }
+        entries.sort_by_key(|(n, ..)| std::cmp::Reverse(*n));
+        for key in keys {
+            let found = entries
+                .iter()
+                .find(|(_, saved, _)| saved == key)
+                .or_else(|| entries.iter().find(|(_, saved, _)| saved.starts_with(key)));
+            if let Some((_, saved, dir)) = found {
+                return Ok(Some((dir.clone(), saved.clone())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Save `paths` under `key` unless a cache already has it, returns if
+    /// it was saved
+    pub(super) fn save(&self, save: &CacheSave) -> io::Result<bool> {
+        let CacheSave {
+            key,
+            paths,
+            resolved,
+        } = save;
+        let exists = self
+            .lookup(paths, &[key])?
+            .is_some_and(|(_, saved)| saved == *key);
+        if exists || !resolved.iter().any(|path| path.exists()) {
+            return Ok(false);
+        }
+        let dir = numbered(&self.cache_dir)?;
+        for (idx, path) in resolved.iter().enumerate() {
+            if path.exists() {
+                copy(path, &dir.join(idx.to_string()))?;
+            }
+        }
+        fs::write(dir.join("key"), format!("{key}\n{}", paths.join("\n")))?;
+        Ok(true)
+    }
+}
+
+/// Run the built in stand-in for `action` if there is one
+pub(super) fn builtin(
+    action: &RepositoryRef,
+    inputs: &BTreeMap<String, String>,
+    defaults: &StepDefaults<'_>,
+    run: &mut StepRun,
+) -> Option<Result<FileCommands, String>> {
+    let stand_in = match (action.repository().as_str(), action.path.as_deref()) {
+        ("actions/upload-artifact", None) => upload,
+        ("actions/download-artifact", None) => download,
+        ("actions/cache", None) => cache,
+        ("actions/cache", Some("restore")) => restore,
+        ("actions/cache", Some("save")) => save,
+        _ => return None,
+    };
+    let input = |name: &str| inputs.get(name).map_or("", |value| value.trim());
+    Some(stand_in(&input, defaults, run))
+}
+
+type Input<'a> = dyn Fn(&str) -> &'a str + 'a;
+
+fn upload(
+    input: &Input<'_>,
+    defaults: &StepDefaults<'_>,
+    run: &mut StepRun,
+) -> Result<FileCommands, String> {
+    let name = match input("name") {
+        "" => "artifact",
+        name => name,
+    };
+    if let Some(c) = name.chars().find(|c| "\"<>|*?:\\/\r\n".contains(*c)) {
+        return Err(format!("the artifact name `{name}` can't contain {c:?}"));
+    }
+    let retention_days = match input("retention-days") {
+        "" | "0" => DEFAULT_RETENTION_DAYS,
+        days => days
+            .parse()
+            .ok()
+            .filter(|days| (1..=DEFAULT_RETENTION_DAYS).contains(days))
+            .ok_or_else(|| {
+                format!("`retention-days` must be between 1 and {DEFAULT_RETENTION_DAYS}, found `{days}`")
+            })?,
+    };
+    let patterns: Vec<_> = lines(input("path")).collect();
+    if patterns.is_empty() {
+        return Err("the `path` input is required".into());
+    }
+    let (root, files) = search(defaults, &patterns, input("include-hidden-files") == "true")
+        .map_err(|e| e.to_string())?;
+    if files.is_empty() {
+        let message = format!(
+            "no files were found with the path `{}`",
+            patterns.join(", ")
+        );
+        return match input("if-no-files-found") {
+            "error" => Err(message),
+            "ignore" => Ok(FileCommands::default()),
+            _ => {
+                run.message = Some(format!("{message}, no artifact was uploaded"));
+                Ok(FileCommands::default())
+            }
+        };
+    }
+    let artifact = defaults.store.upload(
+        name,
+        &root,
+        &files,
+        retention_days,
+        input("overwrite") == "true",
+        defaults.job,
+    )?;
+    let outputs = BTreeMap::from([
+        ("artifact-id".to_string(), artifact.id.to_string()),
+        (
+            "artifact-url".to_string(),
+            format!(
+                "file://{}",
+                defaults.store.artifact_dir(&artifact).display()
+            ),
+        ),
+    ]);
+    Ok(FileCommands {
+        outputs,
+        ..Default::default()
+    })
+}
+
+fn download(
+    input: &Input<'_>,
+    defaults: &StepDefaults<'_>,
+    _: &mut StepRun,
+) -> Result<FileCommands, String> {
+    let path = resolve(defaults, input("path"));
+    let artifacts = defaults.store.artifacts();
+    let ids: Vec<_> = input("artifact-ids")
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .collect();
+    let name = input("name");
+    let io = |e: io::Error| e.to_string();
+    if !name.is_empty() {
+        let artifact = artifacts
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| format!("no artifact named `{name}`"))?;
+        copy(&defaults.store.artifact_dir(artifact), &path).map_err(io)?;
+    } else {
+        let pattern = Some(input("pattern"))
+            .filter(|p| !p.is_empty())
+            .map(Glob::new);
+        let merge = input("merge-multiple") == "true";
+        let selected = artifacts.iter().filter(|a| {
+            pattern.as_ref().is_none_or(|p| p.matches(&a.name))
+                && (ids.is_empty() || ids.contains(&a.id.to_string().as_str()))
+        });
+        for artifact in selected {
+            let to = if merge {
+                path.clone()
+            } else {
+                path.join(&artifact.name)
+            };
+            copy(&defaults.store.artifact_dir(artifact), &to).map_err(io)?;
+        }
+    }
+    let outputs = BTreeMap::from([("download-path".to_string(), path.display().to_string())]);
+    Ok(FileCommands {
+        outputs,
+        ..Default::default()
+    })
+}
+
+/// `actions/cache`, which saves at the end of the job unless the key was hit
+fn cache(
+    input: &Input<'_>,
+    defaults: &StepDefaults<'_>,
+    run: &mut StepRun,
+) -> Result<FileCommands, String> {
+    let mut commands = restore(input, defaults, run)?;
+    if commands.outputs.get("cache-hit").map(String::as_str) != Some("true") {
+        commands.saves.push(cache_save(input, defaults)?);
+    }
+    commands.outputs.remove("cache-primary-key");
+    commands.outputs.remove("cache-matched-key");
+    Ok(commands)
+}
+
+/// `actions/cache/restore`
+fn restore(
+    input: &Input<'_>,
+    defaults: &StepDefaults<'_>,
+    _: &mut StepRun,
+) -> Result<FileCommands, String> {
+    let CacheSave {
+        key,
+        paths,
+        resolved,
+    } = cache_save(input, defaults)?;
+    let keys: Vec<_> = [key.as_str()]
+        .into_iter()
+        .chain(lines(input("restore-keys")))
+        .collect();
+    let io = |e: io::Error| e.to_string();
+    let found = defaults.store.lookup(&paths, &keys).map_err(io)?;
+    let mut outputs = BTreeMap::from([("cache-primary-key".to_string(), key.to_string())]);
+    match found {
+        Some((dir, matched)) => {
+            if input("lookup-only") != "true" {
+                for (idx, path) in resolved.iter().enumerate() {
+                    let saved = dir.join(idx.to_string());
+                    if saved.exists() {
+                        copy(&saved, path).map_err(io)?;
+                    }
+                }
+            }
+            outputs.insert("cache-hit".into(), (matched == key).to_string());
+            outputs.insert("cache-matched-key".into(), matched);
+        }
+        None if input("fail-on-cache-miss") == "true" => {
+            return Err(format!("no cache for the key `{key}`"));
+        }
+        None => {
+            outputs.insert("cache-hit".into(), String::new());
+        }
+    }
+    Ok(FileCommands {
+        outputs,
+        ..Default::default()
+    })
+}
+
+/// `actions/cache/save`
+fn save(
+    input: &Input<'_>,
+    defaults: &StepDefaults<'_>,
+    run: &mut StepRun,
+) -> Result<FileCommands, String> {
+    let save = cache_save(input, defaults)?;
+    if !defaults.store.save(&save).map_err(|e| e.to_string())? {
+        run.message = Some(format!("the cache `{}` wasn't saved", save.key));
+    }
+    Ok(FileCommands::default())
+}
+
+/// The `key` and `path` inputs of the cache actions
+fn cache_save(input: &Input<'_>, defaults: &StepDefaults<'_>) -> Result<CacheSave, String> {
+    let key = match input("key") {
+        "" => return Err("the `key` input is required".into()),
+        key if key.contains(',') => return Err(format!("the key `{key}` can't contain commas")),
+        key => key.to_string(),
+    };
+    let paths: Vec<_> = lines(input("path")).map(String::from).collect();
+    if paths.is_empty() {
+        return Err("the `path` input is required".into());
+    }
+    let resolved = paths.iter().map(|path| resolve(defaults, path)).collect();
+    Ok(CacheSave {
+        key,
+        paths,
+        resolved,
+    })
+}
+
+fn lines(input: &str) -> impl Iterator<Item = &str> {
+    input.lines().map(str::trim).filter(|line| !line.is_empty())
+}
+
+/// `path` relative to the workspace, or the job's home directory for `~/`
+fn resolve(defaults: &StepDefaults<'_>, path: &str) -> PathBuf {
+    let path = match path.strip_prefix('~') {
+        Some("") => defaults.home.to_path_buf(),
+        Some(rest) if rest.starts_with('/') => defaults.home.join(&rest[1..]),
+        _ => defaults.workspace.join(path),
+    };
+    let mut ret = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                ret.pop();
+            }
+            component => ret.push(component),
+        }
+    }
+    ret
+}
+
+/// The files matching the `path` patterns of `upload-artifact`, relative to
+/// the least common ancestor of the directories searched
+fn search(
+    defaults: &StepDefaults<'_>,
+    patterns: &[&str],
+    hidden: bool,
+) -> io::Result<(PathBuf, Vec<PathBuf>)> {
+    let (excludes, includes): (Vec<_>, Vec<_>) = patterns
+        .iter()
+        .map(|pattern| match pattern.strip_prefix('!') {
+            Some(pattern) => (true, split_glob(&resolve(defaults, pattern))),
+            None => (false, split_glob(&resolve(defaults, pattern))),
+        })
+        .partition(|(negated, _)| *negated);
+    let excluded = |file: &Path| {
+        excludes.iter().any(|(_, (root, glob))| match glob {
+            Some(glob) => file
+                .strip_prefix(root)
+                .is_ok_and(|rest| glob.matches(&rest.to_string_lossy())),
+            None => file.starts_with(root),
+        })
+    };
+
+    let mut files = BTreeSet::new();
+    let mut roots = Vec::new();
+    for (_, (root, glob)) in &includes {
+        let (root, glob) = match glob {
+            Some(glob) => (root.clone(), glob.clone()),
+            None if root.is_dir() => (root.clone(), Glob::new("**")),
+            None => {
+                if root.is_file() && !excluded(root) {
+                    files.insert(root.clone());
+                }
+                roots.push(root.parent().unwrap_or(root).to_path_buf());
+                continue;
+            }
+        };
+        walk(&root, &mut |file| {
+            let rest = file.strip_prefix(&root).unwrap_or(file);
+            let is_hidden = rest
+                .components()
+                .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+            if (hidden || !is_hidden) && glob.matches(&rest.to_string_lossy()) && !excluded(file) {
+                files.insert(file.to_path_buf());
+            }
+        })?;
+        roots.push(root);
+    }
+
+    let mut root = roots.first().cloned().unwrap_or_default();
+    for other in &roots {
+        while !other.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+    let files = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(&root).ok().map(Path::to_path_buf))
+        .collect();
+    Ok((root, files))
+}
+
+/// The directory before the first component with a wildcard and the glob
+/// for the rest, `None` for a literal path
+fn split_glob(path: &Path) -> (PathBuf, Option<Glob>) {
+    let mut root = PathBuf::new();
+    let mut components = path.components();
+    for component in components.by_ref() {
+        if Glob::is_glob(&component.as_os_str().to_string_lossy()) {
+            let mut rest = vec![component.as_os_str().to_string_lossy().into_owned()];
+            rest.extend(components.map(|c| c.as_os_str().to_string_lossy().into_owned()));
+            return (root, Some(Glob::new(&rest.join("/"))));
+        }
+        root.push(component);
+    }
+    (root, None)
+}
+
+/// Call `f` with every file below `dir`
+fn walk(dir: &Path, f: &mut dyn FnMut(&Path)) -> io::Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            walk(&path, f)?;
+        } else {
+            f(&path);
+        }
+    }
+    Ok(())
+}
+
+/// Copy the file or directory `from` to `to`, merging directories
+fn copy(from: &Path, to: &Path) -> io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+/// Create the next numbered directory in `dir`
+fn numbered(dir: &Path) -> io::Result<PathBuf> {
+    let mut n = fs::read_dir(dir)?.count() + 1;
+    loop {
+        let path = dir.join(n.to_string());
+        match fs::create_dir(&path) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::Event,
+        runner::{Conclusion, Runner, WorkflowRun},
+        Workflow,
+    };
+
+    fn run(store: &Path) -> WorkflowRun {
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: release
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    strategy:
+      matrix:
+        target: [linux, macos]
+    steps:
+      - id: cache
+        uses: actions/cache@v4
+        with:
+          path: target
+          key: build-${{ matrix.target }}-2
+          restore-keys: build-${{ matrix.target }}-
+      - run: |
+          echo "hit=${{ steps.cache.outputs.cache-hit }} $(cat target/built 2>/dev/null)"
+          mkdir -p target dist/${{ matrix.target }}/.hidden
+          echo ${{ matrix.target }} > target/built
+          echo ${{ matrix.target }} > dist/${{ matrix.target }}/app
+          echo debug > dist/${{ matrix.target }}/app.pdb
+          touch dist/${{ matrix.target }}/.hidden/file
+      - uses: actions/upload-artifact@v4
+        with:
+          name: dist-${{ matrix.target }}
+          retention-days: 7
+          path: |
+            dist/**
+            !**/*.pdb
+  release:
+    needs: build
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/download-artifact@v4
+        with:
+          pattern: dist-*
+          path: out
+          merge-multiple: true
+      - uses: actions/download-artifact@v4
+        with:
+          name: dist-linux
+      - run: find out linux -type f | sort
+      - uses: actions/download-artifact@v4
+        with:
+          name: missing
+"###,
+        )
+        .unwrap();
+        Runner::builder()
+            .event(Event::builder().name("push").build())
+            .parallel(8)
+            .store(store)
+            .build()
+            .run_workflow(&workflow)
+            .unwrap()
+    }
+
+    #[test]
+    fn artifacts_and_caches() {
+        let store = crate::testing::TempDir::new("store-test");
+        // a cache saved by an earlier run under an older key
+        let old = store.join("cache/1");
+        fs::create_dir_all(old.join("0")).unwrap();
+        fs::write(old.join("0/built"), "old\n").unwrap();
+        fs::write(old.join("key"), "build-linux-1\ntarget").unwrap();
+
+        let first = run(&store);
+        let second = run(&store);
+
+        let lines = |run: &WorkflowRun| {
+            let mut jobs: Vec<_> = run.jobs.iter().collect();
+            jobs.sort_by(|a, b| a.name.cmp(&b.name));
+            jobs.iter()
+                .flat_map(|job| {
+                    job.steps.iter().map(move |s| {
+                        let out = s.message.as_deref().unwrap_or(s.stdout.trim_end());
+                        format!("{} {} {}: {out}", job.name, s.conclusion, s.name)
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        insta::assert_debug_snapshot!((lines(&first), lines(&second)));
+        let artifacts: Vec<_> = first
+            .artifacts
+            .iter()
+            .map(|a| (a.name.as_str(), a.files, a.size, a.retention_days))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            artifacts,
+            [("dist-linux", 1, 6, 7), ("dist-macos", 1, 6, 7)]
+        );
+        assert_eq!(first.result, Conclusion::Failure);
+    }
+
+    #[test]
+    fn upload_globs() {
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: build
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: mkdir -p dist/sub && touch dist/app-1.tar dist/app-12.tar dist/sub/a dist/sub/b
+      - uses: actions/upload-artifact@v4
+        with:
+          name: dist
+          path: |
+            dist/app-?.tar
+            dist/s*
+            !dist/sub/[b-z]
+"###,
+        )
+        .unwrap();
+        let run = Runner::builder()
+            .event(Event::builder().name("push").build())
+            .build()
+            .run_workflow(&workflow)
+            .unwrap();
+        assert_eq!(run.result, Conclusion::Success);
+        assert_eq!(run.artifacts[0].files, 2);
+    }
+
+    #[test]
+    fn sandboxed_home() {
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: build
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    steps:
+      - run: mkdir -p ~/.grackle-tool && echo built > ~/.grackle-tool/bin && echo $HOME
+      - uses: actions/upload-artifact@v4
+        with:
+          name: tool
+          path: ~/.grackle-tool
+"###,
+        )
+        .unwrap();
+        let run = Runner::builder()
+            .event(Event::builder().name("push").build())
+            .build()
+            .run_workflow(&workflow)
+            .unwrap();
+        assert_eq!(run.result, Conclusion::Success);
+        assert_eq!(run.artifacts[0].files, 1);
+        let home = run.jobs[0].steps[0].stdout.trim_end();
+        assert_ne!(Some(home.into()), std::env::var_os("HOME"));
+        assert!(home.starts_with(&*std::env::temp_dir().to_string_lossy()));
+        assert!(!Path::new(home).exists());
+    }
+
+    #[test]
+    fn temporary_store() {
+        let workflow: Workflow = serde_yml::from_str(
+            r###"name: build
+on: push
+jobs:
+  build:
+    runs-on: ubuntu-latest
+    outputs:
+      url: ${{ steps.upload.outputs.artifact-url }}
+    steps:
+      - run: echo built > app
+      - id: upload
+        uses: actions/upload-artifact@v4
+        with:
+          name: app
+          path: app
+"###,
+        )
+        .unwrap();
+        let runner = Runner::builder()
+            .event(Event::builder().name("push").build())
+            .build();
+        let run = runner.run_workflow(&workflow).unwrap();
+        assert_eq!(run.result, Conclusion::Success);
+        assert_eq!(run.artifacts.len(), 1);
+        assert_eq!(run.artifacts[0].path, None);
+        let url = &run.job("build").next().unwrap().outputs["url"];
+        assert!(!Path::new(url.strip_prefix("file://").unwrap()).exists());
+
+        let run = runner.run_job(&workflow, "build").unwrap();
+        assert_eq!(run.result, Conclusion::Success);
+        assert!(!Path::new(run.outputs["url"].strip_prefix("file://").unwrap()).exists());
+    }
+}
@@ -0,0 +1,333 @@
+//! Serialize [`Diagnostic`]s as a [SARIF 2.1.0] log, the format GitHub code
+//! scanning ingests
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use crate::{
+    lint::{Diagnostic, Registry, Severity},
+    span::{Source, Span},
+};
+use serde::Serialize;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// A SARIF log with a single run of grackle
+#[derive(Debug, Clone, Serialize)]
+pub struct Log {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub tool: Tool,
+    pub artifacts: Vec<Artifact>,
+    pub results: Vec<SarifResult>,
+    /// Columns are counted in characters, matching [`Source::position`]
+    pub column_kind: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub name: &'static str,
+    pub version: &'static str,
+    pub information_uri: &'static str,
+    pub rules: Vec<ReportingDescriptor>,
+}
+
+/// The metadata of a rule
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportingDescriptor {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_description: Option<Message>,
+    pub default_configuration: Configuration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Configuration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Artifact {
+    pub location: ArtifactLocation,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactLocation {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri_base_id: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: Message,
+    pub locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixes: Vec<SarifFix>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+/// Both the line/column and character offset forms of a region, consumers
+/// may use either
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub char_offset: usize,
+    pub char_length: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<Message>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifFix {
+    pub description: Message,
+    pub artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactChange {
+    pub artifact_location: ArtifactLocation,
+    pub replacements: Vec<Replacement>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replacement {
+    pub deleted_region: Region,
+    pub inserted_content: Message,
+}
+
+impl Log {
+    /// An empty log describing every rule in `registry`
+    pub fn new(registry: &Registry) -> Self {
+        let rules = registry
+            .rules()
+            .map(|rule| ReportingDescriptor {
+                id: rule.id().to_string(),
+                short_description: Some(Message {
+                    text: rule.description().to_string(),
+                }),
+                default_configuration: Configuration {
+                    level: level(rule.default_severity()),
+                },
+            })
+            .collect();
+        Self {
+            schema: SCHEMA,
+            version: "2.1.0",
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: env!("CARGO_PKG_NAME"),
+                        version: env!("CARGO_PKG_VERSION"),
+                        information_uri: env!("CARGO_PKG_REPOSITORY"),
+                        rules,
+                    },
+                },
+                artifacts: Vec::new(),
+                results: Vec::new(),
+                column_kind: "unicodeCodePoints",
+            }],
+        }
+    }
+
+    /// Add the `diagnostics` reported against `source`
+    pub fn add(&mut self, source: &Source, diagnostics: &[Diagnostic]) {
+        let run = &mut self.runs[0];
+        let artifact = artifact_location(source);
+        if !run.artifacts.iter().any(|a| a.location == artifact) {
+            run.artifacts.push(Artifact {
+                location: artifact.clone(),
+            });
+        }
+        for diagnostic in diagnostics {
+            let rules = &mut run.tool.driver.rules;
+            let rule_index = match rules.iter().position(|r| r.id == diagnostic.rule) {
+                Some(idx) => idx,
+                None => {
+                    // diagnostics from outside of the registry, e.g. parse errors
+                    rules.push(ReportingDescriptor {
+                        id: diagnostic.rule.clone(),
+                        short_description: None,
+                        default_configuration: Configuration {
+                            level: level(diagnostic.severity),
+                        },
+                    });
+                    rules.len() - 1
+                }
+            };
+            let related_locations = diagnostic
+                .notes
+                .iter()
+                .filter_map(|note| Some((note.span?, &note.message)))
+                .enumerate()
+                .map(|(idx, (span, message))| Location {
+                    id: Some(idx + 1),
+                    physical_location: PhysicalLocation {
+                        artifact_location: artifact.clone(),
+                        region: region(source, span, true),
+                    },
+                    message: Some(Message {
+                        text: message.clone(),
+                    }),
+                })
+                .collect();
+            // notes without a location are only available in the message
+            let mut text = diagnostic.message.clone();
+            for note in diagnostic.notes.iter().filter(|n| n.span.is_none()) {
+                text.push_str("\n\nnote: ");
+                text.push_str(&note.message);
+            }
+            let fixes = diagnostic
+                .fix
+                .iter()
+                .map(|fix| SarifFix {
+                    description: Message {
+                        text: format!("{} ({})", fix.message, fix.applicability),
+                    },
+                    artifact_changes: vec![ArtifactChange {
+                        artifact_location: artifact.clone(),
+                        replacements: fix
+                            .edits
+                            .iter()
+                            .map(|edit| Replacement {
+                                deleted_region: region(source, edit.span, false),
+                                inserted_content: Message {
+                                    text: edit.replacement.clone(),
+                                },
+                            })
+                            .collect(),
+                    }],
+                })
+                .collect();
+            run.results.push(SarifResult {
+                rule_id: diagnostic.rule.clone(),
+                rule_index,
+                level: level(diagnostic.severity),
+                message: Message { text },
+                locations: vec![Location {
+                    id: None,
+                    physical_location: PhysicalLocation {
+                        artifact_location: artifact.clone(),
+                        region: region(source, diagnostic.span, true),
+                    },
+                    message: None,
+                }],
+                related_locations,
+                fixes,
+            });
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("sarif log is valid json")
+    }
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+fn artifact_location(source: &Source) -> ArtifactLocation {
+    let uri = source.path().replace('\\', "/");
+    let relative = !uri.starts_with('/') && !uri.contains(':');
+    ArtifactLocation {
+        uri: uri.trim_start_matches("./").to_string(),
+        uri_base_id: relative.then_some("%SRCROOT%"),
+    }
+}
+
+fn region(source: &Source, span: Span, snippet: bool) -> Region {
+    let start = source.position(span.start);
+    let end = source.position(span.end);
+    Region {
+        start_line: start.line,
+        start_column: start.column,
+        end_line: end.line,
+        end_column: end.column,
+        char_offset: source.text()[..span.start].chars().count(),
+        char_length: source.slice(span).chars().count(),
+        snippet: snippet.then(|| Message {
+            text: source.slice(span).to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lint::{Config, Linter};
+
+    #[test]
+    fn sarif_log() {
+        let source = Source::new(
+            ".github/workflows/ci.yml",
+            r#"name: ci
+on: pull_request_target
+jobs:
+  build:
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          ref: ${{ github.event.pull_request.head.sha }}
+      - run: echo "${{ github.event.pull_request.title }}"
+"#,
+        );
+        let linter = Linter::new(Registry::builtin(), Config::default());
+        let diagnostics = linter.lint(&source).unwrap();
+        let mut log = Log::new(linter.registry());
+        log.add(&source, &diagnostics);
+        insta::assert_json_snapshot!(log);
+    }
+}
@@ -0,0 +1,587 @@
+//! Source text for a workflow file along with the byte spans of its nodes.
+//!
+//! `serde_yml` doesn't expose locations for the values it deserializes so
+//! nodes are located after the fact by walking the indentation of the block
+//! style yaml that workflows are almost always written in. Flow style
+//! collections (`[a, b]`, `{a: b}`) can be located but not descended into.
+
+use serde::Serialize;
+use std::{fmt, path::Path};
+
+/// A half open range of byte offsets into a [`Source`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn empty(at: usize) -> Self {
+        Self { start: at, end: at }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    pub fn overlaps(&self, other: &Span) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// A 1 based line and column, the column is counted in characters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A single step in a [`YamlPath`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The location of a node in a yaml document, e.g. `jobs.build.steps[1].run`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct YamlPath(Vec<Segment>);
+
+impl YamlPath {
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// The path to the job named `job`
+    pub fn job(job: impl ToString) -> Self {
+        Self::root().key("jobs").key(job)
+    }
+
+    /// The path to the step at `index` in the job named `job`
+    pub fn step(job: impl ToString, index: usize) -> Self {
+        Self::job(job).key("steps").index(index)
+    }
+
+    pub fn key(mut self, key: impl ToString) -> Self {
+        self.0.push(Segment::Key(key.to_string()));
+        self
+    }
+
+    pub fn index(mut self, index: usize) -> Self {
+        self.0.push(Segment::Index(index));
+        self
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        &self.0
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        let (_, rest) = self.0.split_last()?;
+        Some(Self(rest.to_vec()))
+    }
+}
+
+impl fmt::Display for YamlPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, seg) in self.0.iter().enumerate() {
+            match seg {
+                Segment::Key(key) if i == 0 => write!(f, "{key}")?,
+                Segment::Key(key) => write!(f, ".{key}")?,
+                Segment::Index(idx) => write!(f, "[{idx}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A located yaml node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Node {
+    /// The span of the key when this node is a mapping value
+    pub key: Option<Span>,
+    /// The span of the value, for block scalars this includes the `|` or `>`
+    /// indicator
+    pub value: Span,
+    /// The column that the node's key (or sequence item dash) starts at
+    pub indent: usize,
+}
+
+/// The text of a workflow file and the path it was read from
+#[derive(Debug, Clone)]
+pub struct Source {
+    path: String,
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    pub fn new(path: impl Into<String>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self {
+            path: path.into(),
+            text,
+            line_starts,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        Ok(Self::new(path.display().to_string(), text))
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn slice(&self, span: Span) -> &str {
+        &self.text[span.start..span.end]
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The 1 based line and column of `offset`
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.line_index(offset);
+        let column = self.text[self.line_starts[line]..offset].chars().count() + 1;
+        Position {
+            line: line + 1,
+            column,
+        }
+    }
+
+    /// The span of the 1 based `line` not including its line ending
+    pub fn line_span(&self, line: usize) -> Span {
+        let start = self.line_starts[line - 1];
+        let end = self
+            .line_starts
+            .get(line)
+            .map(|next| next - 1)
+            .unwrap_or(self.text.len());
+        let end = if self.text[start..end].ends_with('\r') {
+            end - 1
+        } else {
+            end
+        };
+        Span::new(start, end)
+    }
+
+    pub fn line_text(&self, line: usize) -> &str {
+        self.slice(self.line_span(line))
+    }
+
+    /// The comments in the file as their 1 based line and text from the `#`
+    /// on, leaving out anything that looks like a comment inside of a quoted
+    /// string or block scalar
+    pub fn comments(&self) -> Vec<(usize, &str)> {
+        let scalar_lines: Vec<_> = tokenize(&self.text)
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::Scalar)
+            .map(|t| self.position(t.start).line)
+            .collect();
+        (1..self.line_count() + 1)
+            .filter(|line| !scalar_lines.contains(line))
+            .filter_map(|line| {
+                let text = self.line_text(line);
+                let trimmed = text.trim_start();
+                if trimmed.starts_with('#') {
+                    return Some((line, trimmed));
+                }
+                comment_start(text).map(|idx| (line, &text[idx..]))
+            })
+            .collect()
+    }
+
+    fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(idx) => idx,
+            Err(idx) => idx - 1,
+        }
+    }
+
+    /// Find the node at `path`
+    pub fn locate(&self, path: &YamlPath) -> Option<Node> {
+        let tokens = tokenize(&self.text);
+        let mut region = &tokens[..];
+        let mut node = Node {
+            key: None,
+            value: Span::new(
+                region.first().map(|t| t.start).unwrap_or_default(),
+                region.last().map(|t| t.end).unwrap_or_default(),
+            ),
+            indent: 0,
+        };
+        for seg in path.segments() {
+            let (found, children) = match seg {
+                Segment::Key(key) => find_key(&self.text, region, key)?,
+                Segment::Index(idx) => find_index(region, *idx)?,
+            };
+            node = found;
+            region = children;
+        }
+        Some(node)
+    }
+
+    /// The span of the value at `path`, falling back to the nearest ancestor
+    /// that could be located
+    pub fn span_of(&self, path: &YamlPath) -> Span {
+        self.locate_nearest(path)
+            .map(|node| node.value)
+            .unwrap_or_default()
+    }
+
+    /// The span of the key at `path`, or its value for sequence items,
+    /// falling back to the nearest ancestor that could be located
+    pub fn key_span_of(&self, path: &YamlPath) -> Span {
+        self.locate_nearest(path)
+            .map(|node| node.key.unwrap_or(node.value))
+            .unwrap_or_default()
+    }
+
+    fn locate_nearest(&self, path: &YamlPath) -> Option<Node> {
+        let mut path = path.clone();
+        loop {
+            if let Some(node) = self.locate(&path) {
+                return Some(node);
+            }
+            path = path.parent()?;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// The `-` that starts a sequence item
+    Dash,
+    /// Everything after the indentation (and any dashes) on a line
+    Line,
+    /// A line belonging to a block scalar
+    Scalar,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    indent: usize,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    // the indent of the key that started a block scalar, any following lines
+    // indented further belong to that scalar
+    let mut scalar_parent: Option<usize> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start_matches(' ');
+        let indent = line.len() - trimmed.len();
+        let content_end = line_start + line.trim_end().len();
+        if let Some(parent) = scalar_parent {
+            if trimmed.is_empty() || indent > parent {
+                if !trimmed.is_empty() {
+                    tokens.push(Token {
+                        kind: TokenKind::Scalar,
+                        indent,
+                        start: line_start + indent,
+                        end: content_end,
+                    });
+                }
+                continue;
+            }
+            scalar_parent = None;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+        let mut col = indent;
+        let mut rest = trimmed;
+        while rest == "-" || rest.starts_with("- ") {
+            tokens.push(Token {
+                kind: TokenKind::Dash,
+                indent: col,
+                start: line_start + col,
+                end: line_start + col + 1,
+            });
+            let after = rest[1..].trim_start_matches(' ');
+            col += rest.len() - after.len();
+            rest = after;
+        }
+        if rest.is_empty() {
+            continue;
+        }
+        let value = strip_comment(rest);
+        if is_block_scalar_indicator(inline_value(value).unwrap_or(value)) {
+            scalar_parent = Some(col);
+        }
+        tokens.push(Token {
+            kind: TokenKind::Line,
+            indent: col,
+            start: line_start + col,
+            end: line_start + col + value.len(),
+        });
+    }
+    tokens
+}
+
+fn is_block_scalar_indicator(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some('|' | '>'))
+        && chars.all(|c| matches!(c, '-' | '+') || c.is_ascii_digit())
+}
+
+/// Remove a trailing ` # comment` that isn't inside of quotes
+fn strip_comment(line: &str) -> &str {
+    match comment_start(line) {
+        Some(idx) => line[..idx].trim_end(),
+        None => line.trim_end(),
+    }
+}
+
+/// The offset of the `#` starting a trailing comment that isn't inside of
+/// quotes
+fn comment_start(line: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut prev = ' ';
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') if prev == ' ' || prev == ':' => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if prev == ' ' || prev == '\t' => return Some(idx),
+            _ => {}
+        }
+        prev = c;
+    }
+    None
+}
+
+/// Split a `key: value` line into its unquoted key and the byte offset of
+/// the text after the `:`
+fn split_key(line: &str) -> Option<(&str, usize)> {
+    if let Some(quote @ ('"' | '\'')) = line.chars().next() {
+        let close = line[1..].find(quote)? + 1;
+        let after = &line[close + 1..];
+        if after.starts_with(':') && (after.len() == 1 || after[1..].starts_with(' ')) {
+            return Some((&line[1..close], close + 2));
+        }
+        return None;
+    }
+    let colon = line
+        .match_indices(':')
+        .map(|(idx, _)| idx)
+        .find(|idx| line[idx + 1..].is_empty() || line[idx + 1..].starts_with(' '))?;
+    Some((line[..colon].trim_end(), colon + 1))
+}
+
+fn inline_value(line: &str) -> Option<&str> {
+    let (_, after) = split_key(line)?;
+    Some(line[after..].trim())
+}
+
+/// The tokens that belong to the node starting at `tokens[idx]`: anything
+/// indented further than it along with, for mapping values, a sequence
+/// written at the same indent as its key
+fn children(tokens: &[Token], idx: usize) -> &[Token] {
+    let parent = tokens[idx];
+    let rest = &tokens[idx + 1..];
+    let same_indent_seq = parent.kind == TokenKind::Line
+        && rest
+            .first()
+            .map(|t| t.kind == TokenKind::Dash && t.indent == parent.indent)
+            .unwrap_or(false);
+    let len = rest
+        .iter()
+        .position(|t| {
+            t.indent < parent.indent
+                || (t.indent == parent.indent && !(same_indent_seq && t.kind == TokenKind::Dash))
+        })
+        .unwrap_or(rest.len());
+    &rest[..len]
+}
+
+fn region_indent(region: &[Token]) -> Option<usize> {
+    region.first().map(|t| t.indent)
+}
+
+fn find_key<'a>(text: &str, region: &'a [Token], key: &str) -> Option<(Node, &'a [Token])> {
+    let indent = region_indent(region)?;
+    let (idx, tok) = region.iter().enumerate().find(|(_, t)| {
+        t.kind == TokenKind::Line
+            && t.indent == indent
+            && split_key(&text[t.start..t.end]).map(|(k, _)| k == key) == Some(true)
+    })?;
+    let line = &text[tok.start..tok.end];
+    let (k, after) = split_key(line)?;
+    let key_start = if line.starts_with(['"', '\'']) {
+        tok.start + 1
+    } else {
+        tok.start
+    };
+    let key_span = Span::new(key_start, key_start + k.len());
+    let inline = &line[after..];
+    let inline_start = tok.start + after + (inline.len() - inline.trim_start().len());
+    let inline = inline.trim();
+    let children = children(region, idx);
+    let value = match (inline.is_empty(), children.last()) {
+        (true, Some(last)) => Span::new(children[0].start, last.end),
+        (true, None) => Span::empty(tok.end),
+        (false, Some(last)) if is_block_scalar_indicator(inline) => {
+            Span::new(inline_start, last.end)
+        }
+        (false, _) => Span::new(inline_start, inline_start + inline.len()),
+    };
+    let node = Node {
+        key: Some(key_span),
+        value,
+        indent: tok.indent,
+    };
+    let children = if inline.is_empty() { children } else { &[] };
+    Some((node, children))
+}
+
+fn find_index(region: &[Token], index: usize) -> Option<(Node, &[Token])> {
+    let indent = region_indent(region)?;
+    let (idx, tok) = region
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| t.kind == TokenKind::Dash && t.indent == indent)
+        .nth(index)?;
+    let children = children(region, idx);
+    let value = match (children.first(), children.last()) {
+        (Some(first), Some(last)) => Span::new(first.start, last.end),
+        _ => Span::empty(tok.end),
+    };
+    let node = Node {
+        key: None,
+        value,
+        indent: tok.indent,
+    };
+    Some((node, children))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKFLOW: &str = r#"name: ci
+"on":
+  push:
+    branches: [main]
+jobs:
+  build:
+    runs-on: ubuntu-latest # the default
+    needs:
+    - plan
+    steps:
+      - uses: actions/checkout@v4
+        with:
+          submodules: recursive
+      - name: test
+        run: |
+          # run the tests
+          cargo test
+  plan:
+    runs-on: ubuntu-latest
+"#;
+
+    fn located(path: YamlPath) -> String {
+        let source = Source::new("ci.yml", WORKFLOW);
+        source
+            .slice(source.locate(&path).unwrap().value)
+            .to_string()
+    }
+
+    #[test]
+    fn locate_nodes() {
+        assert_eq!(located(YamlPath::root().key("name")), "ci");
+        assert_eq!(
+            located(YamlPath::root().key("on").key("push").key("branches")),
+            "[main]"
+        );
+        assert_eq!(
+            located(YamlPath::job("build").key("runs-on")),
+            "ubuntu-latest"
+        );
+        assert_eq!(
+            located(YamlPath::job("build").key("needs").index(0)),
+            "plan"
+        );
+        assert_eq!(
+            located(YamlPath::step("build", 0).key("with").key("submodules")),
+            "recursive"
+        );
+        assert_eq!(
+            located(YamlPath::step("build", 1).key("run")),
+            "|\n          # run the tests\n          cargo test"
+        );
+        assert_eq!(
+            located(YamlPath::job("plan").key("runs-on")),
+            "ubuntu-latest"
+        );
+        let source = Source::new("ci.yml", WORKFLOW);
+        assert!(source.locate(&YamlPath::step("build", 2)).is_none());
+        assert_eq!(
+            source.span_of(&YamlPath::step("build", 0).key("with").key("missing")),
+            source
+                .locate(&YamlPath::step("build", 0).key("with"))
+                .unwrap()
+                .value
+        );
+    }
+
+    #[test]
+    fn positions() {
+        let source = Source::new("ci.yml", WORKFLOW);
+        let node = source
+            .locate(&YamlPath::job("build").key("runs-on"))
+            .unwrap();
+        assert_eq!(
+            source.position(node.value.start),
+            Position {
+                line: 7,
+                column: 14
+            }
+        );
+        assert_eq!(
+            source.line_text(7),
+            "    runs-on: ubuntu-latest # the default"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let source = Source::new(
+            "ci.yml",
+            format!("{WORKFLOW}  # trailing\nenv:\n  A: \"a # b\" # quoted\n"),
+        );
+        assert_eq!(
+            source.comments(),
+            [(7, "# the default"), (20, "# trailing"), (22, "# quoted")]
+        );
+    }
+}
@@ -0,0 +1,199 @@
+//! The references in `JobStep::uses` and `Job::uses`
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+
+/// A parsed `uses` value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Uses {
+    /// An action or reusable workflow in the same repository, `./path`
+    Local(String),
+    /// A docker image, `docker://image:tag`
+    Docker(String),
+    /// An action or reusable workflow in another repository,
+    /// `owner/repo/path@ref`
+    Repository(RepositoryRef),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepositoryRef {
+    pub owner: String,
+    pub repo: String,
+    /// The path inside of the repository, e.g. `init` for
+    /// `github/codeql-action/init@v3`
+    pub path: Option<String>,
+    pub git_ref: String,
+}
+
+/// What kind of git ref a [`RepositoryRef`] points to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum RefKind {
+    /// A full commit sha, the only immutable kind of ref
+    Sha,
+    Tag,
+    Branch,
+}
+
+impl RepositoryRef {
+    /// `owner/repo`
+    pub fn repository(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+
+    /// `owner/repo@ref` without any path, the key used in a [`Lockfile`]
+    pub fn key(&self) -> String {
+        format!("{}@{}", self.repository(), self.git_ref)
+    }
+
+    /// Classify the ref, without access to the repository version-like refs
+    /// (`v4`, `1.2.3`) are assumed to be tags and everything else a branch
+    pub fn kind(&self) -> RefKind {
+        let r = self.git_ref.as_str();
+        if r.len() == 40 && r.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return RefKind::Sha;
+        }
+        let version = r.strip_prefix('v').unwrap_or(r);
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        if !core.is_empty() && core.split('.').all(|part| part.parse::<u64>().is_ok()) {
+            RefKind::Tag
+        } else {
+            RefKind::Branch
+        }
+    }
+
+    /// The same action or workflow at `git_ref`
+    pub fn with_ref(&self, git_ref: impl Into<String>) -> Self {
+        Self {
+            git_ref: git_ref.into(),
+            ..self.clone()
+        }
+    }
+}
+
+impl fmt::Display for RepositoryRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.repo)?;
+        if let Some(path) = &self.path {
+            write!(f, "/{path}")?;
+        }
+        write!(f, "@{}", self.git_ref)
+    }
+}
+
+impl Uses {
+    /// If a docker reference is pinned to a digest
+    pub fn is_docker_digest(&self) -> bool {
+        matches!(self, Uses::Docker(image) if image.contains("@sha256:"))
+    }
+}
+
+impl fmt::Display for Uses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Uses::Local(path) => write!(f, "{path}"),
+            Uses::Docker(image) => write!(f, "docker://{image}"),
+            Uses::Repository(r) => write!(f, "{r}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidUses(pub String);
+
+impl fmt::Display for InvalidUses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a local path, docker image or `owner/repo@ref`",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InvalidUses {}
+
+impl FromStr for Uses {
+    type Err = InvalidUses;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with("./") {
+            return Ok(Uses::Local(s.to_string()));
+        }
+        if let Some(image) = s.strip_prefix("docker://") {
+            return Ok(Uses::Docker(image.to_string()));
+        }
+        let invalid = || InvalidUses(s.to_string());
+        let (name, git_ref) = s.split_once('@').ok_or_else(invalid)?;
+        let mut parts = name.splitn(3, '/');
+        let owner = parts.next().filter(|p| !p.is_empty()).ok_or_else(invalid)?;
+        let repo = parts.next().filter(|p| !p.is_empty()).ok_or_else(invalid)?;
+        if git_ref.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Uses::Repository(RepositoryRef {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            path: parts.next().map(ToString::to_string),
+            git_ref: git_ref.to_string(),
+        }))
+    }
+}
+
+/// The commit shas that tags and branches of actions resolved to, used to
+/// pin actions without network access
+///
+/// ```toml
+/// [actions]
+/// "actions/checkout@v4" = "b4ffde65f46336ab88eb53be808477a3936bae11"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub actions: BTreeMap<String, String>,
+}
+
+impl Lockfile {
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// The sha that `reference` was locked to
+    pub fn resolve(&self, reference: &RepositoryRef) -> Option<&str> {
+        self.actions.get(&reference.key()).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_classify() {
+        let Uses::Repository(r) = "github/codeql-action/init@v3".parse().unwrap() else {
+            panic!("expected a repository");
+        };
+        assert_eq!(r.repository(), "github/codeql-action");
+        assert_eq!(r.path.as_deref(), Some("init"));
+        assert_eq!(r.key(), "github/codeql-action@v3");
+        assert_eq!(r.kind(), RefKind::Tag);
+        assert_eq!(r.with_ref("main").kind(), RefKind::Branch);
+        assert_eq!(r.with_ref("1.2.3-rc.1").kind(), RefKind::Tag);
+        assert_eq!(
+            r.with_ref("b4ffde65f46336ab88eb53be808477a3936bae11")
+                .kind(),
+            RefKind::Sha
+        );
+        assert_eq!(
+            "./.github/actions/setup".parse::<Uses>().unwrap(),
+            Uses::Local("./.github/actions/setup".into())
+        );
+        assert!("docker://alpine@sha256:abc"
+            .parse::<Uses>()
+            .unwrap()
+            .is_docker_digest());
+        assert!("actions/checkout".parse::<Uses>().is_err());
+    }
+}